@@ -0,0 +1,131 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Contention benchmark of the handler registry: reader threads query `ctrlc::handlers()` while
+//! the main thread keeps registering and removing a handler. On Unix, also the dispatch of
+//! signals to a route of `ctrlc::on()` while another thread keeps adding and removing handlers.
+//!
+//! Run with `cargo bench --bench handlers`.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DURATION: Duration = Duration::from_secs(1);
+
+fn run(readers: usize, writer: bool) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let reads = Arc::new(AtomicU64::new(0));
+
+    let threads: Vec<_> = (0..readers)
+        .map(|_| {
+            let stop = stop.clone();
+            let reads = reads.clone();
+            thread::spawn(move || {
+                let mut count = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    std::hint::black_box(ctrlc::handlers());
+                    count += 1;
+                }
+                reads.fetch_add(count, Ordering::Relaxed);
+            })
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut writes = 0u64;
+    while start.elapsed() < DURATION {
+        if writer {
+            ctrlc::set_handler_once(|| {}).unwrap();
+            ctrlc::remove_all_handlers().unwrap();
+            writes += 1;
+        } else {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+    stop.store(true, Ordering::Relaxed);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "readers: {:2}, writer: {:5} => {:12.0} reads/s, {:8.0} writes/s",
+        readers,
+        writer,
+        reads.load(Ordering::Relaxed) as f64 / elapsed,
+        writes as f64 / elapsed,
+    );
+}
+
+#[cfg(unix)]
+fn run_dispatch(writer: bool) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let dispatched = Arc::new(AtomicU64::new(0));
+    let id = ctrlc::on(ctrlc::Signal::User1, {
+        let dispatched = dispatched.clone();
+        move || {
+            dispatched.fetch_add(1, Ordering::SeqCst);
+        }
+    })
+    .unwrap();
+
+    let churn = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let mut writes = 0u64;
+            while !stop.load(Ordering::Relaxed) {
+                if writer {
+                    let id = ctrlc::add_handler(|| {}).unwrap();
+                    ctrlc::remove(id);
+                    writes += 1;
+                } else {
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+            writes
+        }
+    });
+
+    let start = Instant::now();
+    while start.elapsed() < DURATION {
+        let before = dispatched.load(Ordering::SeqCst);
+        signal_hook::low_level::raise(signal_hook::consts::SIGUSR1).unwrap();
+        while dispatched.load(Ordering::SeqCst) == before {
+            std::hint::spin_loop();
+        }
+    }
+    stop.store(true, Ordering::Relaxed);
+    let writes = churn.join().unwrap();
+    ctrlc::remove(id);
+    ctrlc::remove_all_handlers().unwrap();
+
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "dispatch,   writer: {:5} => {:12.0} signals/s, {:8.0} writes/s",
+        writer,
+        dispatched.load(Ordering::Relaxed) as f64 / elapsed,
+        writes as f64 / elapsed,
+    );
+}
+
+fn main() {
+    // `cargo test` runs benchmarks once in test mode, keep that fast.
+    if std::env::args().any(|arg| arg == "--bench") {
+        for readers in [1, 4, 8] {
+            run(readers, false);
+            run(readers, true);
+        }
+        #[cfg(unix)]
+        for writer in [false, true] {
+            run_dispatch(writer);
+        }
+    }
+}
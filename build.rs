@@ -0,0 +1,80 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Picks the Unix backend from the enabled features and the target, so the sources check one
+//! cfg instead of repeating which feature takes precedence on which target:
+//!
+//! - `ctrlc_channel`: how the handler thread is woken up, `"pipe"`, `"semaphore"`, `"signalfd"`
+//!   or `"kqueue"`.
+//! - `ctrlc_hook`: how the signals are taken over, `"sigaction"`, `"registry"`, `"signalfd"`,
+//!   `"kqueue"` or `"sigwait"`.
+//! - `ctrlc_eventfd`: the pipe is an eventfd.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let vendor = env::var("CARGO_CFG_TARGET_VENDOR").unwrap_or_default();
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{}", name)).is_some();
+    let linux = os == "linux" || os == "android";
+    let kqueue = vendor == "apple" || os == "freebsd" || os == "openbsd";
+    let sigtimedwait = linux || os == "freebsd" || os == "dragonfly" || os == "netbsd";
+
+    // Apple platforms lack unnamed semaphores.
+    let channel = if feature("SIGNALFD") && linux {
+        "signalfd"
+    } else if feature("KQUEUE") && kqueue {
+        "kqueue"
+    } else if feature("POSIX_SEMAPHORE") && vendor != "apple" {
+        "semaphore"
+    } else {
+        "pipe"
+    };
+    // signalfd, kqueue and sigwait install no handler, so there's nothing to share through the
+    // registry.
+    let hook = if channel == "signalfd" || channel == "kqueue" {
+        channel
+    } else if feature("SIGWAIT") && sigtimedwait {
+        "sigwait"
+    } else if feature("SIGNAL_HOOK_REGISTRY") {
+        "registry"
+    } else {
+        "sigaction"
+    };
+    println!("cargo:rustc-cfg=ctrlc_channel=\"{}\"", channel);
+    println!("cargo:rustc-cfg=ctrlc_hook=\"{}\"", hook);
+    if channel == "pipe" && feature("EVENTFD") && linux {
+        println!("cargo:rustc-cfg=ctrlc_eventfd");
+    }
+
+    // Older toolchains warn about the instruction instead of checking the cfgs.
+    if rustc_minor_version() >= 80 {
+        println!(
+            "cargo:rustc-check-cfg=cfg(ctrlc_channel, values(\"pipe\", \"semaphore\", \"signalfd\", \"kqueue\"))"
+        );
+        println!(
+            "cargo:rustc-check-cfg=cfg(ctrlc_hook, values(\"sigaction\", \"registry\", \"signalfd\", \"kqueue\", \"sigwait\"))"
+        );
+        println!("cargo:rustc-check-cfg=cfg(ctrlc_eventfd)");
+    }
+}
+
+fn rustc_minor_version() -> u32 {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|version| version.split('.').nth(1)?.parse().ok())
+        .unwrap_or(0)
+}
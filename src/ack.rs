@@ -0,0 +1,110 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::shutdown;
+use std::collections::BTreeMap;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+struct Acks {
+    next_id: u64,
+    /// Names of the handles that have not acknowledged yet, in registration order.
+    pending: BTreeMap<u64, String>,
+}
+
+static ACKS: Mutex<Acks> = Mutex::new(Acks {
+    next_id: 0,
+    pending: BTreeMap::new(),
+});
+static ACKED: Condvar = Condvar::new();
+
+fn lock() -> MutexGuard<'static, Acks> {
+    ACKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handle of a component that must acknowledge it has stopped during shutdown.
+///
+/// Components obtain a handle at startup and call [`acknowledge()`](#method.acknowledge) once
+/// they have finished shutting down, while [`wait_for_acks()`] blocks until every handle has
+/// been acknowledged. Dropping a handle acknowledges it as well, as the component is gone.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// let ack = ctrlc::AckHandle::new("worker");
+/// std::thread::spawn(move || {
+///     // ... work until shutdown is requested ...
+///     ack.acknowledge();
+/// });
+///
+/// // During shutdown:
+/// if let Err(laggards) = ctrlc::wait_for_acks(Duration::from_secs(10)) {
+///     eprintln!("Components did not stop in time: {}", laggards.join(", "));
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use = "dropping the handle acknowledges it immediately"]
+pub struct AckHandle {
+    id: u64,
+}
+
+impl AckHandle {
+    /// Register a component named `name` that has to acknowledge shutdown.
+    pub fn new<S: Into<String>>(name: S) -> AckHandle {
+        let mut acks = lock();
+        let id = acks.next_id;
+        acks.next_id += 1;
+        acks.pending.insert(id, name.into());
+        AckHandle { id }
+    }
+
+    /// Acknowledge that the component has stopped.
+    pub fn acknowledge(self) {}
+}
+
+impl Drop for AckHandle {
+    fn drop(&mut self) {
+        let mut acks = lock();
+        acks.pending.remove(&self.id);
+        if acks.pending.is_empty() {
+            ACKED.notify_all();
+            drop(acks);
+            shutdown::notify_progress();
+        }
+    }
+}
+
+/// Block until all [`AckHandle`]s have been acknowledged or `timeout` expires.
+///
+/// # Errors
+/// Returns the names of the components that did not acknowledge in time, in registration order.
+pub fn wait_for_acks(timeout: Duration) -> Result<(), Vec<String>> {
+    let deadline = Instant::now().checked_add(timeout);
+    let mut acks = lock();
+    while !acks.pending.is_empty() {
+        let remaining = match deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => Duration::MAX,
+        };
+        if remaining.is_zero() {
+            return Err(acks.pending.values().cloned().collect());
+        }
+        acks = ACKED
+            .wait_timeout(acks, remaining)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+    Ok(())
+}
+
+/// Whether every [`AckHandle`] has been acknowledged.
+pub(crate) fn all_acknowledged() -> bool {
+    lock().pending.is_empty()
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Waiting for Ctrl-C on the tokio reactor with the `tokio` feature, or on the `async-io`
+//! reactor of smol and async-std with the `async-io` feature, on Unix.
+
+use crate::error::Error;
+use crate::unix::SignalNotifier;
+use crate::SignalEvent;
+
+/// Wait for one of the [`default_signals()`](../fn.default_signals.html) on the tokio reactor,
+/// without a handler thread.
+///
+/// The handler is installed when the future is first polled, over a
+/// [`SignalNotifier`](../unix/struct.SignalNotifier.html) registered with
+/// [`AsyncFd`], and removed once it resolves or is dropped. Like with
+/// [`set_handler()`](../fn.set_handler.html), only one handler can be registered at a time.
+/// Must be polled within a tokio runtime with I/O enabled.
+///
+/// # Example
+/// ```no_run
+/// # async fn serve() {}
+/// # async fn f() -> Result<(), ctrlc::Error> {
+/// tokio::select! {
+///     _ = serve() => {}
+///     event = ctrlc::async_io::ctrl_c() => println!("Got {:?}, shutting down", event?.signal()),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+/// Same as [`unix::SignalNotifier::new()`](../unix/struct.SignalNotifier.html#method.new), or an
+/// error if a system error occurred while waiting.
+#[cfg(feature = "tokio")]
+pub async fn ctrl_c() -> Result<SignalEvent, Error> {
+    use tokio::io::unix::AsyncFd;
+    use tokio::io::Interest;
+
+    let notifier = SignalNotifier::new(&crate::default_signals(), true)?;
+    let notifier = AsyncFd::with_interest(notifier, Interest::READABLE).map_err(Error::System)?;
+    loop {
+        let mut ready = notifier.readable().await.map_err(Error::System)?;
+        match ready.get_inner().try_recv()? {
+            Some(event) => return Ok(event),
+            // Another signal may have been taken meanwhile, wait for the next one.
+            None => ready.clear_ready(),
+        }
+    }
+}
+
+/// A [`SignalNotifier`](../unix/struct.SignalNotifier.html) registered with the `async-io`
+/// reactor, for smol and async-std, with the `async-io` feature.
+///
+/// Like the notifier it wraps, creating it installs the OS handler without a handler thread and
+/// dropping it removes the OS handler again. Works on any executor, the `async-io` reactor
+/// runs on its own thread.
+///
+/// # Example
+/// ```no_run
+/// # async fn f() -> Result<(), ctrlc::Error> {
+/// let notifier = ctrlc::async_io::AsyncNotifier::new(&ctrlc::default_signals(), true)?;
+/// loop {
+///     let event = notifier.recv().await?;
+///     println!("Got {:?}", event.signal());
+/// }
+/// # }
+/// ```
+#[cfg(feature = "async-io")]
+#[derive(Debug)]
+pub struct AsyncNotifier {
+    notifier: ::async_io::Async<SignalNotifier>,
+}
+
+#[cfg(feature = "async-io")]
+impl AsyncNotifier {
+    /// Install the OS handler for `signals`, see
+    /// [`unix::SignalNotifier::new()`](../unix/struct.SignalNotifier.html#method.new).
+    ///
+    /// # Errors
+    /// Same as [`unix::SignalNotifier::new()`](../unix/struct.SignalNotifier.html#method.new),
+    /// or an error if the descriptor could not be registered with the reactor.
+    #[track_caller]
+    pub fn new<P>(signals: &[crate::Signal], policy: P) -> Result<AsyncNotifier, Error>
+    where
+        P: Into<crate::InstallPolicy>,
+    {
+        let notifier = SignalNotifier::new(signals, policy)?;
+        // The descriptor is non-blocking already.
+        let notifier = ::async_io::Async::new_nonblocking(notifier).map_err(Error::System)?;
+        Ok(AsyncNotifier { notifier })
+    }
+
+    /// Wait for the next signal.
+    ///
+    /// # Errors
+    /// Same as [`Handle::wait()`](../struct.Handle.html#method.wait), or an error if a system
+    /// error occurred while waiting.
+    pub async fn recv(&self) -> Result<SignalEvent, Error> {
+        loop {
+            if let Some(event) = self.notifier.get_ref().try_recv()? {
+                return Ok(event);
+            }
+            self.notifier.readable().await.map_err(Error::System)?;
+        }
+    }
+}
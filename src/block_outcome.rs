@@ -1,5 +1,18 @@
+use crate::platform::Signal;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlockOutcome {
+    /// Woken up by a signal. There are no signals on ESP-IDF.
+    #[cfg_attr(target_os = "espidf", allow(dead_code))]
+    Signal(Signal),
+    /// Woken up without a signal, by an injected wakeup.
     Awaited,
     HandlerRemoved,
+    /// The timeout passed before anything woke it up, see the platforms'
+    /// `block_ctrl_c_timeout_for()`.
+    TimedOut,
 }
+
+/// Most wakeups taken at once by the platforms' `block_ctrl_c_all_for()`, so that a signal storm
+/// can't keep it from returning.
+pub(crate) const MAX_BATCH: usize = 1024;
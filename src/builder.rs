@@ -0,0 +1,1026 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::block_outcome::BlockOutcome;
+use crate::cleanup;
+use crate::consumer;
+use crate::delivery::Delivery;
+use crate::env::EnvOverrides;
+use crate::error::Error;
+use crate::exit::{self, EscalationStage, ExitMechanism, ExitPolicy};
+use crate::grace;
+use crate::guard::CtrlcGuard;
+use crate::handlers;
+use crate::hardening;
+use crate::inflight;
+use crate::lifecycle::{self, LifecycleState};
+use crate::listener;
+use crate::orchestrator::Orchestrator;
+use crate::platform;
+use crate::preset::{Preset, Readiness, KUBERNETES_GRACE_PERIOD};
+use crate::priority::Scheduling;
+use crate::routes;
+use crate::shutdown;
+use crate::weak;
+use crate::{Displaced, InstallPolicy, SignalEvent, SignalType, ThreadPriority};
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::Location;
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Configures how the Ctrl-C handler is installed.
+///
+/// [`set_handler()`](fn.set_handler.html) is equivalent to `Builder::new().set_handler()`.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// ctrlc::Builder::new()
+///     .grace_period(Duration::from_secs(30))
+///     .force_after(2)
+///     .env()
+///     .expect("Invalid CTRLC_* environment variable")
+///     .set_handler(|| println!("Shutting down..."))
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+pub struct Builder {
+    signals: Option<Vec<SignalType>>,
+    install_policy: InstallPolicy,
+    grace_period: Option<Duration>,
+    watchdog: Option<Duration>,
+    force_after: Option<u32>,
+    drain_delay: Option<Duration>,
+    readiness: Readiness,
+    orchestrator: Option<Orchestrator>,
+    debugger_passthrough: bool,
+    force_quit_message: Option<String>,
+    nohup: bool,
+    external: bool,
+    storm_protection: Option<(u32, Duration)>,
+    force_on_repeat: Option<Duration>,
+    reraise: bool,
+    filters: Vec<Filter>,
+    exit_policy: ExitPolicy,
+    scheduling: Scheduling,
+    stack_size: Option<usize>,
+    delivery: Delivery,
+    sender_names: bool,
+}
+
+type Filter = Box<dyn FnMut(&SignalEvent) -> bool + Send>;
+
+enum UserHandler {
+    Exclusive(Box<dyn FnMut(platform::Signal) + Send>),
+    Shared(Arc<dyn Fn() + Send + Sync>),
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("signals", &self.signals)
+            .field("install_policy", &self.install_policy)
+            .field("grace_period", &self.grace_period)
+            .field("watchdog", &self.watchdog)
+            .field("force_after", &self.force_after)
+            .field("drain_delay", &self.drain_delay)
+            .field("readiness", &self.readiness)
+            .field("orchestrator", &self.orchestrator)
+            .field("debugger_passthrough", &self.debugger_passthrough)
+            .field("force_quit_message", &self.force_quit_message)
+            .field("nohup", &self.nohup)
+            .field("external", &self.external)
+            .field("storm_protection", &self.storm_protection)
+            .field("force_on_repeat", &self.force_on_repeat)
+            .field("reraise", &self.reraise)
+            .field("filters", &self.filters.len())
+            .field("exit_policy", &self.exit_policy)
+            .field("scheduling", &self.scheduling)
+            .field("stack_size", &self.stack_size)
+            .field("delivery", &self.delivery)
+            .field("sender_names", &self.sender_names)
+            .finish()
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Create a builder with the same configuration as [`set_handler()`](fn.set_handler.html).
+    pub fn new() -> Builder {
+        Builder {
+            signals: None,
+            install_policy: InstallPolicy::ForceReplace,
+            grace_period: None,
+            watchdog: None,
+            force_after: None,
+            drain_delay: None,
+            readiness: Readiness::new(),
+            orchestrator: None,
+            debugger_passthrough: false,
+            force_quit_message: None,
+            nohup: false,
+            external: false,
+            storm_protection: None,
+            force_on_repeat: None,
+            reraise: false,
+            filters: Vec::new(),
+            exit_policy: ExitPolicy::default(),
+            scheduling: Scheduling::default(),
+            stack_size: None,
+            delivery: Delivery::default(),
+            sender_names: false,
+        }
+    }
+
+    /// Handle exactly `signals` instead of the default set (`SIGINT`, plus `SIGTERM` and `SIGHUP`
+    /// with the `termination` feature on Unix; all console control events on Windows).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[cfg(unix)] {
+    /// use ctrlc::{Signal, SignalType};
+    ///
+    /// // SIGINT and SIGTERM, but not SIGHUP.
+    /// ctrlc::Builder::new()
+    ///     .signals([SignalType::Ctrlc, SignalType::Other(Signal::SIGTERM)])
+    ///     .overwrite(false)
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// # }
+    /// ```
+    pub fn signals<I>(mut self, signals: I) -> Builder
+    where
+        I: IntoIterator<Item = SignalType>,
+    {
+        self.signals = Some(signals.into_iter().collect());
+        self
+    }
+
+    /// Handle `signal` in addition to the signals selected so far, the default set unless
+    /// [`signals()`](#method.signals) was called.
+    ///
+    /// Lets a batch job pick the resource limit signals it can react to one by one, e.g. to
+    /// checkpoint on `SIGXCPU` before the hard CPU time limit kills it, on `SIGXFSZ` instead of
+    /// being killed when a file grows too large, or on `SIGPWR` when the UPS reports a power
+    /// failure on Linux. They don't count as termination signals, see
+    /// [`SignalInfo::is_termination()`](struct.SignalInfo.html#method.is_termination), so they
+    /// run the handler without starting the shutdown: the [`lifecycle()`](fn.lifecycle.html)
+    /// stays `Running`, [`ShutdownToken`](struct.ShutdownToken.html)s are not cancelled and the
+    /// grace period doesn't start.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[cfg(unix)] {
+    /// use ctrlc::{Signal, SignalType};
+    ///
+    /// ctrlc::Builder::new()
+    ///     .add_signal(SignalType::Other(Signal::SIGXCPU))
+    ///     .add_signal(SignalType::Other(Signal::SIGXFSZ))
+    ///     .set_handler_with_signal(|signal| match signal {
+    ///         Signal::SIGXCPU | Signal::SIGXFSZ => println!("Checkpointing..."),
+    ///         _ => println!("Shutting down..."),
+    ///     })
+    ///     .expect("Error setting Ctrl-C handler");
+    /// # }
+    /// ```
+    pub fn add_signal(mut self, signal: SignalType) -> Builder {
+        self.signals
+            .get_or_insert_with(|| {
+                crate::default_signals()
+                    .into_iter()
+                    .map(SignalType::Other)
+                    .collect()
+            })
+            .push(signal);
+        self
+    }
+
+    /// Whether existing handlers for the signals may be overwritten. Defaults to `true`.
+    ///
+    /// `false` gives the behaviour of [`try_set_handler()`](fn.try_set_handler.html). Shorthand
+    /// for [`install_policy()`](#method.install_policy) with [`InstallPolicy::ForceReplace`] or
+    /// [`InstallPolicy::ErrorIfExists`].
+    pub fn overwrite(mut self, overwrite: bool) -> Builder {
+        self.install_policy = overwrite.into();
+        self
+    }
+
+    /// What to do with the handlers already installed for the signals. Defaults to
+    /// [`InstallPolicy::ForceReplace`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// // Under nohup, SIGHUP is ignored and gets handled. A handler installed by another
+    /// // component is left alone.
+    /// ctrlc::Builder::new()
+    ///     .signals([ctrlc::SignalType::Ctrlc, ctrlc::SignalType::Termination])
+    ///     .install_policy(ctrlc::InstallPolicy::ReplaceIfDefault)
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn install_policy(mut self, install_policy: InstallPolicy) -> Builder {
+        self.install_policy = install_policy;
+        self
+    }
+
+    /// Ignore `SIGHUP` like `nohup(1)`, instead of handling it. Defaults to `false`.
+    ///
+    /// Long running jobs started over SSH then survive the terminal going away, while `SIGINT`
+    /// and `SIGTERM` still run the handler. `SIGHUP` stays ignored after the handler is removed.
+    /// Has no effect on Windows, where closing the console can't be ignored.
+    pub fn nohup(mut self, nohup: bool) -> Builder {
+        self.nohup = nohup;
+        self
+    }
+
+    /// Let a host runtime that owns signal handling (JNI, cgo, Python, ...) forward signals with
+    /// [`external::deliver()`](external/fn.deliver.html), instead of installing OS handlers.
+    ///
+    /// The handler, filters, orchestrator, grace period and other options work as usual, but
+    /// `sigaction` is never called, so the handlers of the host are left alone. `nohup` has no
+    /// effect. Defaults to `false`.
+    pub fn external(mut self, external: bool) -> Builder {
+        self.external = external;
+        self
+    }
+
+    /// Forcibly exit the process if it is still running `grace_period` after the first signal.
+    pub fn grace_period(mut self, grace_period: Duration) -> Builder {
+        self.grace_period = Some(grace_period);
+        self
+    }
+
+    /// Forcibly exit the process if it is still running `timeout` after the first termination
+    /// signal, see [`SignalInfo::is_termination()`](struct.SignalInfo.html#method.is_termination),
+    /// or requested shutdown.
+    ///
+    /// Unlike [`grace_period`](#method.grace_period), other signals handled by the same
+    /// builder, e.g. `SIGUSR1` to reload the configuration, don't arm it. Protects services from
+    /// hanging forever in a buggy cleanup path. Choose how the process ends with
+    /// [`exit_mechanism`](#method.exit_mechanism) for [`EscalationStage::Watchdog`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ctrlc::{EscalationStage, ExitMechanism};
+    /// use std::time::Duration;
+    ///
+    /// ctrlc::Builder::new()
+    ///     .watchdog(Duration::from_secs(30))
+    ///     .exit_mechanism(EscalationStage::Watchdog, ExitMechanism::Reraise)
+    ///     .set_handler(|| println!("Cleaning up..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn watchdog(mut self, timeout: Duration) -> Builder {
+        self.watchdog = Some(timeout);
+        self
+    }
+
+    /// Forcibly exit the process on the `count`th signal instead of running the handler again.
+    ///
+    /// Signals are counted even while the handler is running.
+    ///
+    /// A `count` of 2 gives the common "press Ctrl-C again to force quit" behaviour.
+    pub fn force_after(mut self, count: u32) -> Builder {
+        self.force_after = Some(count);
+        self
+    }
+
+    /// Forcibly exit the process as soon as `count` signals are received within `window`,
+    /// even if the handler is still running.
+    ///
+    /// Protects against supervisors spamming signals at a wedged process.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// ctrlc::Builder::new()
+    ///     .storm_protection(10, Duration::from_secs(2))
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn storm_protection(mut self, count: u32, window: Duration) -> Builder {
+        self.storm_protection = Some((count, window));
+        self
+    }
+
+    /// Kill the process with the signal itself when another one arrives within `window` of the
+    /// previous one, or at any time after the first for `None`, even if the handler is still
+    /// running.
+    ///
+    /// [`EscalationStage::Repeat`] defaults to [`ExitMechanism::Reraise`]: the signal gets its
+    /// default action back, so mashing Ctrl-C always ends the process like it would without a
+    /// handler, also when the graceful shutdown hangs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// ctrlc::Builder::new()
+    ///     .force_on_repeat(Some(Duration::from_secs(2)))
+    ///     .set_handler(|| println!("Shutting down, press Ctrl-C again to force quit..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn force_on_repeat(mut self, window: Option<Duration>) -> Builder {
+        self.force_on_repeat = Some(window.unwrap_or(Duration::MAX));
+        self
+    }
+
+    /// Once the shutdown of a termination signal is finished, kill the process with that
+    /// signal, see [`reraise()`](fn.reraise.html). Defaults to `false`.
+    ///
+    /// The handler, the handlers added with [`add_handler()`](fn.add_handler.html), the
+    /// [`on_shutdown()`](fn.on_shutdown.html) callbacks and the orchestrator run first, then
+    /// the parent sees the process killed by `SIGINT` or `SIGTERM` instead of a normal exit.
+    /// A requested shutdown without a signal uses the Ctrl-C signal.
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::Builder::new()
+    ///     .reraise(true)
+    ///     .set_handler(|| println!("Cleaning up..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn reraise(mut self, reraise: bool) -> Builder {
+        self.reraise = reraise;
+        self
+    }
+
+    /// How the process is terminated at `stage`. Defaults to [`ExitMechanism::Exit`], except
+    /// for [`EscalationStage::Repeat`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ctrlc::{EscalationStage, ExitMechanism};
+    /// use std::time::Duration;
+    ///
+    /// ctrlc::Builder::new()
+    ///     .force_after(2)
+    ///     .grace_period(Duration::from_secs(30))
+    ///     .exit_mechanism(EscalationStage::ForceAfter, ExitMechanism::Immediate)
+    ///     .exit_mechanism(EscalationStage::GracePeriod, ExitMechanism::Abort)
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn exit_mechanism(mut self, stage: EscalationStage, mechanism: ExitMechanism) -> Builder {
+        self.exit_policy.set(stage, mechanism);
+        self
+    }
+
+    /// Run the threads waiting for signals and running the handler with `priority`, so the
+    /// shutdown isn't starved by worker threads on a heavily loaded machine.
+    ///
+    /// Failures, e.g. missing privileges, are reported on stderr. See [`ThreadPriority`] for the
+    /// supported platforms.
+    pub fn thread_priority(mut self, priority: ThreadPriority) -> Builder {
+        self.scheduling.priority = Some(priority);
+        self
+    }
+
+    /// Restrict the threads waiting for signals and running the handler to the CPUs with the
+    /// indices in `cpus`. Supported on Linux, Android and Windows.
+    ///
+    /// Failures are reported on stderr.
+    pub fn thread_affinity<I>(mut self, cpus: I) -> Builder
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.scheduling.affinity = Some(cpus.into_iter().collect());
+        self
+    }
+
+    /// Choose how the handler runs when a signal arrives while it is still running, see
+    /// [`Delivery`]. Defaults to [`Delivery::Serialized`].
+    pub fn delivery(mut self, delivery: Delivery) -> Builder {
+        self.delivery = delivery;
+        self
+    }
+
+    /// Stack size in bytes of the threads waiting for signals and running the handler, instead
+    /// of the default of [`std::thread`]. Handlers with deep call chains may need more.
+    pub fn stack_size(mut self, size: usize) -> Builder {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Look up the name of the process that sent a signal, see
+    /// [`SignalEvent::sender_name()`](struct.SignalEvent.html#method.sender_name). Defaults to
+    /// `false`.
+    ///
+    /// The name is read from `/proc/<pid>/comm` on the thread waiting for signals, right after
+    /// the signal was received, so a sender that exits quickly, like `kill(1)`, is usually still
+    /// found. Only supported on Linux and Android.
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::Builder::new()
+    ///     .sender_names(true)
+    ///     .filter(|event| {
+    ///         if let (Some(sender), Some(name)) = (event.sender(), event.sender_name()) {
+    ///             eprintln!("Signal sent by {} (pid {})", name, sender.pid());
+    ///         }
+    ///         true
+    ///     })
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn sender_names(mut self, sender_names: bool) -> Builder {
+        self.sender_names = sender_names;
+        self
+    }
+
+    /// Print `template` to stderr on the first signal when [`force_after`](#method.force_after)
+    /// is set, telling the user how to force quit.
+    ///
+    /// The placeholders `{remaining}` and `{grace}` are replaced by the number of further
+    /// signals that force quit and by the grace period (e.g. `30s`, empty if there is none).
+    /// The message is written by the handler thread, before the handler runs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// ctrlc::Builder::new()
+    ///     .force_after(2)
+    ///     .grace_period(Duration::from_secs(10))
+    ///     .force_quit_message("Shutting down within {grace}, press Ctrl-C again to force quit")
+    ///     .set_handler(|| {})
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn force_quit_message<S: Into<String>>(mut self, template: S) -> Builder {
+        self.force_quit_message = Some(template.into());
+        self
+    }
+
+    /// Wait `drain_delay` after the first signal before running the handler.
+    ///
+    /// The [`Readiness`] flag flips as soon as the signal arrives, so the delay gives load
+    /// balancers time to stop routing traffic to the process before it starts shutting down.
+    /// The grace period, if any, includes the delay.
+    pub fn drain_delay(mut self, drain_delay: Duration) -> Builder {
+        self.drain_delay = Some(drain_delay);
+        self
+    }
+
+    /// Apply a [`Preset`] configuration. Options set afterwards override the preset.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use ctrlc::Preset;
+    ///
+    /// let builder = ctrlc::Builder::new().preset(Preset::Kubernetes {
+    ///     drain_delay: Duration::from_secs(5),
+    /// });
+    /// let readiness = builder.readiness();
+    /// builder
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    ///
+    /// // Report `readiness.is_ready()` from the readiness probe endpoint.
+    /// ```
+    pub fn preset(self, preset: Preset) -> Builder {
+        match preset {
+            Preset::Kubernetes { drain_delay } => self
+                .signals([SignalType::Ctrlc, SignalType::Termination])
+                .drain_delay(drain_delay)
+                .grace_period(KUBERNETES_GRACE_PERIOD),
+        }
+    }
+
+    /// Only run the handler for signals accepted by `filter`.
+    ///
+    /// Filters run on the signal handling thread as soon as a signal is received, all of them
+    /// must accept it. A rejected signal doesn't start the shutdown:
+    /// [`ShutdownToken`](struct.ShutdownToken.html)s are not cancelled, the
+    /// [`lifecycle()`](fn.lifecycle.html) and the readiness flag are unchanged, the grace period
+    /// and the watchdog don't start, and [`signals()`](fn.signals.html) doesn't see it. Rejected signals still count for [`force_after`](#method.force_after),
+    /// [`storm_protection`](#method.storm_protection) and
+    /// [`force_on_repeat`](#method.force_on_repeat).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let maintenance = Arc::new(AtomicBool::new(false));
+    /// let m = maintenance.clone();
+    /// ctrlc::Builder::new()
+    ///     .filter(move |_| !m.load(Ordering::SeqCst))
+    ///     .set_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    pub fn filter<F>(mut self, filter: F) -> Builder
+    where
+        F: FnMut(&SignalEvent) -> bool + Send + 'static,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Run the tasks of `orchestrator` after the handler has returned for the first termination
+    /// signal.
+    pub fn orchestrator(mut self, orchestrator: Orchestrator) -> Builder {
+        self.orchestrator = Some(orchestrator);
+        self
+    }
+
+    /// Leave Ctrl-C alone when a debugger is attached, see
+    /// [`is_debugger_attached()`](fn.is_debugger_attached.html). Defaults to `false`.
+    ///
+    /// Ctrl-C then keeps breaking into gdb or lldb instead of shutting the application down.
+    /// Other signals, such as [`SignalType::Termination`], are still handled.
+    pub fn debugger_passthrough(mut self, debugger_passthrough: bool) -> Builder {
+        self.debugger_passthrough = debugger_passthrough;
+        self
+    }
+
+    /// Readiness flag of the handler installed by this builder.
+    pub fn readiness(&self) -> Readiness {
+        self.readiness.clone()
+    }
+
+    /// Apply overrides from the environment, letting operators tune a deployed binary:
+    ///
+    /// * `CTRLC_SIGNALS`: comma separated signal names, e.g. `INT,TERM`.
+    /// * `CTRLC_GRACE_SECS`: grace period in seconds, see [`grace_period`](#method.grace_period).
+    /// * `CTRLC_FORCE_AFTER`: signal count, see [`force_after`](#method.force_after).
+    ///
+    /// Unset or empty variables leave the configuration unchanged.
+    ///
+    /// # Errors
+    /// Will return [`Error::Env`] if a variable is set to an invalid value.
+    pub fn env(mut self) -> Result<Builder, Error> {
+        let overrides = EnvOverrides::read()?;
+        if let Some(signals) = overrides.signals {
+            self.signals = Some(signals);
+        }
+        if let Some(grace_period) = overrides.grace_period {
+            self.grace_period = Some(grace_period);
+        }
+        if let Some(force_after) = overrides.force_after {
+            self.force_after = Some(force_after);
+        }
+        Ok(self)
+    }
+
+    /// Register the signal handler with this configuration.
+    ///
+    /// See [`set_handler()`](fn.set_handler.html) for details.
+    ///
+    /// # Errors
+    /// Will return an error if a handler is already registered, if another handler exists and
+    /// overwriting was disabled, or if a system error occurred while setting the handler.
+    #[track_caller]
+    pub fn set_handler<F>(self, user_handler: F) -> Result<JoinHandle<()>, Error>
+    where
+        F: FnMut() + 'static + Send,
+    {
+        let mut user_handler = user_handler;
+        let location = handlers::caller_location();
+        let user_handler = UserHandler::Exclusive(Box::new(move |_| user_handler()));
+        crate::init_handler(|| self.set_handler_inner(user_handler, location))
+    }
+
+    /// Register the signal handler with this configuration, removing it again when the returned
+    /// guard is dropped.
+    ///
+    /// See [`set_handler_guarded()`](fn.set_handler_guarded.html) for details.
+    ///
+    /// # Errors
+    /// The same as [`set_handler`](#method.set_handler).
+    #[track_caller]
+    pub fn set_handler_guarded<F>(self, user_handler: F) -> Result<CtrlcGuard, Error>
+    where
+        F: FnMut() + 'static + Send,
+    {
+        let mut user_handler = user_handler;
+        let location = handlers::caller_location();
+        let user_handler = UserHandler::Exclusive(Box::new(move |_| user_handler()));
+        crate::init_handler(|| {
+            self.set_handler_inner(user_handler, location)
+                .map(CtrlcGuard::new)
+        })
+    }
+
+    /// Register a handler that is told which signal triggered it.
+    ///
+    /// See [`set_handler_with_signal()`](fn.set_handler_with_signal.html) for details.
+    ///
+    /// # Errors
+    /// The same as [`set_handler`](#method.set_handler).
+    #[track_caller]
+    pub fn set_handler_with_signal<F>(self, user_handler: F) -> Result<JoinHandle<()>, Error>
+    where
+        F: FnMut(crate::Signal) + 'static + Send,
+    {
+        let mut user_handler = user_handler;
+        let location = handlers::caller_location();
+        let user_handler = UserHandler::Exclusive(Box::new(move |signal| {
+            user_handler(platform::to_signal(signal))
+        }));
+        crate::init_handler(|| self.set_handler_inner(user_handler, location))
+    }
+
+    /// Register a handler that can run on several threads at once, which
+    /// [`Delivery::Concurrent`] requires.
+    ///
+    /// Otherwise the same as [`set_handler`](#method.set_handler).
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::Builder::new()
+    ///     .delivery(ctrlc::Delivery::Concurrent)
+    ///     .set_sync_handler(|| println!("Shutting down..."))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    ///
+    /// # Errors
+    /// The same as [`set_handler`](#method.set_handler).
+    #[track_caller]
+    pub fn set_sync_handler<F>(self, user_handler: F) -> Result<JoinHandle<()>, Error>
+    where
+        F: Fn() + 'static + Send + Sync,
+    {
+        let location = handlers::caller_location();
+        let user_handler = UserHandler::Shared(Arc::new(user_handler));
+        crate::init_handler(|| self.set_handler_inner(user_handler, location))
+    }
+
+    /// Start latching the configured signals before the handler is registered.
+    ///
+    /// Signals received between this call and [`set_handler`](#method.set_handler) are not
+    /// lost: the default action (usually terminating the process) is suppressed and the handler
+    /// runs once for each latched signal as soon as it is registered. The builder used for
+    /// `set_handler` may handle a different set of signals, the dispositions are updated then.
+    ///
+    /// Calling this again, after a handler is registered or after
+    /// [`set_flag()`](fn.set_flag.html), has no effect.
+    ///
+    /// # Errors
+    /// Will return an error if a system error occurred while installing the latch.
+    pub fn prearm(&self) -> Result<(), Error> {
+        let _guard = crate::INIT_LOCK.lock().unwrap();
+        unsafe {
+            if !platform::is_handler_init() && !crate::flag::is_installed() {
+                let signals = self.platform_signals();
+                self.init_os_handler(&signals)?;
+            }
+        }
+        Ok(())
+    }
+
+    unsafe fn init_os_handler(
+        &self,
+        signals: &[platform::Signal],
+    ) -> Result<Vec<Displaced>, Error> {
+        let displaced =
+            platform::init_os_handler(self.installed_signals(signals), self.install_policy)?;
+        Ok(displaced)
+    }
+
+    /// The signals to install OS handlers for, none in external mode.
+    fn installed_signals<'a>(&self, signals: &'a [platform::Signal]) -> &'a [platform::Signal] {
+        if self.external {
+            &[]
+        } else {
+            signals
+        }
+    }
+
+    fn platform_signals(&self) -> Vec<platform::Signal> {
+        let mut signals = match self.signals {
+            Some(ref kinds) => {
+                let mut signals = Vec::new();
+                for signal in kinds.iter().flat_map(platform::signals_of) {
+                    if !signals.contains(&signal) {
+                        signals.push(signal);
+                    }
+                }
+                signals
+            }
+            None => platform::default_signals(),
+        };
+        if self.nohup {
+            let hangup = platform::hangup_signals();
+            signals.retain(|signal| !hangup.contains(signal));
+        }
+        if self.debugger_passthrough && platform::is_debugger_attached() {
+            let ctrlc = platform::signals_of(&SignalType::Ctrlc);
+            signals.retain(|signal| !ctrlc.contains(signal));
+        }
+        signals
+    }
+
+    fn set_handler_inner(
+        self,
+        mut user_handler: UserHandler,
+        location: Option<&'static Location<'static>>,
+    ) -> Result<JoinHandle<()>, Error> {
+        if self.delivery == Delivery::Concurrent {
+            if let UserHandler::Exclusive(_) = user_handler {
+                return Err(Error::System(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "concurrent delivery requires a handler set with set_sync_handler",
+                )));
+            }
+        }
+        let mut signals = self.platform_signals();
+        let displaced = unsafe { self.init_os_handler(&signals)? };
+        // A waiter left over from a removed handler must not take our signals.
+        let generation = platform::generation();
+        // Signals left alone by the install policy are not handled.
+        signals.retain(|&signal| {
+            !displaced
+                .iter()
+                .any(|displaced| displaced.platform_signal() == signal && !displaced.is_replaced())
+        });
+        unsafe {
+            if self.nohup && !self.external {
+                if let Err(e) = platform::ignore_hangup() {
+                    let _ = platform::deinit_os_handler();
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let Builder {
+            grace_period,
+            mut watchdog,
+            force_after,
+            drain_delay,
+            readiness,
+            mut orchestrator,
+            force_quit_message,
+            storm_protection,
+            force_on_repeat,
+            reraise,
+            mut filters,
+            exit_policy,
+            scheduling,
+            delivery,
+            external,
+            stack_size,
+            sender_names,
+            ..
+        } = self;
+        let force_quit_message = match (force_after, force_quit_message) {
+            (Some(count), Some(template)) => Some(render_force_quit_message(
+                &template,
+                count.saturating_sub(1),
+                grace_period,
+            )),
+            _ => None,
+        };
+        let mut storm = storm_protection.map(|(count, window)| StormDetector::new(count, window));
+
+        // The handler runs on its own thread, so that signals keep being counted for
+        // `force_after` and storm protection while it runs.
+        let (signal_tx, signal_rx) = mpsc::channel::<SignalEvent>();
+        let handler_scheduling = scheduling.clone();
+        // Runs without a signal are reported as Ctrl-C.
+        let fallback_signal = platform::signals_of(&SignalType::Ctrlc)[0];
+        let worker = handler_thread("ctrl-c-handler", stack_size)
+            .spawn(move || {
+                handler_scheduling.apply();
+                let mut first = true;
+                while let Ok(mut event) = signal_rx.recv() {
+                    if delivery == Delivery::Coalesced {
+                        // Signals received while the handler was running share one run.
+                        while let Ok(next) = signal_rx.try_recv() {
+                            event.coalesce(next);
+                        }
+                    }
+                    let signal = event.platform_signal();
+                    let terminates = signal.map_or(true, platform::is_termination);
+                    let is_first = first && terminates;
+                    if is_first {
+                        first = false;
+                        if let Some(drain_delay) = drain_delay {
+                            thread::sleep(drain_delay);
+                        }
+                    }
+                    let orchestrator = if is_first { orchestrator.take() } else { None };
+                    let reraise =
+                        (reraise && terminates).then(|| signal.unwrap_or(fallback_signal));
+                    match user_handler {
+                        UserHandler::Shared(ref handler) if delivery == Delivery::Concurrent => {
+                            let handler = handler.clone();
+                            let spawned =
+                                handler_thread("ctrl-c-handler", stack_size).spawn(move || {
+                                    handler();
+                                    finish_event(is_first, orchestrator, signal, reraise);
+                                });
+                            if spawned.is_err() {
+                                eprintln!("[ctrlc] Could not start a handler thread");
+                            }
+                        }
+                        UserHandler::Shared(ref handler) => {
+                            handler();
+                            finish_event(is_first, orchestrator, signal, reraise);
+                        }
+                        UserHandler::Exclusive(ref mut handler) => {
+                            handler(signal.unwrap_or(fallback_signal));
+                            finish_event(is_first, orchestrator, signal, reraise);
+                        }
+                    }
+                }
+            })
+            .map_err(Error::System)?;
+
+        let thread = handler_thread("ctrl-c", stack_size)
+            .spawn(move || {
+                scheduling.apply();
+                let mut received = 0u32;
+                let mut accepted = false;
+                let mut last_received: Option<Instant> = None;
+                'receive: loop {
+                    // Taking all signals already received hands a burst to the handler thread
+                    // at once, so that `Delivery::Coalesced` runs the handler once for it.
+                    let outcomes = unsafe {
+                        platform::block_ctrl_c_all_for(generation).unwrap_or_else(|err| {
+                            panic!("Critical system error while waiting for Ctrl-C: {err:?}")
+                        })
+                    };
+                    for outcome in outcomes {
+                        let signal = match outcome {
+                            BlockOutcome::Signal(signal) => Some(signal),
+                            BlockOutcome::Awaited => None,
+                            BlockOutcome::HandlerRemoved => break 'receive,
+                            // Only waits with a timeout run out.
+                            BlockOutcome::TimedOut => continue,
+                        };
+                        if signal.map_or(false, routes::dispatch) || consumer::dispatch() {
+                            continue;
+                        }
+                        hardening::check_signal();
+                        received = received.saturating_add(1);
+                        let mut event = SignalEvent::new(received, signal);
+                        if sender_names {
+                            event.resolve_sender_name();
+                        }
+
+                        if force_after.map_or(false, |count| received >= count) {
+                            exit_policy.exit(EscalationStage::ForceAfter, signal);
+                        }
+                        if storm.as_mut().map_or(false, StormDetector::record) {
+                            exit_policy.exit(EscalationStage::Storm, signal);
+                        }
+                        let now = Instant::now();
+                        if let (Some(window), Some(last)) = (force_on_repeat, last_received) {
+                            if now.saturating_duration_since(last) <= window {
+                                exit_policy.exit(EscalationStage::Repeat, signal);
+                            }
+                        }
+                        last_received = Some(now);
+                        // A rejected signal has no other effect, it doesn't start the shutdown.
+                        if !filters.iter_mut().all(|filter| filter(&event)) {
+                            continue;
+                        }
+                        // Only termination signals start the shutdown, e.g. SIGXCPU doesn't.
+                        let terminates = signal.map_or(true, platform::is_termination);
+                        if terminates {
+                            shutdown::record_signal();
+                        }
+                        #[cfg(feature = "ffi")]
+                        crate::ffi::record_signal();
+                        listener::record_signal(&event);
+                        if terminates && !accepted {
+                            accepted = true;
+                            if let Some(ref message) = force_quit_message {
+                                eprintln!("{}", message);
+                            }
+                            readiness.set_not_ready();
+                            lifecycle::advance(LifecycleState::Draining);
+                            if let Some(grace_period) = grace_period {
+                                grace::start_timer(
+                                    grace_period,
+                                    exit_policy,
+                                    EscalationStage::GracePeriod,
+                                    signal,
+                                );
+                            }
+                        }
+                        if terminates {
+                            if let Some(timeout) = watchdog.take() {
+                                grace::start_timer(
+                                    timeout,
+                                    exit_policy,
+                                    EscalationStage::Watchdog,
+                                    signal,
+                                );
+                            }
+                        }
+                        // Fails only if the handler panicked, keep counting signals.
+                        let _ = signal_tx.send(event);
+                    }
+                }
+                // Let the handler finish the signals received before the removal.
+                drop(signal_tx);
+                let _ = worker.join();
+            })
+            .map_err(Error::System)?;
+
+        if external {
+            crate::external::set_handled(signals.clone());
+        }
+        handlers::register(signals, displaced, false, location);
+        Ok(thread)
+    }
+}
+
+/// Whether the current thread is one of the threads started by the builder.
+pub(crate) fn is_handler_thread() -> bool {
+    thread::current()
+        .name()
+        .map_or(false, |name| name == "ctrl-c" || name == "ctrl-c-handler")
+}
+
+fn handler_thread(name: &str, stack_size: Option<usize>) -> thread::Builder {
+    let builder = thread::Builder::new().name(name.into());
+    match stack_size {
+        Some(size) => builder.stack_size(size),
+        None => builder,
+    }
+}
+
+/// Run the part of the shutdown that follows the handler, starting with the handlers added with
+/// `add_handler()`. Only the first accepted termination signal waits for in-flight requests and
+/// runs the orchestrator and the `on_shutdown()` callbacks, and only termination signals stop the
+/// lifecycle. Ends the process with `reraise` if set.
+fn finish_event(
+    is_first: bool,
+    orchestrator: Option<Orchestrator>,
+    signal: Option<platform::Signal>,
+    reraise: Option<platform::Signal>,
+) {
+    routes::run_added();
+    weak::dispatch();
+    if is_first {
+        inflight::drain();
+    }
+    cleanup::run(signal);
+    if let Some(orchestrator) = orchestrator {
+        for task in orchestrator.run().timed_out() {
+            eprintln!("[ctrlc] Shutdown task {:?} timed out", task.name());
+        }
+    }
+    if signal.map_or(true, platform::is_termination) {
+        lifecycle::advance(LifecycleState::Stopped);
+    }
+    if let Some(signal) = reraise {
+        exit::reraise(platform::to_signal(signal));
+    }
+}
+
+/// Detects `count` signals within `window`.
+struct StormDetector {
+    count: usize,
+    window: Duration,
+    received: VecDeque<Instant>,
+}
+
+impl StormDetector {
+    fn new(count: u32, window: Duration) -> StormDetector {
+        let count = count.max(1) as usize;
+        StormDetector {
+            count,
+            window,
+            received: VecDeque::with_capacity(count),
+        }
+    }
+
+    /// Record a signal, returns whether it completes a storm.
+    fn record(&mut self) -> bool {
+        let now = Instant::now();
+        if self.received.len() == self.count {
+            self.received.pop_front();
+        }
+        self.received.push_back(now);
+        self.received.len() == self.count && now.duration_since(self.received[0]) <= self.window
+    }
+}
+
+fn render_force_quit_message(
+    template: &str,
+    remaining: u32,
+    grace_period: Option<Duration>,
+) -> String {
+    let grace = grace_period.map_or(String::new(), |grace| format!("{:?}", grace));
+    template
+        .replace("{remaining}", &remaining.to_string())
+        .replace("{grace}", &grace)
+}
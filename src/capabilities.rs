@@ -0,0 +1,71 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+/// What works on the current platform and build, see [`capabilities()`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Arbitrary POSIX signals can be handled with [`SignalType::Other`](enum.SignalType.html).
+    pub posix_signals: bool,
+    /// Windows console control events, such as closing the console window, are delivered. False
+    /// when the process has no console.
+    pub console_events: bool,
+    /// `unix::enable_job_control()` is available.
+    pub job_control: bool,
+    /// [`io::read_interruptible()`](io/fn.read_interruptible.html) works.
+    pub interruptible_reads: bool,
+    /// [`Builder::thread_priority()`](struct.Builder.html#method.thread_priority) is applied.
+    pub thread_priority: bool,
+    /// [`Builder::thread_affinity()`](struct.Builder.html#method.thread_affinity) is applied.
+    pub thread_affinity: bool,
+    /// [`is_debugger_attached()`](fn.is_debugger_attached.html) can detect a debugger.
+    pub debugger_detection: bool,
+    /// [`testing::send_interrupt()`](testing/fn.send_interrupt.html) can interrupt a child.
+    pub send_interrupt: bool,
+    /// `unix::on_seccomp_violation()` is available.
+    pub seccomp_violations: bool,
+    /// `unix::on_realtime()` is available.
+    pub realtime_signals: bool,
+}
+
+/// Report which features work on this platform and build, so portable applications can adapt
+/// their shutdown strategy at runtime instead of guessing with `#[cfg]`.
+///
+/// # Example
+/// ```
+/// let capabilities = ctrlc::capabilities();
+/// if !capabilities.interruptible_reads {
+///     // Fall back to read timeouts.
+/// }
+/// ```
+pub fn capabilities() -> Capabilities {
+    let unix = cfg!(all(unix, not(target_os = "espidf")));
+    let linux = cfg!(any(target_os = "linux", target_os = "android"));
+    let windows = cfg!(windows);
+
+    #[cfg(windows)]
+    let console_events = unsafe { crate::platform::has_console() };
+    #[cfg(not(windows))]
+    let console_events = false;
+
+    Capabilities {
+        posix_signals: unix,
+        console_events,
+        job_control: unix,
+        interruptible_reads: unix || windows,
+        thread_priority: linux || windows,
+        thread_affinity: linux || windows,
+        debugger_detection: linux
+            || windows
+            || cfg!(all(target_vendor = "apple", target_pointer_width = "64")),
+        send_interrupt: unix || windows,
+        seccomp_violations: linux,
+        realtime_signals: linux,
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::orchestrator::{OnTimeout, Orchestrator};
+use crate::platform::{self, Signal};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+struct Entry {
+    id: u64,
+    priority: i32,
+    timeout: Option<(Duration, OnTimeout)>,
+    callback: Callback,
+}
+
+struct Callbacks {
+    next_id: u64,
+    entries: Vec<Entry>,
+}
+
+static CALLBACKS: Mutex<Callbacks> = Mutex::new(Callbacks {
+    next_id: 0,
+    entries: Vec::new(),
+});
+
+fn lock() -> MutexGuard<'static, Callbacks> {
+    CALLBACKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Run `callback` once the shutdown starts, ordered by `priority`.
+///
+/// The callbacks run once, for the first termination signal (see
+/// [`SignalInfo::is_termination()`](struct.SignalInfo.html#method.is_termination)) or requested
+/// shutdown that reaches the main handler: after it and the handlers added with
+/// [`add_handler()`](fn.add_handler.html), before the tasks of the
+/// [`Orchestrator`](struct.Orchestrator.html). They run one after another in ascending
+/// `priority`, callbacks with the same priority in registration order, so a larger application
+/// can tear down in stages instead of in one handler. A panicking callback doesn't stop the
+/// others. The handler must be registered for the callbacks to run.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::OnTimeout;
+/// use std::time::Duration;
+///
+/// ctrlc::set_handler(|| println!("Shutting down...")).expect("Error setting Ctrl-C handler");
+///
+/// ctrlc::on_shutdown(0, || println!("Stop accepting connections"));
+/// ctrlc::on_shutdown(10, || println!("Flush the write-ahead log"))
+///     .timeout(Duration::from_secs(5), OnTimeout::Skip);
+/// ctrlc::on_shutdown(20, || println!("Close the database"));
+/// ```
+pub fn on_shutdown<F>(priority: i32, callback: F) -> ShutdownCallback
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut callbacks = lock();
+    let id = callbacks.next_id;
+    callbacks.next_id += 1;
+    callbacks.entries.push(Entry {
+        id,
+        priority,
+        timeout: None,
+        callback: Box::new(callback),
+    });
+    ShutdownCallback { id }
+}
+
+/// A callback registered with [`on_shutdown()`].
+#[derive(Debug)]
+pub struct ShutdownCallback {
+    id: u64,
+}
+
+impl ShutdownCallback {
+    /// Give the callback at most `timeout` to finish, then handle it according to `on_timeout`.
+    /// Without a timeout the next callbacks wait for it as long as it takes.
+    pub fn timeout(self, timeout: Duration, on_timeout: OnTimeout) -> Self {
+        let mut callbacks = lock();
+        if let Some(entry) = callbacks
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == self.id)
+        {
+            entry.timeout = Some((timeout, on_timeout));
+        }
+        self
+    }
+}
+
+/// Run the callbacks registered with [`on_shutdown()`] if `signal` asks the process to
+/// terminate, `None` for a requested shutdown. Each callback runs only once.
+pub(crate) fn run(signal: Option<Signal>) {
+    if !signal.map_or(true, platform::is_termination) {
+        return;
+    }
+    let mut entries = std::mem::take(&mut lock().entries);
+    if entries.is_empty() {
+        return;
+    }
+    // Stable, so callbacks with the same priority keep the registration order.
+    entries.sort_by_key(|entry| entry.priority);
+
+    let mut orchestrator = Orchestrator::new();
+    let mut previous: Option<String> = None;
+    for entry in entries {
+        let name = format!("on_shutdown #{}", entry.id);
+        let after: Vec<&str> = previous.as_deref().into_iter().collect();
+        let options = orchestrator
+            .add_task(&name, &after, entry.callback)
+            .expect("a chain of unique names");
+        if let Some((timeout, on_timeout)) = entry.timeout {
+            options.timeout(timeout, on_timeout);
+        }
+        previous = Some(name);
+    }
+    for task in orchestrator.run().timed_out() {
+        eprintln!("[ctrlc] Shutdown callback {:?} timed out", task.name());
+    }
+}
+
+pub(crate) fn clear() {
+    lock().entries.clear();
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+type Consumer = Box<dyn FnOnce() + Send>;
+
+struct Stack {
+    next_id: u64,
+    consumers: Vec<(u64, Consumer)>,
+}
+
+static STACK: Mutex<Stack> = Mutex::new(Stack {
+    next_id: 0,
+    consumers: Vec::new(),
+});
+
+/// Length of the stack, so that the dispatch of signals nobody consumes doesn't lock. Updated
+/// with the stack locked.
+static LEN: AtomicUsize = AtomicUsize::new(0);
+
+fn lock() -> MutexGuard<'static, Stack> {
+    STACK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Scope of a consumer pushed with [`push_consumer()`]. Dropping it removes the consumer if it
+/// has not received an interrupt.
+#[derive(Debug)]
+#[must_use = "dropping the guard removes the consumer immediately"]
+pub struct ConsumerGuard {
+    id: u64,
+}
+
+impl Drop for ConsumerGuard {
+    fn drop(&mut self) {
+        let mut stack = lock();
+        stack.consumers.retain(|&(id, _)| id != self.id);
+        LEN.store(stack.consumers.len(), Ordering::SeqCst);
+    }
+}
+
+/// Push `consumer` on the interrupt consumer stack.
+///
+/// The next Ctrl-C pops the innermost consumer and runs it on the signal handling thread instead
+/// of the handler, e.g. to cancel the running query like psql. With an empty stack, Ctrl-C falls
+/// through to the handler. Consumed signals are not counted towards `force_after` or storm
+/// protection and do not start the shutdown.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// ctrlc::set_handler(|| std::process::exit(130)).expect("Error setting Ctrl-C handler");
+///
+/// let cancelled = Arc::new(AtomicBool::new(false));
+/// let _guard = ctrlc::push_consumer({
+///     let cancelled = cancelled.clone();
+///     move || cancelled.store(true, Ordering::SeqCst)
+/// });
+/// // ... run the query until it completes or `cancelled` is set ...
+/// ```
+pub fn push_consumer<F>(consumer: F) -> ConsumerGuard
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut stack = lock();
+    let id = stack.next_id;
+    stack.next_id += 1;
+    stack.consumers.push((id, Box::new(consumer)));
+    LEN.store(stack.consumers.len(), Ordering::SeqCst);
+    ConsumerGuard { id }
+}
+
+/// Pop and run the innermost consumer, returns whether there was one.
+pub(crate) fn dispatch() -> bool {
+    if LEN.load(Ordering::SeqCst) == 0 {
+        return false;
+    }
+    let consumer = {
+        let mut stack = lock();
+        let consumer = stack.consumers.pop();
+        LEN.store(stack.consumers.len(), Ordering::SeqCst);
+        consumer
+    };
+    match consumer {
+        Some((_, consumer)) => {
+            consumer();
+            true
+        }
+        None => false,
+    }
+}
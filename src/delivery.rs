@@ -0,0 +1,23 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+/// How the handler runs when a signal arrives while it is still running for a previous one, see
+/// [`Builder::delivery()`](struct.Builder.html#method.delivery).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Delivery {
+    /// Run the handler once per signal, one after the other on the handler thread.
+    #[default]
+    Serialized,
+    /// Run the handler once for all the signals received while it was running.
+    Coalesced,
+    /// Run the handler on a new thread for every signal. Requires a handler registered with
+    /// [`Builder::set_sync_handler()`](struct.Builder.html#method.set_sync_handler).
+    Concurrent,
+}
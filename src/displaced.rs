@@ -0,0 +1,64 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::platform::{self, Signal};
+
+/// What was installed for a signal before the OS handler of this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PreviousAction {
+    /// The default action of the signal.
+    Default,
+    /// The signal was ignored, e.g. `SIGHUP` under `nohup`.
+    Ignore,
+    /// A handler function, by address. Includes `SA_SIGINFO` handlers.
+    Handler(usize),
+    /// Can't be found out. Console handler routines on Windows can't be listed.
+    Unknown,
+}
+
+/// What the OS handler found installed for one of its signals, see
+/// [`HandlerInfo::displaced()`](struct.HandlerInfo.html#method.displaced).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Displaced {
+    signal: Signal,
+    previous: PreviousAction,
+    replaced: bool,
+}
+
+impl Displaced {
+    pub(crate) fn new(signal: Signal, previous: PreviousAction, replaced: bool) -> Displaced {
+        Displaced {
+            signal,
+            previous,
+            replaced,
+        }
+    }
+
+    /// The signal.
+    pub fn signal(&self) -> crate::Signal {
+        platform::to_signal(self.signal)
+    }
+
+    /// The signal as the platform sees it.
+    pub(crate) fn platform_signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// What was installed before.
+    pub fn previous(&self) -> PreviousAction {
+        self.previous
+    }
+
+    /// Whether the OS handler took over the signal. `false` if it was left alone, see
+    /// [`InstallPolicy::ReplaceIfDefault`](enum.InstallPolicy.html#variant.ReplaceIfDefault).
+    pub fn is_replaced(&self) -> bool {
+        self.replaced
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::platform;
+use crate::SignalType;
+use std::fmt;
+use std::time::Duration;
+
+/// Comma separated list of signals to handle, e.g. `INT,TERM`.
+pub const SIGNALS_VAR: &str = "CTRLC_SIGNALS";
+/// Grace period in seconds after the first signal before the process is forcibly exited.
+pub const GRACE_SECS_VAR: &str = "CTRLC_GRACE_SECS";
+/// Number of received signals after which the process is forcibly exited.
+pub const FORCE_AFTER_VAR: &str = "CTRLC_FORCE_AFTER";
+
+/// Error parsing a `CTRLC_*` environment variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvError {
+    var: &'static str,
+    value: String,
+    reason: &'static str,
+}
+
+impl EnvError {
+    fn new(var: &'static str, value: &str, reason: &'static str) -> EnvError {
+        EnvError {
+            var,
+            value: value.to_owned(),
+            reason,
+        }
+    }
+
+    /// Name of the offending environment variable.
+    pub fn var(&self) -> &'static str {
+        self.var
+    }
+
+    /// Value the variable was set to.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {}={:?}: {}", self.var, self.value, self.reason)
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+/// Overrides read from the `CTRLC_*` environment variables. Unset variables are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct EnvOverrides {
+    pub signals: Option<Vec<SignalType>>,
+    pub grace_period: Option<Duration>,
+    pub force_after: Option<u32>,
+}
+
+impl EnvOverrides {
+    pub fn read() -> Result<EnvOverrides, EnvError> {
+        Ok(EnvOverrides {
+            signals: read_var(SIGNALS_VAR, parse_signals)?,
+            grace_period: read_var(GRACE_SECS_VAR, parse_grace_secs)?,
+            force_after: read_var(FORCE_AFTER_VAR, parse_force_after)?,
+        })
+    }
+}
+
+fn read_var<T>(
+    var: &'static str,
+    parse: fn(&str) -> Result<T, &'static str>,
+) -> Result<Option<T>, EnvError> {
+    match std::env::var(var) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => parse(&value)
+            .map(Some)
+            .map_err(|reason| EnvError::new(var, &value, reason)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(value)) => Err(EnvError::new(
+            var,
+            &value.to_string_lossy(),
+            "not valid unicode",
+        )),
+    }
+}
+
+fn parse_signals(value: &str) -> Result<Vec<SignalType>, &'static str> {
+    value
+        .split(',')
+        .map(|name| {
+            platform::parse_signal(name)
+                .map(|signal| SignalType::Other(platform::to_signal(signal)))
+                .ok_or("unknown signal name")
+        })
+        .collect()
+}
+
+fn parse_grace_secs(value: &str) -> Result<Duration, &'static str> {
+    let secs: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| "expected a number of seconds")?;
+    Duration::try_from_secs_f64(secs).map_err(|_| "expected a non-negative number of seconds")
+}
+
+fn parse_force_after(value: &str) -> Result<u32, &'static str> {
+    match value.trim().parse() {
+        Ok(0) => Err("expected a signal count of at least 1"),
+        Ok(count) => Ok(count),
+        Err(_) => Err("expected a signal count"),
+    }
+}
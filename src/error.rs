@@ -1,5 +1,6 @@
 use crate::platform;
 use std::fmt;
+use std::io;
 
 /// Ctrl-C error.
 #[derive(Debug)]
@@ -9,9 +10,18 @@ pub enum Error {
     /// Ctrl-C signal handler already registered.
     MultipleHandlers,
     /// Unexpected system error.
-    System(std::io::Error),
+    System(io::Error),
     /// Handler was removed
     HandlerRemoved,
+    /// A `CTRLC_*` environment variable has an invalid value.
+    Env(crate::EnvError),
+    /// A shutdown task with this name is already registered.
+    DuplicateTask(String),
+    /// Registering a shutdown task would create this dependency cycle.
+    DependencyCycle(Vec<String>),
+    /// Installing the handler failed with the first error, and restoring the previous handlers
+    /// failed with the others. Some signals may be left with the handler of this crate.
+    Rollback(Box<Error>, Vec<io::Error>),
 }
 
 impl Error {
@@ -21,25 +31,83 @@ impl Error {
             Error::MultipleHandlers => "Ctrl-C signal handler already registered",
             Error::System(_) => "Unexpected system error",
             Error::HandlerRemoved => "Handler was removed",
+            Error::Env(_) => "Invalid environment configuration",
+            Error::DuplicateTask(_) => "Shutdown task already registered",
+            Error::DependencyCycle(_) => "Shutdown task dependencies form a cycle",
+            Error::Rollback(..) => "Could not restore the previous handlers after a failure",
+        }
+    }
+}
+
+impl Error {
+    /// The OS error code of an [`Error::System`], if it originates from the OS.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match *self {
+            Error::System(ref e) => e.raw_os_error(),
+            Error::Rollback(ref e, _) => e.raw_os_error(),
+            _ => None,
+        }
+    }
+
+    fn io_error_kind(&self) -> io::ErrorKind {
+        match *self {
+            Error::NoSuchSignal(_) => io::ErrorKind::InvalidInput,
+            Error::MultipleHandlers => io::ErrorKind::AlreadyExists,
+            Error::System(ref e) => e.kind(),
+            Error::HandlerRemoved => io::ErrorKind::NotFound,
+            Error::Env(_) => io::ErrorKind::InvalidInput,
+            Error::DuplicateTask(_) => io::ErrorKind::AlreadyExists,
+            Error::DependencyCycle(_) => io::ErrorKind::InvalidInput,
+            Error::Rollback(ref e, _) => e.io_error_kind(),
         }
     }
 }
 
 impl From<platform::Error> for Error {
     fn from(e: platform::Error) -> Error {
-        #[cfg(not(windows))]
+        #[cfg(all(unix, not(target_os = "espidf")))]
         if e == platform::Error::EEXIST {
             return Error::MultipleHandlers;
         }
 
-        let system_error = std::io::Error::new(std::io::ErrorKind::Other, e);
-        Error::System(system_error)
+        // Keeps the OS error code and message, see `Error::raw_os_error`.
+        #[cfg(all(unix, not(target_os = "espidf")))]
+        let e = io::Error::from(e);
+
+        Error::System(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        match e {
+            Error::System(e) => e,
+            e => io::Error::new(e.io_error_kind(), e),
+        }
+    }
+}
+
+impl From<crate::EnvError> for Error {
+    fn from(e: crate::EnvError) -> Error {
+        Error::Env(e)
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Ctrl-C error: {}", self.describe())
+        write!(f, "Ctrl-C error: {}", self.describe())?;
+        match *self {
+            Error::System(ref e) => write!(f, ": {}", e),
+            Error::Env(ref e) => write!(f, ": {}", e),
+            Error::Rollback(ref e, ref failures) => {
+                write!(f, ": {}; while restoring:", e)?;
+                for failure in failures {
+                    write!(f, " {};", failure)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
     }
 }
 
@@ -51,6 +119,8 @@ impl std::error::Error for Error {
     fn cause(&self) -> Option<&dyn std::error::Error> {
         match *self {
             Error::System(ref e) => Some(e),
+            Error::Env(ref e) => Some(e),
+            Error::Rollback(ref e, _) => Some(e.as_ref()),
             _ => None,
         }
     }
@@ -0,0 +1,202 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::platform::{self, Signal};
+use crate::{SignalEvent, SignalType};
+use std::io::{self, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Exit code used when the process is forcibly exited by the grace period, the watchdog or
+/// `force_after`.
+const FORCED_EXIT_CODE: i32 = 1;
+
+/// How long output gets to drain before the process exits anyway.
+const FLUSH_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// How the process is terminated when the crate forces an exit.
+///
+/// Running `atexit` handlers and destructors during a forced shutdown can itself deadlock, e.g.
+/// on a lock held by the wedged handler, so the more drastic mechanisms skip them. stdout and
+/// stderr are flushed in all cases.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExitMechanism {
+    /// [`std::process::exit`], runs `atexit` handlers.
+    #[default]
+    Exit,
+    /// `_exit(2)` on Unix and `TerminateProcess` on Windows, skipping `atexit` handlers.
+    Immediate,
+    /// [`std::process::abort`], which produces a core dump where enabled.
+    Abort,
+    /// Put the default action of the signal back and raise it again, so the process dies by
+    /// the signal as if there was no handler. Stages without a signal use the Ctrl-C signal,
+    /// signals whose default action doesn't terminate fall back to `_exit(128 + signal)`. On
+    /// Windows, `TerminateProcess` with `STATUS_CONTROL_C_EXIT` like the default handler
+    /// routine.
+    Reraise,
+}
+
+/// The reasons for the crate to force an exit, see
+/// [`Builder::exit_mechanism()`](struct.Builder.html#method.exit_mechanism).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EscalationStage {
+    /// The signal count of [`Builder::force_after()`](struct.Builder.html#method.force_after)
+    /// was reached.
+    ForceAfter,
+    /// Too many signals arrived, see
+    /// [`Builder::storm_protection()`](struct.Builder.html#method.storm_protection).
+    Storm,
+    /// The [`Builder::grace_period()`](struct.Builder.html#method.grace_period) expired.
+    GracePeriod,
+    /// Another signal arrived soon after the previous one, see
+    /// [`Builder::force_on_repeat()`](struct.Builder.html#method.force_on_repeat).
+    Repeat,
+    /// The [`Builder::watchdog()`](struct.Builder.html#method.watchdog) timeout expired.
+    Watchdog,
+}
+
+/// [`ExitMechanism`] of each [`EscalationStage`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExitPolicy {
+    force_after: ExitMechanism,
+    storm: ExitMechanism,
+    grace_period: ExitMechanism,
+    repeat: ExitMechanism,
+    watchdog: ExitMechanism,
+}
+
+impl Default for ExitPolicy {
+    fn default() -> ExitPolicy {
+        ExitPolicy {
+            force_after: ExitMechanism::Exit,
+            storm: ExitMechanism::Exit,
+            grace_period: ExitMechanism::Exit,
+            repeat: ExitMechanism::Reraise,
+            watchdog: ExitMechanism::Exit,
+        }
+    }
+}
+
+impl ExitPolicy {
+    pub(crate) fn set(&mut self, stage: EscalationStage, mechanism: ExitMechanism) {
+        match stage {
+            EscalationStage::ForceAfter => self.force_after = mechanism,
+            EscalationStage::Storm => self.storm = mechanism,
+            EscalationStage::GracePeriod => self.grace_period = mechanism,
+            EscalationStage::Repeat => self.repeat = mechanism,
+            EscalationStage::Watchdog => self.watchdog = mechanism,
+        }
+    }
+
+    /// Terminate the process for `stage`, which was reached by `signal` if any.
+    pub(crate) fn exit(&self, stage: EscalationStage, signal: Option<Signal>) -> ! {
+        let mechanism = match stage {
+            EscalationStage::ForceAfter => self.force_after,
+            EscalationStage::Storm => self.storm,
+            EscalationStage::GracePeriod => self.grace_period,
+            EscalationStage::Repeat => self.repeat,
+            EscalationStage::Watchdog => self.watchdog,
+        };
+        flush_output();
+        match mechanism {
+            ExitMechanism::Exit => std::process::exit(FORCED_EXIT_CODE),
+            ExitMechanism::Immediate => platform::exit_immediately(FORCED_EXIT_CODE),
+            ExitMechanism::Abort => std::process::abort(),
+            ExitMechanism::Reraise => platform::exit_with_signal(
+                signal.unwrap_or_else(|| platform::signals_of(&SignalType::Ctrlc)[0]),
+            ),
+        }
+    }
+}
+
+/// Terminate the process by `signal` as if it had no handler, after flushing stdout and stderr.
+///
+/// Puts the default action of the signal back and raises it again, so the parent sees the
+/// process killed by the signal, e.g. a shell reports status 130 for `SIGINT`, instead of a
+/// normal exit. Shells stop a script on a child killed by `SIGINT`, and CI systems and job
+/// control wrappers rely on the same convention. Falls back to `_exit(128 + signal)` for
+/// signals whose default action doesn't terminate. On Windows the process ends with
+/// `STATUS_CONTROL_C_EXIT` like with the default handler routine. See
+/// [`Builder::reraise()`](struct.Builder.html#method.reraise) to do it once the shutdown is
+/// finished. A signal without an equivalent on the current platform is taken for Ctrl-C.
+///
+/// # Example
+/// ```no_run
+/// let handle = ctrlc::Handle::new(&ctrlc::default_signals(), true)
+///     .expect("Error setting Ctrl-C handler");
+/// let signal = handle.wait_signal().expect("Error waiting for Ctrl-C");
+/// println!("Cleaning up...");
+/// if let Some(signal) = signal {
+///     ctrlc::reraise(signal);
+/// }
+/// ```
+pub fn reraise(signal: crate::Signal) -> ! {
+    flush_output();
+    let signal = platform::from_signal(signal)
+        .unwrap_or_else(|| platform::signals_of(&crate::SignalType::Ctrlc)[0]);
+    platform::exit_with_signal(signal)
+}
+
+/// Exit the process with the status a shell reports for the signal of `event`, see
+/// [`SignalEvent::exit_code()`](struct.SignalEvent.html#method.exit_code), after flushing
+/// stdout and stderr.
+///
+/// Unlike [`reraise()`] the process exits normally, running `atexit` handlers, so only the
+/// status tells the parent about the signal.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// // ... start the workers ...
+///
+/// if let Some(event) = ctrlc::signals().next() {
+///     println!("Cleaning up...");
+///     ctrlc::exit_on(&event);
+/// }
+/// ```
+pub fn exit_on(event: &SignalEvent) -> ! {
+    flush_output();
+    std::process::exit(event.exit_code().into())
+}
+
+/// Abort the process after flushing stdout and stderr.
+pub(crate) fn abort() -> ! {
+    flush_output();
+    std::process::abort()
+}
+
+/// Flush stdout and stderr and keep them locked, so the last lines aren't lost or interleaved
+/// with partial output. Gives up after `FLUSH_TIMEOUT`, e.g. if a wedged thread holds a lock
+/// or the output is a full pipe.
+fn flush_output() {
+    let (flushed_tx, flushed_rx) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name("ctrl-c-flush".into())
+        .spawn(move || {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            let _ = stdout.flush();
+            let stderr = io::stderr();
+            let mut stderr = stderr.lock();
+            let _ = stderr.flush();
+            let _ = flushed_tx.send(());
+
+            // Hold the locks until the process is gone.
+            loop {
+                thread::park();
+            }
+        });
+    if spawned.is_ok() {
+        let _ = flushed_rx.recv_timeout(FLUSH_TIMEOUT);
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Signals forwarded by a host runtime, see
+//! [`Builder::external()`](../struct.Builder.html#method.external).
+
+use crate::error::Error;
+use crate::platform::{self, Signal};
+use crate::SignalType;
+use std::sync::{Mutex, MutexGuard};
+
+/// Signals of the handler registered with `Builder::external(true)`, `None` otherwise.
+static HANDLED: Mutex<Option<Vec<Signal>>> = Mutex::new(None);
+
+fn lock() -> MutexGuard<'static, Option<Vec<Signal>>> {
+    HANDLED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Deliver a signal received by the host runtime (JNI, cgo, Python, ...) to the handler.
+///
+/// Runs the same pipeline as a signal received by the OS handler: the handler, the
+/// orchestrator tasks, `force_after` counting and the grace period. Returns `false` without
+/// doing anything if the handler does not handle `kind`.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::SignalType;
+///
+/// ctrlc::Builder::new()
+///     .external(true)
+///     .set_handler(|| println!("Shutting down..."))
+///     .expect("Error setting Ctrl-C handler");
+///
+/// // From the host's signal callback:
+/// ctrlc::external::deliver(SignalType::Ctrlc).unwrap();
+/// ```
+///
+/// # Errors
+/// Will return [`Error::HandlerRemoved`] if no handler was registered with
+/// `Builder::external(true)`, or an error if a system error occurred.
+pub fn deliver(kind: SignalType) -> Result<bool, Error> {
+    let handled = lock();
+    let handled = match *handled {
+        Some(ref handled) => handled,
+        None => return Err(Error::HandlerRemoved),
+    };
+    let signal = match platform::signals_of(&kind)
+        .into_iter()
+        .find(|signal| handled.contains(signal))
+    {
+        Some(signal) => signal,
+        None => return Ok(false),
+    };
+    unsafe { platform::inject_signal(Some(signal))? };
+    Ok(true)
+}
+
+pub(crate) fn set_handled(signals: Vec<Signal>) {
+    *lock() = Some(signals);
+}
+
+pub(crate) fn clear() {
+    *lock() = None;
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Number of signals received by the handler, exported unmangled for C and Fortran code.
+///
+/// Numerical kernels called from Rust can poll it inside their own loops without calling back
+/// into Rust. Requires the `ffi` feature.
+///
+/// ```c
+/// #include <stdatomic.h>
+///
+/// extern _Atomic unsigned int CTRLC_INTERRUPTED;
+///
+/// for (size_t i = 0; i < n && atomic_load(&CTRLC_INTERRUPTED) == 0; i++) {
+///     /* ... */
+/// }
+/// ```
+#[no_mangle]
+pub static CTRLC_INTERRUPTED: AtomicU32 = AtomicU32::new(0);
+
+/// Count a received signal in [`CTRLC_INTERRUPTED`].
+pub(crate) fn record_signal() {
+    // Saturating, so a long running process never wraps around to "not interrupted".
+    let _ = CTRLC_INTERRUPTED.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+        count.checked_add(1)
+    });
+}
@@ -0,0 +1,69 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::{platform, Error};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether a signal was received since [`set_flag()`].
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Whether [`set_flag()`] installed its handler, it is never removed.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Handle Ctrl-C by only setting a flag, polled with [`shutdown_requested()`].
+///
+/// The OS handler stores to an atomic and nothing else: no thread is started and no pipe or
+/// other descriptor is opened, for embedded and latency-sensitive programs that poll anyway.
+/// The handler stays installed for the rest of the process and counts as the registered
+/// handler, so [`set_handler()`](fn.set_handler.html) fails afterwards. Builder options,
+/// listeners and the shutdown lifecycle need a handler thread and don't apply. Calling this
+/// again has no effect.
+///
+/// On Unix, the default signals get a plain signal handler whichever backend features are
+/// enabled, replacing the actions in place.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_flag().expect("Error setting Ctrl-C flag");
+///
+/// while !ctrlc::shutdown_requested() {
+///     // ... next step of the control loop ...
+/// }
+/// ```
+///
+/// # Errors
+/// Will return [`Error::MultipleHandlers`] if a handler is already registered, or an error if
+/// a system error occurred. Always fails on ESP-IDF, which has no signals.
+pub fn set_flag() -> Result<(), Error> {
+    let _guard = crate::INIT_LOCK.lock().unwrap();
+    if INSTALLED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    if crate::INIT.load(Ordering::Relaxed) {
+        return Err(Error::MultipleHandlers);
+    }
+    unsafe { platform::init_flag_handler(&platform::default_signals())? };
+    INSTALLED.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Whether a signal was received since [`set_flag()`]. Never true without it.
+pub fn shutdown_requested() -> bool {
+    REQUESTED.load(Ordering::Acquire)
+}
+
+/// Whether [`set_flag()`] installed its handler.
+pub(crate) fn is_installed() -> bool {
+    INSTALLED.load(Ordering::Acquire)
+}
+
+/// Set the flag, called from the OS handler and async-signal-safe.
+#[cfg_attr(target_os = "espidf", allow(dead_code))]
+pub(crate) fn record_signal() {
+    REQUESTED.store(true, Ordering::Release);
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::exit::{EscalationStage, ExitPolicy};
+use crate::platform::Signal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static DEADLINE: Mutex<Option<Instant>> = Mutex::new(None);
+/// Bumped by [`clear()`], timers started before don't exit anymore.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+fn lock() -> MutexGuard<'static, Option<Instant>> {
+    DEADLINE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Time left until the grace period or the watchdog forcibly exits the process, `None` if
+/// neither is running.
+///
+/// Lets shutdown tasks budget themselves instead of being killed halfway.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// fn shutdown_storage() {
+///     if ctrlc::grace_remaining().map_or(true, |left| left > Duration::from_secs(5)) {
+///         // Optional compaction, only if there is time for it.
+///     }
+/// }
+/// ```
+pub fn grace_remaining() -> Option<Duration> {
+    lock().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Exit the process with `exit_policy` for `stage` once `timeout` has passed, re-raising
+/// `signal` for [`ExitMechanism::Reraise`](crate::ExitMechanism::Reraise).
+pub(crate) fn start_timer(
+    timeout: Duration,
+    exit_policy: ExitPolicy,
+    stage: EscalationStage,
+    signal: Option<Signal>,
+) {
+    let mut deadline = lock();
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let spawned = thread::Builder::new()
+        .name("ctrl-c-grace".into())
+        .spawn(move || {
+            thread::sleep(timeout);
+            if GENERATION.load(Ordering::SeqCst) == generation {
+                exit_policy.exit(stage, signal);
+            }
+        });
+    match spawned {
+        // The first timer to run out exits, a timeout too long for an `Instant` never does.
+        Ok(_) => {
+            if let Some(at) = Instant::now().checked_add(timeout) {
+                *deadline = Some(deadline.map_or(at, |deadline| deadline.min(at)));
+            }
+        }
+        Err(_) => eprintln!("[ctrlc] Could not start the grace period timer"),
+    }
+}
+
+/// Cancel the running timers once the handler was removed.
+pub(crate) fn clear() {
+    let mut deadline = lock();
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    *deadline = None;
+}
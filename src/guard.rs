@@ -0,0 +1,54 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::builder;
+use crate::handlers;
+use std::thread::JoinHandle;
+
+/// Keeps a handler registered with
+/// [`Builder::set_handler_guarded()`](struct.Builder.html#method.set_handler_guarded) alive.
+///
+/// Dropping the guard removes the handler like
+/// [`remove_all_handlers()`](fn.remove_all_handlers.html), restoring the signal actions that were
+/// in place before it was registered. It then waits for the handler threads to finish, unless it
+/// is dropped on one of them. A handler that was already removed
+/// is left alone, and so is a handler registered after that.
+#[derive(Debug)]
+#[must_use = "dropping the guard removes the handler immediately"]
+pub struct CtrlcGuard {
+    id: Option<u64>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CtrlcGuard {
+    /// Guard the registered handler, with `thread` as its signal handling thread.
+    pub(crate) fn new(thread: JoinHandle<()>) -> CtrlcGuard {
+        CtrlcGuard {
+            id: registered(),
+            thread: Some(thread),
+        }
+    }
+}
+
+fn registered() -> Option<u64> {
+    handlers::snapshot().and_then(|handlers| handlers.first().map(|handler| handler.id()))
+}
+
+impl Drop for CtrlcGuard {
+    fn drop(&mut self) {
+        if self.id.is_some() && registered() == self.id {
+            let _ = crate::remove_all_handlers();
+        }
+        if let Some(thread) = self.thread.take() {
+            if !builder::is_handler_thread() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::platform::{self, Signal};
+use crate::Displaced;
+use arc_swap::ArcSwapOption;
+use std::panic::Location;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Readers load the current snapshot without locking. Writers serialize on `WRITER`, which also
+// holds the next id, and publish a new snapshot.
+static HANDLERS: ArcSwapOption<Vec<HandlerInfo>> = ArcSwapOption::const_empty();
+static WRITER: Mutex<u64> = Mutex::new(0);
+
+fn lock() -> MutexGuard<'static, u64> {
+    WRITER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Current snapshot of the registered handlers, never blocks.
+pub(crate) fn snapshot() -> Option<Arc<Vec<HandlerInfo>>> {
+    HANDLERS.load_full()
+}
+
+/// Information about a registered handler, see [`handlers()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerInfo {
+    id: u64,
+    signals: Vec<Signal>,
+    displaced: Vec<Displaced>,
+    once: bool,
+    location: Option<&'static Location<'static>>,
+}
+
+impl HandlerInfo {
+    /// Identifier of the registration, unique for the lifetime of the process.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Signals the handler runs for.
+    pub fn signals(&self) -> Vec<crate::Signal> {
+        self.signals
+            .iter()
+            .map(|&signal| platform::to_signal(signal))
+            .collect()
+    }
+
+    /// Signals the handler runs for, as the platform sees them.
+    pub(crate) fn platform_signals(&self) -> &[Signal] {
+        &self.signals
+    }
+
+    /// What was installed for the signals before the handler, in the order they were
+    /// requested. Signals the handler left alone are included, see
+    /// [`Displaced::is_replaced()`](struct.Displaced.html#method.is_replaced).
+    ///
+    /// Signals added later with [`add_signal()`](fn.add_signal.html) are not listed.
+    pub fn displaced(&self) -> &[Displaced] {
+        &self.displaced
+    }
+
+    /// Whether the handler was registered with `set_handler_once()` and runs at most once.
+    pub fn is_once(&self) -> bool {
+        self.once
+    }
+
+    /// Where the handler was registered. Only recorded with `debug_assertions` enabled.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+}
+
+/// List the currently registered handlers.
+///
+/// Useful to find out which part of a large application registered a handler, or to assert in
+/// tests that the expected handlers are present.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+///
+/// for handler in ctrlc::handlers() {
+///     println!("{:?} registered at {:?}", handler.signals(), handler.location());
+/// }
+/// ```
+pub fn handlers() -> Vec<HandlerInfo> {
+    snapshot().map_or_else(Vec::new, |handlers| handlers.to_vec())
+}
+
+/// Location of the caller when `debug_assertions` are enabled.
+#[track_caller]
+pub(crate) fn caller_location() -> Option<&'static Location<'static>> {
+    if cfg!(debug_assertions) {
+        Some(Location::caller())
+    } else {
+        None
+    }
+}
+
+pub(crate) fn register(
+    signals: Vec<Signal>,
+    displaced: Vec<Displaced>,
+    once: bool,
+    location: Option<&'static Location<'static>>,
+) {
+    let mut next_id = lock();
+    let id = *next_id;
+    *next_id += 1;
+
+    let mut handlers = snapshot().map_or_else(Vec::new, |handlers| handlers.to_vec());
+    handlers.push(HandlerInfo {
+        id,
+        signals,
+        displaced,
+        once,
+        location,
+    });
+    HANDLERS.store(Some(Arc::new(handlers)));
+}
+
+/// Apply `update` to the signals of every registered handler.
+pub(crate) fn update_signals<F>(update: F)
+where
+    F: Fn(&mut Vec<Signal>),
+{
+    let _next_id = lock();
+    if let Some(handlers) = snapshot() {
+        let mut handlers = handlers.to_vec();
+        for handler in handlers.iter_mut() {
+            update(&mut handler.signals);
+        }
+        HANDLERS.store(Some(Arc::new(handlers)));
+    }
+}
+
+pub(crate) fn clear() {
+    let _next_id = lock();
+    HANDLERS.store(None);
+}
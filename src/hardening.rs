@@ -0,0 +1,54 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::exit;
+use crate::lifecycle::{lifecycle, LifecycleState};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static HARDENED: AtomicBool = AtomicBool::new(false);
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+/// Abort instead of running the shutdown when a signal and a panic meet.
+///
+/// Installs a panic hook, chained with the current one, so that a signal received after a
+/// thread has panicked aborts the process instead of running the handler, and a panic while the
+/// shutdown is in progress aborts it too. This prevents the deadlocks where the handler locks
+/// state that a panicking thread holds.
+///
+/// Any panic counts, including ones caught with `catch_unwind`, so this suits programs where
+/// panics are fatal. Calling this more than once has no effect.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::harden_panic_path();
+/// ctrlc::set_handler(|| println!("Shutting down...")).expect("Error setting Ctrl-C handler");
+/// ```
+pub fn harden_panic_path() {
+    if HARDENED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        PANICKED.store(true, Ordering::SeqCst);
+        previous(info);
+        if lifecycle() == LifecycleState::Draining {
+            eprintln!("[ctrlc] Panicked during shutdown, aborting");
+            exit::abort();
+        }
+    }));
+}
+
+/// Abort if a signal was received after a panic with [`harden_panic_path()`] enabled.
+pub(crate) fn check_signal() {
+    if HARDENED.load(Ordering::SeqCst) && PANICKED.load(Ordering::SeqCst) {
+        eprintln!("[ctrlc] Signal received after a panic, aborting");
+        exit::abort();
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::grace;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+static INFLIGHT: Mutex<usize> = Mutex::new(0);
+static DRAINED: Condvar = Condvar::new();
+
+fn lock() -> MutexGuard<'static, usize> {
+    INFLIGHT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Permit of an in-flight request, see [`inflight()`].
+#[derive(Debug)]
+#[must_use = "dropping the permit ends the request immediately"]
+pub struct InflightPermit {
+    _private: (),
+}
+
+impl Drop for InflightPermit {
+    fn drop(&mut self) {
+        let mut inflight = lock();
+        *inflight -= 1;
+        if *inflight == 0 {
+            DRAINED.notify_all();
+        }
+    }
+}
+
+/// Mark a request as in flight until the returned permit is dropped.
+///
+/// After the handler has returned for the first signal, shutdown waits for all permits to be
+/// dropped before running the [`Orchestrator`](struct.Orchestrator.html) tasks that close
+/// resources, so requests complete instead of being cut off. The wait is bounded by the grace
+/// period, if there is one.
+///
+/// # Example
+/// ```no_run
+/// fn handle_request() {
+///     let _permit = ctrlc::inflight();
+///     // ... shutdown does not close resources while the request is served ...
+/// }
+/// ```
+pub fn inflight() -> InflightPermit {
+    *lock() += 1;
+    InflightPermit { _private: () }
+}
+
+/// Number of requests in flight.
+pub fn inflight_count() -> usize {
+    *lock()
+}
+
+/// Block until no request is in flight, or until the grace period runs out.
+pub(crate) fn drain() {
+    let deadline = grace::grace_remaining().and_then(|left| Instant::now().checked_add(left));
+    let mut inflight = lock();
+    while *inflight > 0 {
+        let remaining = match deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => Duration::MAX,
+        };
+        if remaining.is_zero() {
+            break;
+        }
+        inflight = DRAINED
+            .wait_timeout(inflight, remaining)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+    if *inflight > 0 {
+        eprintln!(
+            "[ctrlc] {} in-flight requests did not finish in time",
+            *inflight
+        );
+    }
+}
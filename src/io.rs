@@ -0,0 +1,65 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! I/O that stops blocking when the shutdown starts.
+
+use crate::lifecycle::{self, LifecycleState};
+use crate::platform;
+use std::io::{self, Read};
+use std::net::TcpStream;
+use std::sync::Once;
+
+static LISTENING: Once = Once::new();
+
+/// Read from `stream` like [`Read::read()`], but return an [`io::ErrorKind::Interrupted`]
+/// error once the shutdown starts, i.e. the lifecycle leaves
+/// [`Running`](../enum.LifecycleState.html#variant.Running).
+///
+/// Lets threaded servers stop blocking reads without per-socket timeouts. Waits with `poll(2)`
+/// on Unix and `WSAEventSelect` on Windows, not supported on ESP-IDF.
+///
+/// # Example
+/// ```no_run
+/// use std::io::ErrorKind;
+/// use std::net::TcpStream;
+///
+/// fn serve(stream: TcpStream) -> std::io::Result<()> {
+///     let mut buf = [0u8; 4096];
+///     loop {
+///         match ctrlc::io::read_interruptible(&stream, &mut buf) {
+///             Ok(0) => return Ok(()),
+///             Ok(n) => { /* ... handle &buf[..n] ... */ }
+///             Err(e) if e.kind() == ErrorKind::Interrupted => return Ok(()),
+///             Err(e) => return Err(e),
+///         }
+///     }
+/// }
+/// ```
+///
+/// # Errors
+/// Will return an `Interrupted` error during shutdown, or the error of the read or the wait.
+pub fn read_interruptible(stream: &TcpStream, buf: &mut [u8]) -> io::Result<usize> {
+    LISTENING.call_once(|| {
+        lifecycle::on_lifecycle_change(|state| {
+            // Moving back to `Running` resumes the readers, see `lifecycle::reset()`.
+            if state != LifecycleState::Running {
+                platform::interrupt_readers();
+            }
+        })
+    });
+    platform::prepare_interruptible()?;
+    if lifecycle::lifecycle() != LifecycleState::Running || !platform::wait_readable(stream)? {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "shutdown in progress",
+        ));
+    }
+    let mut stream = stream;
+    stream.read(buf)
+}
@@ -44,17 +44,162 @@
 //! # Handling SIGTERM and SIGHUP
 //! Handling of `SIGTERM and SIGHUP` can be enabled with `termination` feature. If this is enabled,
 //! the handler specified by `set_handler()` will be executed for `SIGINT`, `SIGTERM` and `SIGHUP`.
+//! Daemons reloading their configuration on `SIGHUP` instead can route it to its own callback
+//! with `unix::on_reload()`.
+//!
+//! # Sharing signals with other crates
+//! On Unix, the `signal-hook-registry` feature registers through `signal-hook-registry` instead of
+//! replacing the signal handlers with `sigaction`, so handlers of tokio, signal-hook and other
+//! registry users keep running alongside this one and installing both is no error. The registry
+//! never puts the previous action back, so a signal removed with e.g.
+//! [`remove_all_handlers()`](fn.remove_all_handlers.html) is ignored afterwards, unless another
+//! registry user still handles it. The previous action always runs first, whatever the
+//! [`InstallPolicy`].
+//!
+//! # eventfd on Linux
+//! The `eventfd` feature wakes up the handler thread through an `eventfd` instead of a pipe on
+//! Linux and Android. It takes one file descriptor instead of two and, unlike a pipe, never fills
+//! up, so no signal is dropped however many arrive before the handler runs. Signals that arrived
+//! together are handled lowest signal number first. Kernels without `eventfd` fall back to the
+//! pipe.
+//!
+//! # POSIX semaphore
+//! The `posix-semaphore` feature wakes up the handler thread through a POSIX semaphore posted
+//! with `sem_post()` instead, for platforms or sandboxes where creating pipes is undesirable. It
+//! takes no file descriptors and takes precedence over `eventfd`. Signals that arrived together
+//! are handled lowest signal number first, as with `eventfd`. Apple platforms lack unnamed
+//! semaphores and keep the pipe.
+//!
+//! # signalfd on Linux
+//! The `signalfd` feature blocks the handled signals and reads them from a `signalfd` instead of
+//! installing a signal handler, so no code runs in signal context and the actions in place are
+//! left alone. Blocking only affects the thread setting the handler and the threads it spawns
+//! afterwards, a thread spawned before still gets the action in place, so set the handler early
+//! in `main`. Signals sent to one thread only, e.g. with `raise()`, are only seen if that thread
+//! is the handler thread. The feature takes precedence over `posix-semaphore`, `eventfd` and
+//! `signal-hook-registry`.
+//!
+//! # kqueue on macOS and the BSDs
+//! The `kqueue` feature watches the handled signals with `EVFILT_SIGNAL` filters on a kqueue
+//! instead of installing a signal handler, on Apple platforms, FreeBSD and OpenBSD. The filters
+//! see signals even while they are ignored, so the handled signals are ignored and no code runs in
+//! signal context; the actions in place are put back when the handler is removed. The previous
+//! handlers are never called, [`InstallPolicy::Chain`] replaces them like
+//! [`InstallPolicy::ForceReplace`]. Ignored signals stay ignored in processes spawned meanwhile.
+//! The feature takes precedence over `posix-semaphore` and `signal-hook-registry`.
+//!
+//! # sigwait thread
+//! The `sigwait` feature blocks the handled signals and takes them with `sigtimedwait()` on a
+//! dedicated thread instead of installing a signal handler, on Linux, Android, FreeBSD, DragonFly
+//! and NetBSD. No code runs in signal context and the actions in place are left alone, the
+//! signals reach the handler thread through the same channel as from a signal handler, so the
+//! feature combines with `eventfd` and `posix-semaphore`. Blocking has the same limits as with
+//! `signalfd`. A signal handled after the first ones may wait up to 100 ms for the dispatcher
+//! thread to pick it up. The feature takes precedence over `signal-hook-registry` but not over
+//! `signalfd` or `kqueue`.
+//!
+//! # Event loops
+//! On Unix, [`unix::SignalNotifier`] installs the handler without a handler thread and exposes a
+//! descriptor that is readable while a signal waits, to register with `epoll`, `poll` or
+//! `select`. The `mio` feature implements `mio::event::Source` for it, to register it with a
+//! `mio::Poll` next to sockets. With the `tokio` feature, `async_io::ctrl_c()` waits for
+//! Ctrl-C on the tokio reactor the same way, and with the `async-io` feature
+//! `async_io::AsyncNotifier` on the reactor of smol and async-std. On any platform, the `futures` feature adds
+//! `stream()`, a `futures_core::Stream` of the signals received by the registered handler, woken
+//! up by the handler thread and usable on any executor. The `crossbeam` feature adds
+//! `channel_crossbeam()`, a `crossbeam_channel::Receiver` of them, for `select!` with other
+//! channels.
+//!
+//! # Configuration
+//! [`Builder`] allows choosing the handled signals at runtime, forcibly exiting after a grace
+//! period or repeated signals, and reading these settings from `CTRLC_*` environment variables.
 //!
-
-#[macro_use]
 
 mod error;
 mod platform;
 use block_outcome::BlockOutcome;
-pub use platform::Signal;
+pub use platform::Handle;
 mod signal;
 pub use signal::*;
 mod block_outcome;
+mod builder;
+pub use builder::Builder;
+mod env;
+pub use env::EnvError;
+mod preset;
+pub use preset::{Preset, Readiness};
+mod ack;
+pub mod testing;
+pub use ack::{wait_for_acks, AckHandle};
+mod progress;
+pub use progress::*;
+mod handlers;
+pub use handlers::{handlers, HandlerInfo};
+mod shutdown;
+pub use shutdown::{
+    shutdown, shutdown_complete, Shutdown, ShutdownComplete, ShutdownController, ShutdownReason,
+    ShutdownToken,
+};
+mod weak;
+pub use weak::register_weak;
+mod consumer;
+pub use consumer::{push_consumer, ConsumerGuard};
+mod guard;
+pub use guard::CtrlcGuard;
+mod routes;
+pub use routes::{add_handler, on, remove, HandlerId};
+mod cleanup;
+pub use cleanup::{on_shutdown, ShutdownCallback};
+pub mod external;
+mod flag;
+pub mod io;
+pub use flag::{set_flag, shutdown_requested};
+mod listener;
+#[cfg(feature = "crossbeam")]
+pub use listener::channel_crossbeam;
+pub use listener::{signals, wait_for_count, Recv, SignalListener, Signals};
+#[cfg(feature = "futures")]
+pub use listener::{stream, SignalStream};
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "ffi")]
+pub use ffi::CTRLC_INTERRUPTED;
+mod lifecycle;
+pub use lifecycle::{lifecycle, on_lifecycle_change, LifecycleState};
+mod priority;
+pub use priority::ThreadPriority;
+mod grace;
+pub use grace::grace_remaining;
+mod inflight;
+pub use inflight::{inflight, inflight_count, InflightPermit};
+mod capabilities;
+pub use capabilities::{capabilities, Capabilities};
+mod hardening;
+pub use hardening::harden_panic_path;
+mod delivery;
+pub use delivery::Delivery;
+mod policy;
+pub use policy::InstallPolicy;
+mod displaced;
+pub use displaced::{Displaced, PreviousAction};
+mod exit;
+pub use exit::{exit_on, reraise, EscalationStage, ExitMechanism};
+mod orchestrator;
+pub use orchestrator::{
+    OnTimeout, Orchestrator, ShutdownReport, TaskOptions, TaskOutcome, TaskReport,
+};
+#[cfg(all(
+    unix,
+    not(target_os = "espidf"),
+    any(feature = "tokio", feature = "async-io")
+))]
+pub mod async_io;
+#[cfg(all(target_os = "macos", feature = "macos-app"))]
+pub mod macos;
+#[cfg(all(unix, not(target_os = "espidf")))]
+pub mod unix;
+#[cfg(windows)]
+pub mod windows;
 
 pub use error::Error;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -87,11 +232,15 @@ static INIT_LOCK: Mutex<()> = Mutex::new(());
 /// `fork(2)` on, but not by child processes created via `execve(2)`.
 /// Signal handlers are not inherited on Windows.
 ///
+/// On Windows, programs running in mintty (Git Bash, MSYS2, Cygwin) without a pseudo console
+/// never receive Ctrl-C as a console event, see `windows::is_mintty()`.
+///
 /// # Errors
 /// Will return an error if a system error occurred while setting the handler.
 ///
 /// # Panics
 /// Any panic in the handler will not be caught and will cause the signal handler thread to stop.
+#[track_caller]
 pub fn set_handler<F>(user_handler: F) -> Result<JoinHandle<()>, Error>
 where
     F: FnMut() + 'static + Send,
@@ -99,11 +248,177 @@ where
     init_and_set_handler(user_handler, true)
 }
 
+/// Register signal handler for Ctrl-C until the returned guard is dropped.
+///
+/// Dropping the [`CtrlcGuard`] removes the handler, restores the signal actions that were in
+/// place before and waits for the handler threads, leaving the process as it was found. Useful
+/// in libraries and test harnesses. Otherwise the same as [`set_handler()`](fn.set_handler.html).
+///
+/// # Example
+/// ```no_run
+/// {
+///     let _guard = ctrlc::set_handler_guarded(|| println!("Hello world!"))
+///         .expect("Error setting Ctrl-C handler");
+///     // ... Ctrl-C runs the handler ...
+/// }
+/// // ... Ctrl-C does whatever it did before ...
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+#[track_caller]
+pub fn set_handler_guarded<F>(user_handler: F) -> Result<CtrlcGuard, Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    Builder::new().set_handler_guarded(user_handler)
+}
+
+/// Run `f` with `handler` registered, removing it again afterwards.
+///
+/// Uses [`set_handler_guarded()`](fn.set_handler_guarded.html), so once `f` returns or panics the
+/// previous signal actions are restored and the handler threads have finished. To take over
+/// Ctrl-C from a handler that stays registered, see [`push_consumer()`](fn.push_consumer.html).
+///
+/// # Example
+/// ```no_run
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// let cancelled = Arc::new(AtomicBool::new(false));
+/// let handler = {
+///     let cancelled = cancelled.clone();
+///     move || cancelled.store(true, Ordering::SeqCst)
+/// };
+/// ctrlc::with_handler(handler, || {
+///     while !cancelled.load(Ordering::SeqCst) {
+///         // ... next step of the long operation ...
+///     }
+/// })
+/// .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return [`Error::MultipleHandlers`] if a handler is already registered, or an error if
+/// a system error occurred while setting the handler. `f` is not run then.
+#[track_caller]
+pub fn with_handler<H, F, T>(handler: H, f: F) -> Result<T, Error>
+where
+    H: FnMut() + 'static + Send,
+    F: FnOnce() -> T,
+{
+    let _guard = set_handler_guarded(handler)?;
+    Ok(f())
+}
+
+/// Register a signal handler forwarding every signal into the returned channel.
+///
+/// Receiving from the channel takes the place of the handler. The channel disconnects once the
+/// handler is removed, e.g. by [`remove_all_handlers()`](fn.remove_all_handlers.html). After the
+/// receiver was dropped, the next signal removes the handler like dropping a [`CtrlcGuard`]
+/// would, that signal is lost.
+///
+/// # Example
+/// ```no_run
+/// let signals = ctrlc::channel().expect("Error setting Ctrl-C handler");
+/// let event = signals.recv().expect("Handler removed");
+/// println!("Received {:?}", event.signal());
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+#[track_caller]
+pub fn channel() -> Result<std::sync::mpsc::Receiver<SignalEvent>, Error> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let slot = std::sync::Arc::new(Mutex::new(None::<CtrlcGuard>));
+    let forward = {
+        let slot = slot.clone();
+        move |event: &SignalEvent| {
+            if tx.send(event.clone()).is_err() {
+                // The receiver was dropped, the guard doesn't wait for its own thread.
+                let guard = slot.lock().unwrap_or_else(|e| e.into_inner()).take();
+                drop(guard);
+            }
+            false
+        }
+    };
+    let guard = Builder::new().filter(forward).set_handler_guarded(|| {})?;
+    *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(guard);
+    Ok(rx)
+}
+
+/// Register a signal handler run by [`Handle::pump()`] on the calling thread instead of a
+/// handler thread.
+///
+/// Nothing runs until the returned handle is pumped, e.g. from the main loop of a GUI app
+/// whose handler must run on the UI thread. Dropping the handle removes the handler.
+/// Otherwise the same as [`set_handler()`](fn.set_handler.html).
+///
+/// # Example
+/// ```no_run
+/// let handle = ctrlc::set_handler_no_thread(|| println!("Hello world!"))
+///     .expect("Error setting Ctrl-C handler");
+///
+/// loop {
+///     handle.pump().expect("Error handling Ctrl-C");
+///     // ... handle the UI events ...
+/// }
+/// ```
+///
+/// # Errors
+/// Will return [`Error::MultipleHandlers`] if a handler is already registered, or an error if
+/// a system error occurred while setting the handler.
+#[track_caller]
+pub fn set_handler_no_thread<F>(user_handler: F) -> Result<Handle, Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    Handle::install(
+        &platform::default_signals(),
+        true.into(),
+        Some(Box::new(user_handler)),
+    )
+}
+
+/// Register a signal handler that is told which signal triggered it.
+///
+/// Otherwise the same as [`set_handler()`](fn.set_handler.html). When the handler runs without
+/// a signal, e.g. because of [`ShutdownController::shutdown()`](struct.ShutdownController.html#method.shutdown),
+/// it receives the Ctrl-C signal (`SIGINT` on Unix, `CTRL_C_EVENT` on Windows).
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler_with_signal(|signal| println!("Received {:?}", signal))
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+#[track_caller]
+pub fn set_handler_with_signal<F>(user_handler: F) -> Result<JoinHandle<()>, Error>
+where
+    F: FnMut(Signal) + 'static + Send,
+{
+    Builder::new().set_handler_with_signal(user_handler)
+}
+
+/// Signals handled when no explicit signal set is requested.
+///
+/// [`Signal::CtrlC`] on Unix, plus [`Signal::Terminate`] and [`Signal::Hangup`] with the
+/// `termination` feature. All console control events on Windows.
+pub fn default_signals() -> Vec<Signal> {
+    platform::default_signals()
+        .into_iter()
+        .map(platform::to_signal)
+        .collect()
+}
+
 /// The same as ctrlc::set_handler but errors if a handler already exists for the signal(s).
 ///
 /// # Errors
 /// Will return an error if another handler exists or if a system error occurred while setting the
 /// handler.
+#[track_caller]
 pub fn try_set_handler<F>(user_handler: F) -> Result<JoinHandle<()>, Error>
 where
     F: FnMut() + 'static + Send,
@@ -111,15 +426,75 @@ where
     init_and_set_handler(user_handler, false)
 }
 
+#[track_caller]
 fn init_and_set_handler<F>(user_handler: F, overwrite: bool) -> Result<JoinHandle<()>, Error>
 where
     F: FnMut() + 'static + Send,
+{
+    Builder::new()
+        .overwrite(overwrite)
+        .set_handler(user_handler)
+}
+
+/// Start latching Ctrl-C signals as early as possible, before the handler is registered.
+///
+/// Signals received before [`set_handler()`](fn.set_handler.html) (or any other way of
+/// registering the handler) are replayed to the handler once it is registered. Call this at the
+/// very top of `main` when the real handler is only set up after a slow initialization.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::prearm().expect("Error installing Ctrl-C latch");
+///
+/// // ... expensive startup, Ctrl-C is not lost here ...
+///
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while installing the latch.
+pub fn prearm() -> Result<(), Error> {
+    Builder::new().prearm()
+}
+
+/// Whether a debugger is attached to the process.
+///
+/// Detected through `TracerPid` on Linux and Android, `P_TRACED` on macOS and iOS and
+/// `IsDebuggerPresent` on Windows. Always `false` on other platforms.
+pub fn is_debugger_attached() -> bool {
+    platform::is_debugger_attached()
+}
+
+/// Number of signals dropped since the process started because the handler fell too far behind.
+///
+/// On Unix the signal handler can't block, a signal arriving while the pipe is full, e.g. during
+/// a signal storm with a slow handler, is dropped. The same goes for console control events once
+/// Windows' semaphore reaches its maximum count. Compare the value before and after a batch of
+/// work to find out whether signals were lost meanwhile.
+///
+/// # Example
+/// ```no_run
+/// let missed = ctrlc::missed_signals();
+/// // ... work ...
+/// if ctrlc::missed_signals() > missed {
+///     eprintln!("some signals were dropped");
+/// }
+/// ```
+pub fn missed_signals() -> u64 {
+    platform::missed_signals()
+}
+
+/// Runs `set_handler` unless a handler is already registered.
+pub(crate) fn init_handler<T, F>(set_handler: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
 {
     if !INIT.load(Ordering::Acquire) {
         let _guard = INIT_LOCK.lock().unwrap();
 
-        if !INIT.load(Ordering::Relaxed) {
-            let result = set_handler_inner(user_handler, overwrite)?;
+        // The flag handler of `set_flag()` is never removed.
+        if !INIT.load(Ordering::Relaxed) && !flag::is_installed() {
+            let result = set_handler()?;
             INIT.store(true, Ordering::Release);
             return Ok(result);
         }
@@ -128,29 +503,6 @@ where
     Err(Error::MultipleHandlers)
 }
 
-fn set_handler_inner<F>(mut user_handler: F, overwrite: bool) -> Result<JoinHandle<()>, Error>
-where
-    F: FnMut() + 'static + Send,
-{
-    unsafe {
-        platform::init_os_handler(overwrite)?;
-    }
-
-    thread::Builder::new()
-        .name("ctrl-c".into())
-        .spawn(move || loop {
-            unsafe {
-                match platform::block_ctrl_c() {
-                    Ok(BlockOutcome::Awaited) => {},
-                    Ok(BlockOutcome::HandlerRemoved) => break,
-                    Err(err) => panic!("Critical system error while waiting for Ctrl-C: {err:?}")
-                };
-            }
-            user_handler();
-        })
-        .map_err(Error::System)
-}
-
 
 /// Same as [`ctrlc::set_handler`], but uses [`std::ops::FnOnce`] as a handler that only handles one interrupt.
 /// 
@@ -175,6 +527,7 @@ where
 /// 
 /// assert!(ctrlc::remove_all_handlers().is_err()); // This handler should be already removed after firing once.
 /// ```
+#[track_caller]
 pub fn set_handler_once<F, T>(user_handler: F) -> Result<JoinHandle<Option<F::Output>>, Error>
 where
     F: FnOnce() -> T + 'static + Send,
@@ -189,6 +542,7 @@ where
 /// # Errors
 /// Will return an error if another handler exists or if a system error occurred while setting the
 /// handler.
+#[track_caller]
 pub fn try_set_handler_once<F, T>(user_handler: F) -> Result<JoinHandle<Option<F::Output>>, Error>
 where
     F: FnOnce() -> T + 'static + Send,
@@ -198,44 +552,60 @@ where
 }
 
 
+#[track_caller]
 fn init_and_set_handler_once<F, T>(user_handler: F, overwrite: bool) -> Result<JoinHandle<Option<F::Output>>, Error>
 where
     F: FnOnce() -> T + 'static + Send,
     T: 'static + Send
 {
-    if !INIT.load(Ordering::Acquire) {
-        let _guard = INIT_LOCK.lock().unwrap();
-
-        if !INIT.load(Ordering::Relaxed) {
-            let handle = set_handler_inner_once(user_handler, overwrite)?;
-            INIT.store(true, Ordering::Release);
-            return Ok(handle);
-        }
-    }
-
-    Err(Error::MultipleHandlers)
+    let location = handlers::caller_location();
+    init_handler(|| set_handler_inner_once(user_handler, overwrite, location))
 }
 
 
-fn set_handler_inner_once<F, T>(user_handler: F, overwrite: bool) -> Result<JoinHandle<Option<F::Output>>, Error>
+fn set_handler_inner_once<F, T>(
+    user_handler: F,
+    overwrite: bool,
+    location: Option<&'static std::panic::Location<'static>>,
+) -> Result<JoinHandle<Option<F::Output>>, Error>
 where
     F: FnOnce() -> T + 'static + Send,
     T: 'static + Send,
 {
-    unsafe {
-        platform::init_os_handler(overwrite)?;
-    }
+    let signals = platform::default_signals();
+    let displaced = unsafe { platform::init_os_handler(&signals, overwrite.into())? };
+    let generation = platform::generation();
 
     let thread = thread::Builder::new()
         .name("ctrl-c".into())
         .spawn(move || {
-            let outcome = unsafe {
-                platform::block_ctrl_c().expect("Critical system error while waiting for Ctrl-C")
+            let signal = loop {
+                let outcome = unsafe {
+                    platform::block_ctrl_c_for(generation)
+                        .expect("Critical system error while waiting for Ctrl-C")
+                };
+                let signal = match outcome {
+                    BlockOutcome::HandlerRemoved => return None,
+                    BlockOutcome::Signal(signal) if routes::dispatch(signal) => continue,
+                    BlockOutcome::Signal(signal) => Some(signal),
+                    _ => None,
+                };
+                if !consumer::dispatch() {
+                    break signal;
+                }
             };
-            if outcome == BlockOutcome::HandlerRemoved {
-                return None;
-            }
+            hardening::check_signal();
+            shutdown::record_signal();
+            #[cfg(feature = "ffi")]
+            ffi::record_signal();
+            listener::record_signal(&SignalEvent::new(1, signal));
+            lifecycle::advance(LifecycleState::Draining);
             let result = user_handler();
+            routes::run_added();
+            weak::dispatch();
+            inflight::drain();
+            cleanup::run(signal);
+            lifecycle::advance(LifecycleState::Stopped);
 
             match remove_all_handlers() {
                 Ok(()) |
@@ -246,17 +616,93 @@ where
         })
         .map_err(Error::System)?;
 
+    handlers::register(signals, displaced, true, location);
     Ok(thread)
 }
 
+/// Start running the registered handler for `kind` as well, without reinstalling it.
+///
+/// Only the new signals are installed, the handler thread and pending signals are untouched.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::{Signal, SignalType};
+///
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+///
+/// // Only handle SIGHUP while a config file is loaded.
+/// # #[cfg(unix)]
+/// ctrlc::add_signal(SignalType::Other(Signal::SIGHUP)).expect("Error adding SIGHUP");
+/// # #[cfg(unix)]
+/// ctrlc::remove_signal(SignalType::Other(Signal::SIGHUP)).expect("Error removing SIGHUP");
+/// ```
+///
+/// # Errors
+/// Will return [`Error::HandlerRemoved`] if no handler is registered, or an error if a system
+/// error occurred. Signals installed before the error stay installed.
+pub fn add_signal(kind: SignalType) -> Result<(), Error> {
+    let signals = platform::signals_of(&kind);
+    let _guard = INIT_LOCK.lock().unwrap();
+    if !INIT.load(Ordering::Acquire) {
+        return Err(Error::HandlerRemoved);
+    }
+    for &signal in &signals {
+        unsafe { platform::add_signal(signal)? };
+    }
+    handlers::update_signals(|handled| {
+        for &signal in &signals {
+            if !handled.contains(&signal) {
+                handled.push(signal);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Stop running the registered handler for `kind`, restoring the actions its signals had before.
+///
+/// # Errors
+/// Will return [`Error::HandlerRemoved`] if no handler is registered, or an error if a system
+/// error occurred. Signals removed before the error stay removed.
+pub fn remove_signal(kind: SignalType) -> Result<(), Error> {
+    let signals = platform::signals_of(&kind);
+    let _guard = INIT_LOCK.lock().unwrap();
+    if !INIT.load(Ordering::Acquire) {
+        return Err(Error::HandlerRemoved);
+    }
+    for &signal in &signals {
+        unsafe { platform::remove_signal(signal)? };
+    }
+    handlers::update_signals(|handled| handled.retain(|signal| !signals.contains(signal)));
+    Ok(())
+}
+
 /// Removes all previously added handlers
+///
+/// On Unix, the signal actions in place before the handler was registered are restored, e.g. a
+/// `SIGTERM` handler installed by a logging framework.
+///
+/// A handler may be registered again right away. The signal handling thread of the removed
+/// handler finishes the signals it already received, but never takes those of the new one.
+/// The next handler starts from a clean slate: the [`lifecycle()`] is back to
+/// [`Running`](enum.LifecycleState.html#variant.Running), so [`ShutdownToken`]s are no longer
+/// cancelled, and a running grace period or watchdog no longer exits the process.
 pub fn remove_all_handlers() -> Result<(), Error> {
     if !INIT.load(Ordering::Acquire) {
         return Err(Error::HandlerRemoved);
     }
+    // Waits for a delivery in progress, none may write to the pipe once it is closed.
+    external::clear();
     unsafe {
         platform::deinit_os_handler()?;
         INIT.store(false, Ordering::Relaxed);
     }
+    handlers::clear();
+    shutdown::clear();
+    lifecycle::reset();
+    grace::clear();
+    routes::clear();
+    listener::clear();
+    cleanup::clear();
     Ok(())
 }
@@ -0,0 +1,103 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::platform;
+use crate::shutdown;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Listener = Arc<dyn Fn(LifecycleState) + Send + Sync>;
+
+static STATE: AtomicU8 = AtomicU8::new(LifecycleState::Running as u8);
+static LISTENERS: Mutex<Vec<Listener>> = Mutex::new(Vec::new());
+
+fn lock() -> MutexGuard<'static, Vec<Listener>> {
+    LISTENERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Shutdown progress of the process, see [`lifecycle()`].
+///
+/// The state only moves forward, until [`remove_all_handlers()`](fn.remove_all_handlers.html)
+/// puts it back to `Running` for the next handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum LifecycleState {
+    /// No signal was received yet.
+    Running = 0,
+    /// A signal was received and the shutdown is in progress.
+    Draining = 1,
+    /// The handler and the orchestrator tasks have finished for the first signal.
+    Stopped = 2,
+}
+
+impl LifecycleState {
+    fn from_u8(state: u8) -> LifecycleState {
+        match state {
+            0 => LifecycleState::Running,
+            1 => LifecycleState::Draining,
+            _ => LifecycleState::Stopped,
+        }
+    }
+}
+
+/// Current lifecycle state, so health endpoints can report draining distinctly from running.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::LifecycleState;
+///
+/// ctrlc::set_handler(|| println!("Shutting down...")).expect("Error setting Ctrl-C handler");
+///
+/// // In the health endpoint:
+/// let status = match ctrlc::lifecycle() {
+///     LifecycleState::Running => 200,
+///     _ => 503,
+/// };
+/// ```
+pub fn lifecycle() -> LifecycleState {
+    LifecycleState::from_u8(STATE.load(Ordering::SeqCst))
+}
+
+/// Call `listener` on every lifecycle change.
+///
+/// Listeners run on the thread making the change: the handler thread, or the thread calling
+/// [`Handle::pump()`](struct.Handle.html#method.pump), while shutting down, and the thread calling
+/// [`remove_all_handlers()`](fn.remove_all_handlers.html) when moving back to `Running`.
+pub fn on_lifecycle_change<F>(listener: F)
+where
+    F: Fn(LifecycleState) + Send + Sync + 'static,
+{
+    lock().push(Arc::new(listener));
+}
+
+/// Move to `state` unless the lifecycle is already there or further.
+pub(crate) fn advance(state: LifecycleState) {
+    let previous = STATE.fetch_max(state as u8, Ordering::SeqCst);
+    if previous < state as u8 {
+        notify(state);
+    }
+}
+
+/// Move back to `Running` once the handler was removed, also for
+/// [`io::read_interruptible()`](io/fn.read_interruptible.html).
+pub(crate) fn reset() {
+    let previous = STATE.swap(LifecycleState::Running as u8, Ordering::SeqCst);
+    platform::resume_readers();
+    if previous != LifecycleState::Running as u8 {
+        notify(LifecycleState::Running);
+    }
+}
+
+fn notify(state: LifecycleState) {
+    let listeners = lock().clone();
+    for listener in listeners {
+        listener(state);
+    }
+    shutdown::notify_progress();
+}
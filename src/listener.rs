@@ -0,0 +1,285 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::SignalEvent;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::{mpsc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+struct State {
+    /// Number of signals received so far.
+    received: u64,
+    /// The last signal received.
+    last: Option<SignalEvent>,
+    wakers: Vec<Waker>,
+    /// Senders of the [`signals()`] iterators, dropped when the handler is removed.
+    iterators: Vec<mpsc::Sender<SignalEvent>>,
+    /// Senders of the [`channel_crossbeam()`] receivers.
+    #[cfg(feature = "crossbeam")]
+    senders: Vec<crossbeam_channel::Sender<SignalEvent>>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    received: 0,
+    last: None,
+    wakers: Vec::new(),
+    iterators: Vec::new(),
+    #[cfg(feature = "crossbeam")]
+    senders: Vec::new(),
+});
+static RECEIVED: Condvar = Condvar::new();
+
+fn lock() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Awaits signals received by the registered handler, usable from any async runtime.
+///
+/// Each listener keeps its own position: every signal received after the listener was created
+/// resolves exactly one [`recv()`](#method.recv), even when several arrive before it is awaited
+/// again. The handler must be registered for signals to be received.
+///
+/// # Cancel safety
+/// [`recv()`](#method.recv) is cancel safe. The position only moves when the future resolves,
+/// so dropping it, e.g. in a `tokio::select!` branch that lost, doesn't lose a signal: the next
+/// `recv()` resolves for it.
+///
+/// # Example
+/// ```no_run
+/// # async fn f() {
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// let mut listener = ctrlc::SignalListener::new();
+/// loop {
+///     listener.recv().await;
+///     println!("Received Ctrl-C");
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SignalListener {
+    seen: u64,
+}
+
+impl SignalListener {
+    /// Create a listener for the signals received from now on.
+    pub fn new() -> SignalListener {
+        SignalListener {
+            seen: lock().received,
+        }
+    }
+
+    /// Wait for the next signal.
+    pub fn recv(&mut self) -> Recv<'_> {
+        Recv { listener: self }
+    }
+}
+
+impl Default for SignalListener {
+    fn default() -> SignalListener {
+        SignalListener::new()
+    }
+}
+
+/// Future returned by [`SignalListener::recv()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Recv<'a> {
+    listener: &'a mut SignalListener,
+}
+
+impl Future for Recv<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = lock();
+        if state.received > self.listener.seen {
+            self.listener.seen += 1;
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Block until the registered handler received `n` signals, counting from the call.
+///
+/// Spares counting the signals around a blocking wait, e.g. for "press Ctrl-C twice to really
+/// quit". Returns immediately for 0. The handler must be registered for signals to be received,
+/// otherwise this blocks forever.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Press Ctrl-C again to quit"))
+///     .expect("Error setting Ctrl-C handler");
+///
+/// ctrlc::wait_for_count(2);
+/// println!("Quitting");
+/// ```
+pub fn wait_for_count(n: u32) {
+    let mut state = lock();
+    let target = state.received + u64::from(n);
+    while state.received < target {
+        state = RECEIVED.wait(state).unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+/// An iterator over the signals received by the registered handler from now on.
+///
+/// Blocks for the next signal and yields one item per signal, none is missed when the loop
+/// body takes a while. It ends once the handler is removed, right away if none is registered.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// for event in ctrlc::signals() {
+///     println!("Received {:?}", event.signal());
+///     if event.count() == 2 {
+///         break;
+///     }
+/// }
+/// ```
+pub fn signals() -> Signals {
+    let (tx, rx) = mpsc::channel();
+    let mut state = lock();
+    // Checked under the lock, `remove_all_handlers()` clears the senders after resetting it.
+    if crate::INIT.load(Ordering::Acquire) {
+        state.iterators.push(tx);
+    }
+    Signals { rx }
+}
+
+/// Iterator returned by [`signals()`].
+#[derive(Debug)]
+pub struct Signals {
+    rx: mpsc::Receiver<SignalEvent>,
+}
+
+impl Iterator for Signals {
+    type Item = SignalEvent;
+
+    fn next(&mut self) -> Option<SignalEvent> {
+        self.rx.recv().ok()
+    }
+}
+
+/// A stream of the signals received by the registered handler, usable from any async runtime.
+///
+/// Like a [`SignalListener`], the stream starts with the signals received from now on and is
+/// woken up by the handler thread, so it works on any executor without a reactor. It never
+/// ends. When it falls behind, one item stands for all the signals it missed, the last of them,
+/// see [`SignalEvent::coalesced()`]. The handler must be registered for signals to be received.
+///
+/// # Example
+/// ```no_run
+/// # use futures_core::Stream;
+/// # use std::pin::Pin;
+/// # async fn f() {
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// let mut signals = ctrlc::stream();
+/// while let Some(event) = std::future::poll_fn(|cx| Pin::new(&mut signals).poll_next(cx)).await {
+///     println!("Received {:?}", event.signal());
+/// }
+/// # }
+/// ```
+#[cfg(feature = "futures")]
+pub fn stream() -> SignalStream {
+    SignalStream {
+        seen: lock().received,
+    }
+}
+
+/// Stream returned by [`stream()`].
+#[cfg(feature = "futures")]
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct SignalStream {
+    seen: u64,
+}
+
+#[cfg(feature = "futures")]
+impl futures_core::Stream for SignalStream {
+    type Item = SignalEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SignalEvent>> {
+        let mut state = lock();
+        if state.received > self.seen {
+            let missed = state.received - self.seen;
+            self.seen = state.received;
+            // Set along with `received`.
+            let last = state.last.clone().expect("no signal received");
+            let coalesced = u32::try_from(missed).unwrap_or(u32::MAX);
+            return Poll::Ready(Some(last.with_coalesced(coalesced)));
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// A channel of the signals received by the registered handler from now on, with the
+/// `crossbeam` feature.
+///
+/// Lets a signal take part in `crossbeam_channel::select!` together with other channels,
+/// without forwarding it from the handler. Every receiver gets every signal, it is unbounded
+/// and never disconnected. The handler must be registered for signals to be received.
+///
+/// # Example
+/// ```no_run
+/// use crossbeam_channel::select;
+///
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+/// let signals = ctrlc::channel_crossbeam();
+/// let (jobs_tx, jobs) = crossbeam_channel::unbounded::<String>();
+/// # drop(jobs_tx);
+///
+/// loop {
+///     select! {
+///         recv(jobs) -> job => println!("Working on {:?}", job),
+///         recv(signals) -> _ => break,
+///     }
+/// }
+/// ```
+#[cfg(feature = "crossbeam")]
+pub fn channel_crossbeam() -> crossbeam_channel::Receiver<SignalEvent> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    lock().senders.push(tx);
+    rx
+}
+
+/// Wake the listeners for a received signal.
+pub(crate) fn record_signal(event: &SignalEvent) {
+    let wakers = {
+        let mut state = lock();
+        state.received += 1;
+        state.last = Some(event.clone());
+        // Dropped iterators are forgotten.
+        state.iterators.retain(|tx| tx.send(event.clone()).is_ok());
+        // Dropped receivers are forgotten.
+        #[cfg(feature = "crossbeam")]
+        state.senders.retain(|tx| tx.send(event.clone()).is_ok());
+        RECEIVED.notify_all();
+        std::mem::take(&mut state.wakers)
+    };
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// End the [`signals()`] iterators, the handler was removed.
+pub(crate) fn clear() {
+    lock().iterators.clear();
+}
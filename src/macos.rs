@@ -0,0 +1,47 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! macOS application integration.
+//!
+//! Bundled `.app` builds are asked to quit through `NSApplication` instead of `SIGTERM`. The
+//! functions here route those requests into the registered handler, so both builds share one
+//! shutdown path. The crate does not depend on an Objective-C bridge: call them from the
+//! application delegate and the notification observer of your bridge (e.g. `objc2`).
+
+use crate::ShutdownController;
+
+/// Reply to `applicationShouldTerminate:`, with the values of `NSApplicationTerminateReply`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(usize)]
+pub enum TerminateReply {
+    /// `NSTerminateCancel`
+    Cancel = 0,
+    /// `NSTerminateNow`
+    Now = 1,
+    /// `NSTerminateLater`
+    Later = 2,
+}
+
+/// Call from `-[NSApplicationDelegate applicationShouldTerminate:]` and return the result.
+///
+/// If a handler is registered, it runs as for a termination signal and
+/// [`TerminateReply::Later`] is returned: reply with `replyToApplicationShouldTerminate:` once
+/// the shutdown is done. Without a handler the application can terminate right away.
+pub fn application_should_terminate() -> TerminateReply {
+    match ShutdownController::new().shutdown("applicationShouldTerminate") {
+        Ok(()) => TerminateReply::Later,
+        Err(_) => TerminateReply::Now,
+    }
+}
+
+/// Call when `NSWorkspaceWillPowerOffNotification` is posted, runs the registered handler as
+/// for a termination signal.
+pub fn workspace_will_power_off() {
+    let _ = ShutdownController::new().shutdown("NSWorkspaceWillPowerOffNotification");
+}
@@ -0,0 +1,415 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::error::Error;
+use crate::exit;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+type TaskFn = Box<dyn FnOnce() + Send>;
+
+struct Task {
+    name: String,
+    after: Vec<String>,
+    timeout: Option<(Duration, OnTimeout)>,
+    run: TaskFn,
+}
+
+/// What to do when a shutdown task exceeds its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnTimeout {
+    /// Leave the task running in the background and continue with the tasks depending on it.
+    Skip,
+    /// Abort the process.
+    Abort,
+}
+
+/// Allows configuring a task after registering it with [`Orchestrator::add_task`].
+#[derive(Debug)]
+pub struct TaskOptions<'a> {
+    task: &'a mut Task,
+}
+
+impl TaskOptions<'_> {
+    /// Give the task at most `timeout` to finish, then handle it according to `on_timeout`.
+    pub fn timeout(self, timeout: Duration, on_timeout: OnTimeout) -> Self {
+        self.task.timeout = Some((timeout, on_timeout));
+        self
+    }
+}
+
+impl fmt::Debug for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Task")
+            .field("name", &self.name)
+            .field("after", &self.after)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// How a shutdown task ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// The task returned.
+    Completed,
+    /// The task panicked.
+    Panicked,
+    /// The task exceeded its timeout and was skipped.
+    TimedOut,
+}
+
+/// Result of a single task in a [`ShutdownReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskReport {
+    name: String,
+    elapsed: Duration,
+    outcome: TaskOutcome,
+}
+
+impl TaskReport {
+    /// Name of the task.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Time from starting the task until it finished or timed out.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// How the task ended.
+    pub fn outcome(&self) -> TaskOutcome {
+        self.outcome
+    }
+}
+
+/// Report of an [`Orchestrator::run`], listing tasks in the order they finished.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    tasks: Vec<TaskReport>,
+    elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// Reports of all tasks, in the order they finished.
+    pub fn tasks(&self) -> &[TaskReport] {
+        &self.tasks
+    }
+
+    /// Report of the task named `name`.
+    pub fn task(&self, name: &str) -> Option<&TaskReport> {
+        self.tasks.iter().find(|task| task.name == name)
+    }
+
+    /// Tasks that exceeded their timeout.
+    pub fn timed_out(&self) -> impl Iterator<Item = &TaskReport> {
+        self.tasks
+            .iter()
+            .filter(|task| task.outcome == TaskOutcome::TimedOut)
+    }
+
+    /// Total time the shutdown took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// Runs shutdown tasks in dependency order.
+///
+/// Each task declares which tasks have to finish before it starts. Tasks whose dependencies
+/// have finished run in parallel, each on its own thread. Dependencies may be registered in any
+/// order, but a registration that would create a cycle is rejected. Dependencies on tasks that
+/// were never registered are considered finished.
+///
+/// A panicking task counts as finished, so the rest of the shutdown still runs. Tasks can be
+/// given a [timeout](TaskOptions::timeout), so a single slow task cannot stall the shutdown.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use ctrlc::OnTimeout;
+///
+/// let mut shutdown = ctrlc::Orchestrator::new();
+/// shutdown.add_task("close-listeners", &[], || println!("Closing listeners"))?;
+/// shutdown.add_task("drain-connections", &["close-listeners"], || println!("Draining"))?;
+/// shutdown.add_task("flush-metrics", &["close-listeners"], || println!("Flushing metrics"))?;
+/// shutdown
+///     .add_task("close-db", &["drain-connections"], || println!("Closing database"))?
+///     .timeout(Duration::from_secs(5), OnTimeout::Skip);
+///
+/// ctrlc::Builder::new()
+///     .orchestrator(shutdown)
+///     .set_handler(|| println!("Shutting down..."))?;
+/// # Ok::<(), ctrlc::Error>(())
+/// ```
+#[derive(Default)]
+pub struct Orchestrator {
+    tasks: Vec<Task>,
+}
+
+impl fmt::Debug for Orchestrator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(&self.tasks).finish()
+    }
+}
+
+impl Orchestrator {
+    /// Create an orchestrator without tasks.
+    pub fn new() -> Orchestrator {
+        Orchestrator::default()
+    }
+
+    /// Register a task named `name` that runs after all tasks named in `after` have finished.
+    ///
+    /// # Errors
+    /// Will return [`Error::DuplicateTask`] if a task with the same name exists, or
+    /// [`Error::DependencyCycle`] if the task would create a dependency cycle.
+    pub fn add_task<F>(
+        &mut self,
+        name: &str,
+        after: &[&str],
+        task: F,
+    ) -> Result<TaskOptions<'_>, Error>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if self.index_of(name).is_some() {
+            return Err(Error::DuplicateTask(name.to_owned()));
+        }
+
+        self.tasks.push(Task {
+            name: name.to_owned(),
+            after: after.iter().map(|&dep| dep.to_owned()).collect(),
+            timeout: None,
+            run: Box::new(task),
+        });
+
+        if let Some(cycle) = self.find_cycle(self.tasks.len() - 1) {
+            self.tasks.pop();
+            return Err(Error::DependencyCycle(cycle));
+        }
+        Ok(TaskOptions {
+            task: self.tasks.last_mut().unwrap(),
+        })
+    }
+
+    /// Register an async task named `name`, executed on the runtime of `handle`, that runs after
+    /// all tasks named in `after` have finished.
+    ///
+    /// The future is spawned on the runtime when its dependencies have finished, so the runtime
+    /// has to keep running during the shutdown. A task that panics or is cancelled by the
+    /// runtime shutting down counts as finished.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn close_pool() {}
+    /// let runtime = tokio::runtime::Runtime::new().unwrap();
+    ///
+    /// let mut shutdown = ctrlc::Orchestrator::new();
+    /// shutdown.add_async_task("close-pool", &[], runtime.handle(), close_pool())?;
+    /// # Ok::<(), ctrlc::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// See [`add_task`](#method.add_task).
+    #[cfg(feature = "tokio")]
+    pub fn add_async_task<F>(
+        &mut self,
+        name: &str,
+        after: &[&str],
+        handle: &tokio::runtime::Handle,
+        task: F,
+    ) -> Result<TaskOptions<'_>, Error>
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let handle = handle.clone();
+        self.add_task(name, after, move || {
+            let join = handle.spawn(task);
+            if let Err(e) = handle.block_on(join) {
+                if e.is_panic() {
+                    panic::resume_unwind(e.into_panic());
+                }
+            }
+        })
+    }
+
+    /// Whether no tasks are registered.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Run all tasks, blocking until they have finished or timed out.
+    pub fn run(self) -> ShutdownReport {
+        let started = Instant::now();
+        let dependencies: Vec<Vec<usize>> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                task.after
+                    .iter()
+                    .filter_map(|dep| self.index_of(dep))
+                    .collect()
+            })
+            .collect();
+
+        let mut waiting_for: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+        let mut dependents = vec![Vec::new(); self.tasks.len()];
+        for (task, deps) in dependencies.iter().enumerate() {
+            for &dep in deps {
+                dependents[dep].push(task);
+            }
+        }
+
+        let mut names = Vec::with_capacity(self.tasks.len());
+        let mut timeouts = Vec::with_capacity(self.tasks.len());
+        let mut tasks = Vec::with_capacity(self.tasks.len());
+        for task in self.tasks {
+            names.push(task.name);
+            timeouts.push(task.timeout);
+            tasks.push(Some(task.run));
+        }
+
+        // Tasks run on detached threads, a task that times out is left running.
+        let (done_tx, done_rx) = mpsc::channel();
+        let mut running: Vec<(usize, Instant)> = Vec::new();
+        let mut start = |index: usize, running: &mut Vec<(usize, Instant)>| {
+            let task = tasks[index].take().expect("task started twice");
+            let task_done_tx = done_tx.clone();
+            running.push((index, Instant::now()));
+            let spawned = thread::Builder::new()
+                .name(format!("ctrl-c-task-{}", names[index]))
+                .spawn(move || {
+                    let outcome = match panic::catch_unwind(AssertUnwindSafe(task)) {
+                        Ok(()) => TaskOutcome::Completed,
+                        Err(_) => TaskOutcome::Panicked,
+                    };
+                    let _ = task_done_tx.send((index, outcome));
+                });
+            if spawned.is_err() {
+                eprintln!("[ctrlc] Could not start shutdown task {:?}", names[index]);
+                let _ = done_tx.send((index, TaskOutcome::Panicked));
+            }
+        };
+
+        let ready: Vec<usize> = (0..waiting_for.len())
+            .filter(|&index| waiting_for[index] == 0)
+            .collect();
+        for index in ready {
+            start(index, &mut running);
+        }
+
+        let mut reports = Vec::with_capacity(names.len());
+        while !running.is_empty() {
+            let next_deadline = running
+                .iter()
+                .filter_map(|&(index, started)| {
+                    timeouts[index].map(|(timeout, _)| (started + timeout, index))
+                })
+                .min();
+
+            let finished = match next_deadline {
+                Some((deadline, index)) => {
+                    let wait = deadline.saturating_duration_since(Instant::now());
+                    match done_rx.recv_timeout(wait) {
+                        Ok(done) => done,
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            if let Some((_, OnTimeout::Abort)) = timeouts[index] {
+                                eprintln!(
+                                    "[ctrlc] Shutdown task {:?} timed out, aborting",
+                                    names[index]
+                                );
+                                exit::abort();
+                            }
+                            (index, TaskOutcome::TimedOut)
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => {
+                            unreachable!("sender is kept alive by the orchestrator")
+                        }
+                    }
+                }
+                None => done_rx
+                    .recv()
+                    .expect("sender is kept alive by the orchestrator"),
+            };
+
+            // A task finishing after it was skipped has already been reported.
+            let (index, outcome) = finished;
+            let position = match running.iter().position(|&(i, _)| i == index) {
+                Some(position) => position,
+                None => continue,
+            };
+            let (_, task_started) = running.swap_remove(position);
+            reports.push(TaskReport {
+                name: names[index].clone(),
+                elapsed: task_started.elapsed(),
+                outcome,
+            });
+
+            for &dependent in &dependents[index] {
+                waiting_for[dependent] -= 1;
+                if waiting_for[dependent] == 0 {
+                    start(dependent, &mut running);
+                }
+            }
+        }
+
+        ShutdownReport {
+            tasks: reports,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.tasks.iter().position(|task| task.name == name)
+    }
+
+    /// Find a dependency cycle going through the task at `start`, returned as task names.
+    fn find_cycle(&self, start: usize) -> Option<Vec<String>> {
+        let mut path = vec![start];
+        let mut visited = vec![false; self.tasks.len()];
+        if self.reaches(start, start, &mut path, &mut visited) {
+            Some(path.iter().map(|&i| self.tasks[i].name.clone()).collect())
+        } else {
+            None
+        }
+    }
+
+    fn reaches(
+        &self,
+        from: usize,
+        target: usize,
+        path: &mut Vec<usize>,
+        visited: &mut [bool],
+    ) -> bool {
+        for dep in &self.tasks[from].after {
+            let dep = match self.index_of(dep) {
+                Some(dep) => dep,
+                None => continue,
+            };
+            if dep == target {
+                return true;
+            }
+            if visited[dep] {
+                continue;
+            }
+            visited[dep] = true;
+            path.push(dep);
+            if self.reaches(dep, target, path, visited) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+}
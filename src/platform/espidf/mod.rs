@@ -0,0 +1,408 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! ESP-IDF has no meaningful POSIX signals. The handler is instead woken up by shutdown
+//! requests from the firmware, see [`ShutdownController`](crate::ShutdownController).
+//!
+//! The requests are passed with a `Mutex` and a `Condvar` from std, which ESP-IDF implements
+//! with the pthread layer on top of FreeRTOS semaphores. There is no FreeRTOS event group or
+//! task notification here: calling those needs bindings to ESP-IDF (`esp-idf-sys`), and a
+//! request has to be counted and waited for with a timeout, which the `Condvar` already does.
+
+use std::io;
+use std::io::ErrorKind;
+use std::os::raw::c_int;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::block_outcome::{BlockOutcome, MAX_BATCH};
+use crate::{Displaced, InstallPolicy, PreviousAction, SignalType, ThreadPriority};
+
+/// Platform specific error type
+pub type Error = io::Error;
+
+/// Platform specific signal type
+pub type Signal = c_int;
+
+const SIGHUP: Signal = 1;
+const SIGINT: Signal = 2;
+const SIGTERM: Signal = 15;
+
+struct State {
+    installed: bool,
+    pending: u32,
+    generation: u64,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    installed: false,
+    pending: 0,
+    generation: 0,
+});
+static WAKEUP: Condvar = Condvar::new();
+
+fn lock() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Signals handled when no explicit signal set is requested: `SIGINT`.
+pub fn default_signals() -> Vec<Signal> {
+    vec![SIGINT]
+}
+
+/// Platform signals corresponding to a [`SignalType`](crate::SignalType).
+pub fn signals_of(kind: &SignalType) -> Vec<Signal> {
+    match *kind {
+        SignalType::Ctrlc => vec![SIGINT],
+        SignalType::Termination => vec![SIGTERM, SIGHUP],
+        SignalType::Other(signal) => from_signal(signal).into_iter().collect(),
+    }
+}
+
+/// The [`crate::Signal`] for a platform signal.
+pub fn to_signal(signal: Signal) -> crate::Signal {
+    match signal {
+        SIGINT => crate::Signal::CtrlC,
+        SIGTERM => crate::Signal::Terminate,
+        SIGHUP => crate::Signal::Hangup,
+        signal => crate::Signal::Other(signal),
+    }
+}
+
+/// The platform signal for a [`crate::Signal`], `None` if there is none.
+pub fn from_signal(signal: crate::Signal) -> Option<Signal> {
+    match signal {
+        crate::Signal::CtrlC => Some(SIGINT),
+        crate::Signal::Terminate => Some(SIGTERM),
+        crate::Signal::Hangup => Some(SIGHUP),
+        crate::Signal::Other(signal) => Some(signal),
+        _ => None,
+    }
+}
+
+/// Parse a signal name such as `INT`, `SIGTERM` or `hup`, or its number.
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    let name = name.trim().to_ascii_uppercase();
+    match name.strip_prefix("SIG").unwrap_or(&name) {
+        "HUP" => Some(SIGHUP),
+        "INT" => Some(SIGINT),
+        "TERM" => Some(SIGTERM),
+        other => other.parse().ok(),
+    }
+}
+
+/// Every signal a shutdown can be requested for, there are no POSIX signals on ESP-IDF.
+pub fn supported_signals() -> Vec<Signal> {
+    vec![SIGHUP, SIGINT, SIGTERM]
+}
+
+/// Whether `signal` asks the process to terminate, true for all supported signals.
+pub fn is_termination(signal: Signal) -> bool {
+    supported_signals().contains(&signal)
+}
+
+/// Exit status of a shell for a process killed by `signal`, `128 + signal`.
+pub fn exit_code(signal: Signal) -> u8 {
+    128u8.saturating_add(signal as u8)
+}
+
+/// There is no resize signal on this platform.
+pub fn window_size(_signal: Signal) -> Option<(u16, u16)> {
+    None
+}
+
+/// The sender of a signal is not known on this platform.
+pub fn sender(_signal: Signal) -> Option<(u32, u32)> {
+    None
+}
+
+/// Process names can't be looked up on this platform.
+pub fn process_name(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Name of `signal`, accepted by [`parse_signal()`](fn.parse_signal.html).
+pub fn signal_name(signal: Signal) -> String {
+    match signal {
+        SIGHUP => "SIGHUP".to_string(),
+        SIGINT => "SIGINT".to_string(),
+        SIGTERM => "SIGTERM".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Terminate the process. ESP-IDF has no `atexit` handlers worth skipping, so this is
+/// `process::exit`.
+pub fn exit_immediately(code: i32) -> ! {
+    std::process::exit(code)
+}
+
+/// There are no default actions to put back on ESP-IDF, exits like
+/// [`exit_immediately()`](fn.exit_immediately.html) with code 1.
+pub fn exit_with_signal(_signal: Signal) -> ! {
+    exit_immediately(1)
+}
+
+/// Thread priorities are configured through `esp_idf_hal`, not supported here.
+pub fn set_thread_priority(_priority: ThreadPriority) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread priorities are not supported on ESP-IDF",
+    ))
+}
+
+/// Thread affinity is configured through `esp_idf_hal`, not supported here.
+pub fn set_thread_affinity(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread affinity is not supported on ESP-IDF",
+    ))
+}
+
+/// Signals sent when the controlling terminal goes away, there are none on ESP-IDF.
+pub fn hangup_signals() -> Vec<Signal> {
+    Vec::new()
+}
+
+/// Does nothing, see [`hangup_signals()`](fn.hangup_signals.html).
+pub unsafe fn ignore_hangup() -> Result<(), Error> {
+    Ok(())
+}
+
+/// Whether a debugger is attached. Not detected on ESP-IDF.
+pub fn is_debugger_attached() -> bool {
+    false
+}
+
+/// Register the handler. `signals` only matter for introspection, any shutdown request wakes
+/// up [`block_ctrl_c()`](fn.block_ctrl_c.html).
+///
+/// Calling this again after [`prearm()`](../../fn.prearm.html) keeps the pending requests.
+/// Nothing is displaced, every signal is reported as replaced with an unknown previous action.
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+#[inline]
+pub unsafe fn init_os_handler(
+    signals: &[Signal],
+    _policy: InstallPolicy,
+) -> Result<Vec<Displaced>, Error> {
+    let mut state = lock();
+    if !state.installed {
+        state.installed = true;
+        state.pending = 0;
+    }
+    Ok(signals
+        .iter()
+        .map(|&signal| Displaced::new(signal, PreviousAction::Unknown, true))
+        .collect())
+}
+
+/// Signals are not distinguished on ESP-IDF, only checks that a handler is registered.
+///
+/// # Errors
+/// Will return an error if no handler is registered.
+pub unsafe fn add_signal(_signal: Signal) -> Result<(), Error> {
+    if !lock().installed {
+        return Err(ErrorKind::NotFound.into());
+    }
+    Ok(())
+}
+
+/// Signals are not distinguished on ESP-IDF, only checks that a handler is registered.
+///
+/// # Errors
+/// Will return an error if no handler is registered.
+pub unsafe fn remove_signal(signal: Signal) -> Result<(), Error> {
+    add_signal(signal)
+}
+
+/// Readers are not interruptible on ESP-IDF, nothing to set up.
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+pub fn prepare_interruptible() -> Result<(), Error> {
+    Ok(())
+}
+
+/// Readers are not interruptible on ESP-IDF, does nothing.
+pub fn interrupt_readers() {}
+
+/// Readers are not interruptible on ESP-IDF, does nothing.
+pub fn resume_readers() {}
+
+/// Not supported on ESP-IDF.
+///
+/// # Errors
+/// Always returns an `Unsupported` error.
+pub fn wait_readable(_stream: &std::net::TcpStream) -> Result<bool, Error> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "interruptible reads are not supported on ESP-IDF",
+    ))
+}
+
+/// There are no signals to set the flag of `set_flag()` for, always returns an `Unsupported`
+/// error.
+pub unsafe fn init_flag_handler(_signals: &[Signal]) -> Result<(), Error> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "there are no signals on ESP-IDF",
+    ))
+}
+
+/// Unregisters the handler set by [`init_os_handler()`](fn.init_os_handler.html) and wakes up
+/// [`block_ctrl_c()`](fn.block_ctrl_c.html).
+#[inline]
+pub unsafe fn deinit_os_handler() -> Result<(), Error> {
+    let mut state = lock();
+    if !state.installed {
+        return Err(ErrorKind::NotFound.into());
+    }
+    state.installed = false;
+    state.generation += 1;
+    WAKEUP.notify_all();
+    Ok(())
+}
+
+pub unsafe fn is_handler_init() -> bool {
+    lock().installed
+}
+
+/// Wake up [`block_ctrl_c()`](fn.block_ctrl_c.html) as if a signal was received. There are no
+/// signals on ESP-IDF, `signal` is ignored.
+///
+/// # Errors
+/// Will return an error if no handler is registered.
+pub unsafe fn inject_signal(_signal: Option<Signal>) -> Result<(), Error> {
+    let mut state = lock();
+    if !state.installed {
+        return Err(ErrorKind::NotFound.into());
+    }
+    state.pending = state.pending.saturating_add(1);
+    WAKEUP.notify_one();
+    Ok(())
+}
+
+/// Blocks until a shutdown is requested.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c() -> Result<BlockOutcome, Error> {
+    block_ctrl_c_for(generation())
+}
+
+/// Identifies the current installation of the handler. Changes when it is removed, so a
+/// handler installed again later has a different generation.
+pub fn generation() -> u64 {
+    lock().generation
+}
+
+/// Shutdown requests are counted, none is ever dropped.
+pub fn missed_signals() -> u64 {
+    0
+}
+
+/// Same as [`block_ctrl_c()`](fn.block_ctrl_c.html), but returns
+/// [`BlockOutcome::HandlerRemoved`] once the handler of `generation` was removed, even if it
+/// was installed again since.
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+pub unsafe fn block_ctrl_c_for(generation: u64) -> Result<BlockOutcome, Error> {
+    Ok(wait(generation, None))
+}
+
+/// Same as [`block_ctrl_c()`](fn.block_ctrl_c.html), but returns [`BlockOutcome::TimedOut`]
+/// once `timeout` passed without a shutdown request.
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c_timeout(timeout: Duration) -> Result<BlockOutcome, Error> {
+    block_ctrl_c_timeout_for(generation(), timeout)
+}
+
+/// Same as [`block_ctrl_c_timeout()`](fn.block_ctrl_c_timeout.html), but for the handler of
+/// `generation`, see [`block_ctrl_c_for()`](fn.block_ctrl_c_for.html).
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+pub unsafe fn block_ctrl_c_timeout_for(
+    generation: u64,
+    timeout: Duration,
+) -> Result<BlockOutcome, Error> {
+    // A deadline too far away for an `Instant` is never reached.
+    Ok(wait(generation, Instant::now().checked_add(timeout)))
+}
+
+/// Wait for a shutdown request to the handler of `generation` until `deadline`, if any.
+fn wait(generation: u64, deadline: Option<Instant>) -> BlockOutcome {
+    let mut state = lock();
+    if !state.installed {
+        return BlockOutcome::HandlerRemoved;
+    }
+    loop {
+        if state.generation != generation {
+            return BlockOutcome::HandlerRemoved;
+        }
+        if state.pending > 0 {
+            state.pending -= 1;
+            return BlockOutcome::Awaited;
+        }
+        state = match deadline {
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left == Duration::ZERO {
+                    return BlockOutcome::TimedOut;
+                }
+                let (state, _) = WAKEUP
+                    .wait_timeout(state, left)
+                    .unwrap_or_else(|e| e.into_inner());
+                state
+            }
+            None => WAKEUP.wait(state).unwrap_or_else(|e| e.into_inner()),
+        };
+    }
+}
+
+/// Blocks until a shutdown is requested, then also takes the requests already made behind it.
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c_all() -> Result<Vec<BlockOutcome>, Error> {
+    block_ctrl_c_all_for(generation())
+}
+
+/// Same as [`block_ctrl_c_all()`](fn.block_ctrl_c_all.html), but for the handler of
+/// `generation`, see [`block_ctrl_c_for()`](fn.block_ctrl_c_for.html).
+///
+/// # Errors
+/// Never fails, the signature matches the other platforms.
+pub unsafe fn block_ctrl_c_all_for(generation: u64) -> Result<Vec<BlockOutcome>, Error> {
+    let first = block_ctrl_c_for(generation)?;
+    if first == BlockOutcome::HandlerRemoved {
+        return Ok(vec![first]);
+    }
+    let mut state = lock();
+    let taken = if state.generation == generation {
+        (state.pending as usize).min(MAX_BATCH - 1)
+    } else {
+        0
+    };
+    state.pending -= taken as u32;
+    Ok(vec![BlockOutcome::Awaited; taken + 1])
+}
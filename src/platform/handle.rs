@@ -0,0 +1,245 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::block_outcome::BlockOutcome;
+use crate::error::Error;
+use crate::lifecycle::{self, LifecycleState};
+use crate::platform::{self, Signal};
+use crate::{cleanup, consumer, handlers, hardening, listener, routes, shutdown, weak};
+use crate::{InstallPolicy, SignalEvent};
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+type PumpHandler = Box<dyn FnMut() + Send>;
+
+/// Safe access to the low-level signal handling layer, without a handler thread.
+///
+/// Creating a `Handle` installs the OS handler, [`wait()`](#method.wait) blocks until one of
+/// the signals is received and dropping it removes the OS handler again. Like
+/// [`set_handler()`](fn.set_handler.html), only one handler can be registered at a time, so
+/// a `Handle` can't be created while another handler is registered and vice versa. Dropping a
+/// `Handle` whose handler was already removed with
+/// [`remove_all_handlers()`](fn.remove_all_handlers.html) leaves any handler registered since
+/// alone.
+///
+/// # Example
+/// ```no_run
+/// let handle = ctrlc::Handle::new(&ctrlc::default_signals(), true)
+///     .expect("Error setting Ctrl-C handler");
+///
+/// loop {
+///     handle.wait().expect("Error waiting for Ctrl-C");
+///     println!("Got Ctrl-C!");
+/// }
+/// ```
+pub struct Handle {
+    generation: u64,
+    /// Number of signals received through this handle, see [`SignalEvent::count()`].
+    received: AtomicU32,
+    /// Run by [`pump()`](#method.pump), set by
+    /// [`set_handler_no_thread()`](fn.set_handler_no_thread.html).
+    handler: Mutex<Option<PumpHandler>>,
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let handler = self.handler.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("Handle")
+            .field("generation", &self.generation)
+            .field("received", &self.received)
+            .field("handler", &handler.is_some())
+            .finish()
+    }
+}
+
+impl Handle {
+    /// Install the OS handler for `signals`.
+    ///
+    /// `policy` has the same meaning as in
+    /// [`Builder::install_policy()`](struct.Builder.html#method.install_policy), `true` and
+    /// `false` as in [`Builder::overwrite()`](struct.Builder.html#method.overwrite).
+    ///
+    /// Signals without an equivalent on the current platform are ignored.
+    ///
+    /// # Errors
+    /// Will return [`Error::MultipleHandlers`] if a handler is already registered, or an error
+    /// if a system error occurred while setting the handler.
+    #[track_caller]
+    pub fn new<P>(signals: &[crate::Signal], policy: P) -> Result<Handle, Error>
+    where
+        P: Into<InstallPolicy>,
+    {
+        let signals: Vec<Signal> = signals
+            .iter()
+            .filter_map(|&signal| platform::from_signal(signal))
+            .collect();
+        Handle::install(&signals, policy.into(), None)
+    }
+
+    /// Install the OS handler for `signals` with the handler run by [`pump()`](#method.pump).
+    #[track_caller]
+    pub(crate) fn install(
+        signals: &[Signal],
+        policy: InstallPolicy,
+        handler: Option<PumpHandler>,
+    ) -> Result<Handle, Error> {
+        let location = handlers::caller_location();
+        crate::init_handler(|| {
+            let displaced = unsafe { platform::init_os_handler(signals, policy)? };
+            handlers::register(signals.to_vec(), displaced, false, location);
+            Ok(Handle {
+                generation: platform::generation(),
+                received: AtomicU32::new(0),
+                handler: Mutex::new(handler),
+            })
+        })
+    }
+
+    /// Block until one of the signals is received.
+    ///
+    /// # Errors
+    /// Will return [`Error::HandlerRemoved`] if the handler was removed with
+    /// [`remove_all_handlers()`](fn.remove_all_handlers.html), also when another handler was
+    /// registered since, or an error if a system error occurred.
+    pub fn wait(&self) -> Result<(), Error> {
+        self.wait_signal().map(|_| ())
+    }
+
+    /// Block until one of the signals is received and return it.
+    ///
+    /// Returns `None` when woken up without a signal, e.g. by
+    /// [`ShutdownController::shutdown()`](struct.ShutdownController.html#method.shutdown).
+    ///
+    /// # Errors
+    /// Same as [`wait()`](#method.wait).
+    pub fn wait_signal(&self) -> Result<Option<crate::Signal>, Error> {
+        Ok(self.recv(None)?.and_then(|event| event.signal()))
+    }
+
+    /// Block until one of the signals is received or `timeout` passed, returns `false` if it
+    /// passed first. A signal already received is still taken with a zero `timeout`.
+    ///
+    /// Lets a thread do periodic housekeeping while still reacting promptly to Ctrl-C.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// let handle = ctrlc::Handle::new(&ctrlc::default_signals(), true)
+    ///     .expect("Error setting Ctrl-C handler");
+    ///
+    /// while !handle.wait_timeout(Duration::from_secs(1)).expect("Error waiting for Ctrl-C") {
+    ///     println!("Housekeeping");
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`wait()`](#method.wait).
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<bool, Error> {
+        Ok(self.recv(Some(timeout))?.is_some())
+    }
+
+    /// Take a signal already received without blocking, `None` if there is none.
+    ///
+    /// Lets a game loop or a GUI main loop poll once per frame instead of parking a thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let handle = ctrlc::Handle::new(&ctrlc::default_signals(), true)
+    ///     .expect("Error setting Ctrl-C handler");
+    ///
+    /// loop {
+    ///     if let Some(event) = handle.try_recv().expect("Error polling for Ctrl-C") {
+    ///         println!("Got {:?}, quitting", event.signal());
+    ///         break;
+    ///     }
+    ///     // ... render a frame ...
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`wait()`](#method.wait).
+    pub fn try_recv(&self) -> Result<Option<SignalEvent>, Error> {
+        self.recv(Some(Duration::ZERO))
+    }
+
+    /// Handle every signal received so far on the calling thread without waiting for more,
+    /// returns how many were taken.
+    ///
+    /// Each signal goes through what the handler thread of
+    /// [`set_handler()`](fn.set_handler.html) does with it: the routes of [`on()`](fn.on.html),
+    /// consumers, listeners, the handler of
+    /// [`set_handler_no_thread()`](fn.set_handler_no_thread.html) and the handlers added with
+    /// [`add_handler()`](fn.add_handler.html), then the callbacks of
+    /// [`on_shutdown()`](fn.on_shutdown.html). In-flight requests are not drained. Call it from
+    /// the main loop, e.g. once per frame or from an idle callback of the GUI toolkit.
+    ///
+    /// # Errors
+    /// Same as [`wait()`](#method.wait).
+    pub fn pump(&self) -> Result<usize, Error> {
+        let mut pumped = 0;
+        while let Some(event) = self.try_recv()? {
+            pumped += 1;
+            if event.platform_signal().map_or(false, routes::dispatch) || consumer::dispatch() {
+                continue;
+            }
+            hardening::check_signal();
+            shutdown::record_signal();
+            #[cfg(feature = "ffi")]
+            crate::ffi::record_signal();
+            listener::record_signal(&event);
+            lifecycle::advance(LifecycleState::Draining);
+            let mut handler = self.handler.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(handler) = handler.as_mut() {
+                handler();
+            }
+            drop(handler);
+            routes::run_added();
+            weak::dispatch();
+            cleanup::run(event.platform_signal());
+            lifecycle::advance(LifecycleState::Stopped);
+        }
+        Ok(pumped)
+    }
+
+    /// A descriptor that is readable while a signal waits to be taken, see
+    /// [`unix::SignalNotifier`](unix/struct.SignalNotifier.html).
+    #[cfg(all(unix, not(target_os = "espidf")))]
+    pub(crate) fn notification_fd(&self) -> Result<std::os::fd::OwnedFd, Error> {
+        platform::notification_fd(self.generation)?.ok_or(Error::HandlerRemoved)
+    }
+
+    /// Wait up to `timeout`, if any, for one of the signals, `None` if it passed first.
+    fn recv(&self, timeout: Option<Duration>) -> Result<Option<SignalEvent>, Error> {
+        let outcome = unsafe {
+            match timeout {
+                Some(timeout) => platform::block_ctrl_c_timeout_for(self.generation, timeout)?,
+                None => platform::block_ctrl_c_for(self.generation)?,
+            }
+        };
+        let signal = match outcome {
+            BlockOutcome::Signal(signal) => Some(signal),
+            BlockOutcome::Awaited => None,
+            BlockOutcome::HandlerRemoved => return Err(Error::HandlerRemoved),
+            BlockOutcome::TimedOut => return Ok(None),
+        };
+        let count = self.received.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(SignalEvent::new(count.saturating_add(1), signal)))
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        // Leave alone a handler registered after this one was removed.
+        if platform::generation() == self.generation {
+            let _ = crate::remove_all_handlers();
+        }
+    }
+}
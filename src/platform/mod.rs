@@ -7,14 +7,23 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-#[cfg(unix)]
+mod handle;
+pub use self::handle::Handle;
+
+#[cfg(all(unix, not(target_os = "espidf")))]
 mod unix;
 
+#[cfg(target_os = "espidf")]
+mod espidf;
+
 #[cfg(windows)]
 mod windows;
 
-#[cfg(unix)]
+#[cfg(all(unix, not(target_os = "espidf")))]
 pub use self::unix::*;
 
+#[cfg(target_os = "espidf")]
+pub use self::espidf::*;
+
 #[cfg(windows)]
 pub use self::windows::*;
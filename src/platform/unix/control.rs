@@ -0,0 +1,145 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Wakeups for the channels reading the signals from a kernel descriptor instead of a signal
+//! handler, see `signalfd` and `kqueue`.
+//!
+//! Injected wakeups and the removal of the handler go through a pipe polled next to that
+//! descriptor, closing its write end wakes up every waiter.
+
+use super::{pipe2, poll, Error, GENERATION, INSTALLED};
+use nix::libc::{self, c_int};
+use nix::unistd;
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::Instant;
+
+/// Read end of the control pipe, -1 while no handler is installed.
+static CONTROL_READ: AtomicI32 = AtomicI32::new(-1);
+/// Write end of the control pipe.
+static CONTROL_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+pub(super) fn open() -> Result<(), Error> {
+    use nix::fcntl::OFlag;
+
+    // Both ends are non-blocking, a waiter may find the byte it was woken up for taken.
+    let control = pipe2(OFlag::O_CLOEXEC | OFlag::O_NONBLOCK)?;
+    CONTROL_READ.store(control.0, Ordering::Release);
+    CONTROL_WRITE.store(control.1, Ordering::Release);
+    Ok(())
+}
+
+pub(super) fn close() {
+    let _ = unistd::close(CONTROL_WRITE.swap(-1, Ordering::AcqRel));
+    let _ = unistd::close(CONTROL_READ.swap(-1, Ordering::AcqRel));
+}
+
+/// Wake up one waiter with signal number `sig`, 0 for a wakeup without a signal.
+pub(super) fn send(sig: c_int) -> Result<(), Error> {
+    let fd = CONTROL_WRITE.load(Ordering::Acquire);
+    if fd == -1 {
+        return Err(Error::ENOENT);
+    }
+    unistd::write(unsafe { BorrowedFd::borrow_raw(fd) }, &[sig as u8])?;
+    Ok(())
+}
+
+/// Duplicate the read end of the control pipe and `source` of `generation`, `None` if that
+/// handler was removed. See `pipe::reader()`.
+fn readers(generation: u64, source: &AtomicI32) -> Result<Option<(OwnedFd, OwnedFd)>, Error> {
+    use nix::fcntl;
+
+    let _installed = INSTALLED.lock().unwrap();
+    let control = CONTROL_READ.load(Ordering::Acquire);
+    let source = source.load(Ordering::Acquire);
+    if control == -1 || source == -1 || GENERATION.load(Ordering::Acquire) != generation {
+        return Ok(None);
+    }
+    let dup = |fd| -> Result<OwnedFd, Error> {
+        let dup = fcntl::fcntl(fd, fcntl::FcntlArg::F_DUPFD_CLOEXEC(0))?;
+        Ok(unsafe { OwnedFd::from_raw_fd(dup) })
+    };
+    Ok(Some((dup(control)?, dup(source)?)))
+}
+
+/// Block until a signal is sent or readable from `source` and return its number, `None` once
+/// the handler of `generation` was removed, or fails with `ETIMEDOUT` once `deadline` passed.
+/// `read` reads a signal from a duplicate of `source` without blocking, `None` if there is none.
+pub(super) fn wait(
+    generation: u64,
+    deadline: Option<Instant>,
+    source: &AtomicI32,
+    mut read: impl FnMut(RawFd) -> Result<Option<c_int>, Error>,
+) -> Result<Option<c_int>, Error> {
+    let (control, source) = match readers(generation, source)? {
+        Some(readers) => readers,
+        None => return Ok(None),
+    };
+    let mut fds = [
+        libc::pollfd {
+            fd: control.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: source.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        // Taken before polling, so a signal already there is taken past the deadline too.
+        match take(&control, &source, &mut read)? {
+            Taken::Signal(sig) => return Ok(Some(sig)),
+            Taken::Removed => return Ok(None),
+            Taken::Nothing => poll(&mut fds, deadline)?,
+        }
+    }
+}
+
+/// Take a signal already sent or readable from `source` without blocking, `None` if there is
+/// none or the handler of `generation` was removed. See [`wait()`].
+pub(super) fn try_take(
+    generation: u64,
+    source: &AtomicI32,
+    mut read: impl FnMut(RawFd) -> Result<Option<c_int>, Error>,
+) -> Result<Option<c_int>, Error> {
+    let (control, source) = match readers(generation, source)? {
+        Some(readers) => readers,
+        None => return Ok(None),
+    };
+    match take(&control, &source, &mut read)? {
+        Taken::Signal(sig) => Ok(Some(sig)),
+        Taken::Removed | Taken::Nothing => Ok(None),
+    }
+}
+
+enum Taken {
+    Signal(c_int),
+    Removed,
+    /// Nothing to take, or another waiter was faster.
+    Nothing,
+}
+
+fn take(
+    control: &OwnedFd,
+    source: &OwnedFd,
+    read: &mut impl FnMut(RawFd) -> Result<Option<c_int>, Error>,
+) -> Result<Taken, Error> {
+    // The control pipe first, a removed handler doesn't report its signals.
+    let mut buf = [0u8];
+    match unistd::read(control.as_raw_fd(), &mut buf) {
+        Ok(1) => return Ok(Taken::Signal(buf[0] as c_int)),
+        Ok(_) => return Ok(Taken::Removed),
+        Err(Error::EAGAIN) | Err(Error::EINTR) => {}
+        Err(e) => return Err(e),
+    }
+    Ok(read(source.as_raw_fd())?.map_or(Taken::Nothing, Taken::Signal))
+}
@@ -0,0 +1,172 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! A kqueue with an `EVFILT_SIGNAL` filter per signal in place of a signal handler and the
+//! self-pipe, with the `kqueue` feature on Apple platforms, FreeBSD and OpenBSD.
+//!
+//! The filter records every attempt to deliver a signal, even an ignored one, so the handled
+//! signals are ignored and no code runs in signal context. Their previous actions are put back
+//! when they are no longer handled, but never called: [`InstallPolicy::Chain`] behaves like
+//! [`InstallPolicy::ForceReplace`]. Ignored signals stay ignored across `exec()`, like with
+//! `SIG_IGN` set by hand.
+//!
+//! Injected wakeups and the removal of the handler go through `control`.
+//!
+//! [`InstallPolicy::Chain`]: crate::InstallPolicy::Chain
+//! [`InstallPolicy::ForceReplace`]: crate::InstallPolicy::ForceReplace
+
+use super::{control, notify, sig_handler_to_sig_action, Channel, Error, Signal, PREVIOUS};
+use nix::libc::{self, c_int};
+use nix::sys::signal::{sigaction, SigAction, SigHandler};
+use nix::{fcntl, unistd};
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+use std::{mem, ptr};
+
+/// The kqueue, -1 while no handler is installed.
+static KQUEUE: AtomicI32 = AtomicI32::new(-1);
+/// Signals ignored by [`hook()`], each has a filter on the kqueue.
+static HOOKED: Mutex<Vec<Signal>> = Mutex::new(Vec::new());
+
+pub(super) struct Kqueue;
+
+fn hooked() -> MutexGuard<'static, Vec<Signal>> {
+    HOOKED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Add or delete the filter for `sig` on `kq`, `flags` is `EV_ADD` or `EV_DELETE`.
+fn change(kq: RawFd, sig: Signal, flags: u16) -> Result<(), Error> {
+    // Zeroed for the fields only some systems have.
+    let mut event: libc::kevent = unsafe { mem::zeroed() };
+    event.ident = sig as usize;
+    event.filter = libc::EVFILT_SIGNAL;
+    event.flags = flags;
+    if unsafe { libc::kevent(kq, &event, 1, ptr::null_mut(), 0, ptr::null()) } == -1 {
+        return Err(Error::last());
+    }
+    Ok(())
+}
+
+/// Ignore `sig` and watch it on the kqueue, returns the action in place before.
+pub(super) unsafe fn hook(sig: Signal) -> Result<SigAction, Error> {
+    let mut hooked = hooked();
+    let old = sigaction(sig, &sig_handler_to_sig_action(SigHandler::SigIgn))?;
+    if hooked.contains(&sig) {
+        return Ok(old);
+    }
+    let kq = KQUEUE.load(Ordering::Acquire);
+    if kq != -1 {
+        if let Err(e) = change(kq, sig, libc::EV_ADD) {
+            let _ = sigaction(sig, &old);
+            return Err(e);
+        }
+    }
+    hooked.push(sig);
+    Ok(old)
+}
+
+/// Stop watching `sig`, putting `previous` back.
+pub(super) unsafe fn unhook(sig: Signal, previous: &SigAction) -> Result<(), Error> {
+    let mut hooked = hooked();
+    sigaction(sig, previous)?;
+    if let Some(index) = hooked.iter().position(|&hooked| hooked == sig) {
+        hooked.remove(index);
+        let kq = KQUEUE.load(Ordering::Acquire);
+        if kq != -1 {
+            let _ = change(kq, sig, libc::EV_DELETE);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `handler` is the one installed by [`hook()`] for `sig`. Called after `hook()`, so an
+/// ignored `sig` is ours only if it was handled before.
+pub(super) fn is_ours(sig: Signal, handler: SigHandler) -> bool {
+    handler == SigHandler::SigIgn && PREVIOUS.lock().unwrap().iter().any(|&(s, _)| s == sig)
+}
+
+fn kqueue() -> Result<RawFd, Error> {
+    let kq = unsafe { libc::kqueue() };
+    if kq == -1 {
+        return Err(Error::last());
+    }
+    if let Err(e) = fcntl::fcntl(kq, fcntl::FcntlArg::F_SETFD(fcntl::FdFlag::FD_CLOEXEC)) {
+        let _ = unistd::close(kq);
+        return Err(e);
+    }
+    for &sig in hooked().iter() {
+        if let Err(e) = change(kq, sig, libc::EV_ADD) {
+            let _ = unistd::close(kq);
+            return Err(e);
+        }
+    }
+    Ok(kq)
+}
+
+/// Take a signal from `kq`, `None` if there is none.
+fn read(kq: RawFd) -> Result<Option<c_int>, Error> {
+    let mut event: libc::kevent = unsafe { mem::zeroed() };
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    match unsafe { libc::kevent(kq, ptr::null(), 0, &mut event, 1, &timeout) } {
+        -1 => match Error::last() {
+            Error::EINTR => Ok(None),
+            e => Err(e),
+        },
+        0 => Ok(None),
+        _ => {
+            let sig = event.ident as c_int;
+            // The filter counts the deliveries since it was last read, the others get them
+            // through the control pipe.
+            for _ in 1..event.data {
+                notify(sig);
+            }
+            Ok(Some(sig))
+        }
+    }
+}
+
+impl Channel for Kqueue {
+    fn open() -> Result<(), Error> {
+        control::open()?;
+        match kqueue() {
+            Ok(kq) => KQUEUE.store(kq, Ordering::Release),
+            Err(e) => {
+                control::close();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn close() {
+        let _ = unistd::close(KQUEUE.swap(-1, Ordering::AcqRel));
+        control::close();
+    }
+
+    fn is_open() -> bool {
+        KQUEUE.load(Ordering::Acquire) != -1
+    }
+
+    fn send(sig: c_int) -> Result<(), Error> {
+        control::send(sig)
+    }
+
+    fn recv(generation: u64, deadline: Option<Instant>) -> Result<Option<c_int>, Error> {
+        control::wait(generation, deadline, &KQUEUE, read)
+    }
+
+    fn try_recv(generation: u64) -> Result<Option<c_int>, Error> {
+        control::try_take(generation, &KQUEUE, read)
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Blocking the handled signals for the hooks taking them without a signal handler, see
+//! `signalfd` and `sigwait`.
+//!
+//! Blocking only affects the thread installing the handler and the threads it spawns
+//! afterwards, a thread spawned before still gets the action in place for the signals sent to it.
+
+use super::{Error, Signal};
+use nix::libc;
+use nix::sys::signal::{pthread_sigmask, SigSet, SigmaskHow};
+use std::ptr;
+
+pub(super) fn sigset(signals: &[Signal]) -> SigSet {
+    let mut set = SigSet::empty();
+    for &signal in signals {
+        set.add(signal);
+    }
+    set
+}
+
+/// Block `sig` in the calling thread.
+pub(super) fn block(sig: Signal) -> Result<(), Error> {
+    pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&sigset(&[sig])), None)
+}
+
+/// Unblock `sig` in the calling thread, dropping the deliveries still pending.
+pub(super) fn unblock(sig: Signal) -> Result<(), Error> {
+    // Nobody takes the signals still pending now, unblocking would deliver them.
+    let set = sigset(&[sig]);
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    while unsafe { libc::sigtimedwait(set.as_ref(), ptr::null_mut(), &timeout) } > 0 {}
+    pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&set), None)
+}
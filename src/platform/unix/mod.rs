@@ -7,16 +7,50 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-use crate::block_outcome::BlockOutcome;
+use crate::block_outcome::{BlockOutcome, MAX_BATCH};
 use crate::error::Error as CtrlcError;
+use crate::{Displaced, InstallPolicy, PreviousAction, SignalType, ThreadPriority};
 use nix::sys::signal::SigAction;
 use nix::sys::signal::SigHandler;
 use nix::unistd;
+use std::io;
 use std::os::fd::BorrowedFd;
 use std::os::fd::IntoRawFd;
+use std::os::fd::OwnedFd;
 use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-static mut PIPE: (RawFd, RawFd) = (-1, -1);
+/// Changes whenever the channel is closed, e.g. by
+/// [`deinit_os_handler()`](fn.deinit_os_handler.html), see [`generation()`](fn.generation.html).
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Signals dropped because the channel was full, see
+/// [`missed_signals()`](fn.missed_signals.html).
+static MISSED: AtomicU64 = AtomicU64::new(0);
+/// Signals we handle. Its lock also serializes opening and closing the channel.
+static INSTALLED: Mutex<Vec<Signal>> = Mutex::new(Vec::new());
+/// Actions in place before we installed ours, restored when a signal is no longer handled.
+static PREVIOUS: Mutex<Vec<(Signal, SigAction)>> = Mutex::new(Vec::new());
+
+/// Whether the previous handlers are called after ours, see [`InstallPolicy::Chain`].
+static CHAIN: AtomicBool = AtomicBool::new(false);
+const MAX_SIGNAL: usize = 128;
+// Only used to initialize the arrays below.
+#[allow(clippy::declare_interior_mutable_const)]
+const NOT_CHAINED: AtomicUsize = AtomicUsize::new(0);
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_SIGINFO: AtomicBool = AtomicBool::new(false);
+/// Address of the previous handler for each signal number, 0 if it is not chained. The signal
+/// handler can't take the `PREVIOUS` lock.
+static CHAINED: [AtomicUsize; MAX_SIGNAL] = [NOT_CHAINED; MAX_SIGNAL];
+/// Whether the previous handler is a `SA_SIGINFO` handler.
+static CHAINED_SIGINFO: [AtomicBool; MAX_SIGNAL] = [NO_SIGINFO; MAX_SIGNAL];
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_SENDER: AtomicU64 = AtomicU64::new(0);
+/// Process id and user id of the process that sent the last of each signal number, packed by
+/// [`record_sender()`], 0 if the kernel sent it.
+static SENDERS: [AtomicU64; MAX_SIGNAL] = [NO_SENDER; MAX_SIGNAL];
 
 /// Platform specific error type
 pub type Error = nix::Error;
@@ -24,14 +58,283 @@ pub type Error = nix::Error;
 /// Platform specific signal type
 pub type Signal = nix::sys::signal::Signal;
 
-extern "C" fn os_handler(_: nix::libc::c_int) {
-    // Assuming this always succeeds. Can't really handle errors in any meaningful way.
+#[cfg(ctrlc_hook = "registry")]
+mod registry;
+#[cfg(ctrlc_hook = "registry")]
+use self::registry::{hook, is_ours, unhook};
+
+#[cfg(ctrlc_hook = "sigwait")]
+mod sigwait;
+#[cfg(ctrlc_hook = "sigwait")]
+use self::sigwait::{hook, is_ours, unhook};
+
+#[cfg(any(ctrlc_hook = "signalfd", ctrlc_hook = "sigwait"))]
+mod mask;
+
+/// How the signal handler wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html), chosen at compile
+/// time by `ctrlc_channel`, see `build.rs`: the self-pipe, see `pipe`, a POSIX semaphore with the
+/// `posix-semaphore` feature, see `semaphore`, or instead of a signal handler a signalfd with the
+/// `signalfd` feature, see `signalfd`, or a kqueue with the `kqueue` feature, see `kqueue`.
+///
+/// `open()` and `close()` are called with the `INSTALLED` lock held, `close()` after the
+/// generation changed.
+trait Channel {
+    /// Set up the channel for a newly installed handler.
+    fn open() -> Result<(), Error>;
+    /// Tear down the channel and wake up all of its waiters.
+    fn close();
+    /// Whether the channel is set up.
+    fn is_open() -> bool;
+    /// Wake up one waiter with signal number `sig`, 0 for a wakeup without a signal.
+    /// Async-signal-safe.
+    fn send(sig: nix::libc::c_int) -> Result<(), Error>;
+    /// Block until woken up and return the signal number sent, `None` once the handler of
+    /// `generation` was removed. Fails with `ETIMEDOUT` once `deadline` passed, if any.
+    fn recv(generation: u64, deadline: Option<Instant>) -> Result<Option<nix::libc::c_int>, Error>;
+    /// Take a signal number already sent without blocking, `None` if there is none or the
+    /// handler of `generation` was removed.
+    fn try_recv(generation: u64) -> Result<Option<nix::libc::c_int>, Error>;
+    /// A descriptor of its own that is readable while a signal number waits to be taken, `None`
+    /// if the handler of `generation` was removed. Fails with `ENOTSUP` if the channel has no
+    /// such descriptor.
+    fn notification_fd(_generation: u64) -> Result<Option<OwnedFd>, Error> {
+        Err(Error::ENOTSUP)
+    }
+}
+
+#[cfg(ctrlc_channel = "pipe")]
+mod pipe;
+#[cfg(ctrlc_channel = "pipe")]
+use self::pipe::Pipe as Active;
+
+#[cfg(ctrlc_channel = "semaphore")]
+mod semaphore;
+#[cfg(ctrlc_channel = "semaphore")]
+use self::semaphore::Semaphore as Active;
+
+#[cfg(ctrlc_channel = "signalfd")]
+mod signalfd;
+#[cfg(ctrlc_channel = "signalfd")]
+use self::signalfd::{hook, is_ours, unhook, Signalfd as Active};
+
+#[cfg(ctrlc_channel = "kqueue")]
+mod kqueue;
+#[cfg(ctrlc_channel = "kqueue")]
+use self::kqueue::{hook, is_ours, unhook, Kqueue as Active};
+
+#[cfg(any(ctrlc_channel = "signalfd", ctrlc_channel = "kqueue"))]
+mod control;
+
+#[cfg(any(ctrlc_channel = "semaphore", ctrlc_eventfd))]
+mod pending;
+
+/// Wake up [`block_ctrl_c()`](fn.block_ctrl_c.html) with `sig`, called from the signal handler.
+fn notify(sig: nix::libc::c_int) {
+    // The channel is full during a signal storm, or was closed meanwhile. All a signal handler
+    // can do about it is count it.
+    if Active::send(sig).is_err() {
+        MISSED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Remember who sent `sig`, before waking up [`block_ctrl_c()`](fn.block_ctrl_c.html) for it.
+/// `None` if it was not sent by a process. Async-signal-safe.
+fn record_sender(sig: nix::libc::c_int, sender: Option<(u32, u32)>) {
+    let index = sig as usize;
+    if index >= MAX_SIGNAL {
+        return;
+    }
+    let packed = sender.map_or(0, |(pid, uid)| u64::from(pid) << 32 | u64::from(uid));
+    SENDERS[index].store(packed, Ordering::Release);
+}
+
+/// Whether a signal with `si_code` `code` was sent by a process, e.g. with `kill(2)`, rather
+/// than by the kernel or a timer.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sent_by_process(code: nix::libc::c_int) -> bool {
+    use nix::libc;
+
+    matches!(code, libc::SI_USER | libc::SI_QUEUE | libc::SI_TKILL)
+}
+
+/// Sender of a signal as `(pid, uid)` from its `siginfo_t`, `None` if the kernel sent it.
+#[cfg_attr(
+    not(any(
+        ctrlc_hook = "sigaction",
+        ctrlc_hook = "registry",
+        ctrlc_hook = "sigwait"
+    )),
+    allow(dead_code)
+)]
+fn siginfo_sender(info: &nix::libc::siginfo_t) -> Option<(u32, u32)> {
+    // Elsewhere the kernel leaves the process id of its signals 0.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if !sent_by_process(info.si_code) {
+        return None;
+    }
+    let (pid, uid) = unsafe { (info.si_pid(), info.si_uid()) };
+    (pid > 0).then_some((pid as u32, uid))
+}
+
+/// Process id and user id of the process that sent the last `signal` received, `None` if it
+/// was not sent by a process, e.g. Ctrl-C in the terminal, or this is not known.
+pub fn sender(signal: Signal) -> Option<(u32, u32)> {
+    let packed = SENDERS.get(signal as usize)?.load(Ordering::Acquire);
+    (packed != 0).then_some(((packed >> 32) as u32, packed as u32))
+}
+
+#[cfg_attr(not(ctrlc_hook = "sigaction"), allow(dead_code))]
+extern "C" fn os_handler(
+    sig: nix::libc::c_int,
+    info: *mut nix::libc::siginfo_t,
+    context: *mut nix::libc::c_void,
+) {
+    record_sender(sig, unsafe { info.as_ref() }.and_then(siginfo_sender));
+    notify(sig);
+    call_chained(sig, info, context);
+}
+
+/// Signal handler of [`init_flag_handler()`].
+extern "C" fn flag_handler(_sig: nix::libc::c_int) {
+    crate::flag::record_signal();
+}
+
+/// Install a handler for `signals` that only sets the flag of `set_flag()`, without a channel.
+/// A plain `sigaction()` on every backend, since nothing reads a signalfd or kqueue then.
+///
+/// # Errors
+/// Will return an error if a system error occurred, the signals handled so far are put back.
+pub unsafe fn init_flag_handler(signals: &[Signal]) -> Result<(), Error> {
+    use nix::sys::signal::sigaction;
+
+    let action = sig_handler_to_sig_action(SigHandler::Handler(flag_handler));
+    let mut installed = Vec::with_capacity(signals.len());
+    for &sig in signals {
+        match sigaction(sig, &action) {
+            Ok(old) => installed.push((sig, old)),
+            Err(e) => {
+                for (sig, old) in installed.iter().rev() {
+                    let _ = sigaction(*sig, old);
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn call_chained(
+    sig: nix::libc::c_int,
+    info: *mut nix::libc::siginfo_t,
+    context: *mut nix::libc::c_void,
+) {
+    let index = sig as usize;
+    if index >= MAX_SIGNAL {
+        return;
+    }
+    let address = CHAINED[index].load(Ordering::Acquire);
+    if address == 0 {
+        return;
+    }
+    // The addresses were taken from `SigHandler::Handler` and `SigHandler::SigAction`.
     unsafe {
-        let fd = BorrowedFd::borrow_raw(PIPE.1);
-        let _ = unistd::write(fd, &[0u8]);
+        if CHAINED_SIGINFO[index].load(Ordering::Relaxed) {
+            let handler: extern "C" fn(
+                nix::libc::c_int,
+                *mut nix::libc::siginfo_t,
+                *mut nix::libc::c_void,
+            ) = std::mem::transmute(address);
+            handler(sig, info, context);
+        } else {
+            let handler: extern "C" fn(nix::libc::c_int) = std::mem::transmute(address);
+            handler(sig);
+        }
+    }
+}
+
+/// Chain to `previous`, the action in place before ours, if chaining is enabled and it is a
+/// handler function. `None` stops chaining.
+fn set_chained(sig: Signal, previous: Option<&SigAction>) {
+    let index = sig as usize;
+    if index >= MAX_SIGNAL {
+        return;
+    }
+    let chained = match previous.map(SigAction::handler) {
+        _ if !CHAIN.load(Ordering::Relaxed) => None,
+        Some(SigHandler::Handler(handler)) => Some((handler as usize, false)),
+        Some(SigHandler::SigAction(handler)) => Some((handler as usize, true)),
+        _ => None,
+    };
+    match chained {
+        Some((address, siginfo)) => {
+            CHAINED_SIGINFO[index].store(siginfo, Ordering::Relaxed);
+            CHAINED[index].store(address, Ordering::Release);
+        }
+        None => CHAINED[index].store(0, Ordering::Release),
+    }
+}
+
+fn previous_action(action: &SigAction) -> PreviousAction {
+    match action.handler() {
+        SigHandler::SigDfl => PreviousAction::Default,
+        SigHandler::SigIgn => PreviousAction::Ignore,
+        SigHandler::Handler(handler) => PreviousAction::Handler(handler as usize),
+        SigHandler::SigAction(handler) => PreviousAction::Handler(handler as usize),
     }
 }
 
+#[cfg_attr(not(ctrlc_hook = "sigaction"), allow(dead_code))]
+fn os_sig_handler() -> SigHandler {
+    SigHandler::SigAction(os_handler)
+}
+
+/// The action currently installed for `sig`. The mask is left out, it is only needed to put an
+/// action back, which the backends calling this never do.
+#[cfg(any(
+    ctrlc_hook = "registry",
+    ctrlc_hook = "signalfd",
+    ctrlc_hook = "sigwait"
+))]
+unsafe fn current_action(sig: Signal) -> Result<SigAction, Error> {
+    use nix::libc::{self, c_int};
+    use nix::sys::signal::{SaFlags, SigSet};
+    use std::{mem, ptr};
+
+    let mut action: libc::sigaction = mem::zeroed();
+    if libc::sigaction(sig as c_int, ptr::null(), &mut action) != 0 {
+        return Err(Error::last());
+    }
+    let flags = SaFlags::from_bits_truncate(action.sa_flags);
+    let handler = match action.sa_sigaction {
+        libc::SIG_DFL => SigHandler::SigDfl,
+        libc::SIG_IGN => SigHandler::SigIgn,
+        address if flags.contains(SaFlags::SA_SIGINFO) => SigHandler::SigAction(mem::transmute::<
+            usize,
+            extern "C" fn(c_int, *mut libc::siginfo_t, *mut libc::c_void),
+        >(address)),
+        address => SigHandler::Handler(mem::transmute::<usize, extern "C" fn(c_int)>(address)),
+    };
+    Ok(SigAction::new(handler, flags, SigSet::empty()))
+}
+
+/// Install our handler for `sig`, returns the action in place before.
+#[cfg(ctrlc_hook = "sigaction")]
+unsafe fn hook(sig: Signal) -> Result<SigAction, Error> {
+    nix::sys::signal::sigaction(sig, &sig_handler_to_sig_action(os_sig_handler()))
+}
+
+/// Stop handling `sig`, putting `previous` back.
+#[cfg(ctrlc_hook = "sigaction")]
+unsafe fn unhook(sig: Signal, previous: &SigAction) -> Result<(), Error> {
+    nix::sys::signal::sigaction(sig, previous).map(drop)
+}
+
+/// Whether `handler` is the one installed by [`hook()`] for `sig`.
+#[cfg(ctrlc_hook = "sigaction")]
+fn is_ours(_sig: Signal, handler: SigHandler) -> bool {
+    handler == os_sig_handler()
+}
+
 // pipe2(2) is not available on macOS, iOS, AIX, Haiku, etc., so we need to use pipe(2) and fcntl(2)
 #[inline]
 #[cfg(any(
@@ -82,112 +385,537 @@ fn pipe2(flags: nix::fcntl::OFlag) -> nix::Result<(RawFd, RawFd)> {
     Ok((pipe.0.into_raw_fd(), pipe.1.into_raw_fd()))
 }
 
-unsafe fn close_pipe() {
-    // Try to close the pipes. close() should not fail,
-    // but if it does, there isn't much we can do
-    let _ = unistd::close(PIPE.1);
-    let _ = unistd::close(PIPE.0);
-    PIPE = (-1, -1);
+/// Block until one of `fds` is ready, fails with `ETIMEDOUT` once `deadline` passed, if any.
+#[cfg(not(ctrlc_channel = "semaphore"))]
+fn poll(fds: &mut [nix::libc::pollfd], deadline: Option<Instant>) -> Result<(), Error> {
+    use nix::libc;
+
+    loop {
+        let timeout = match deadline {
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left == Duration::ZERO {
+                    return Err(Error::ETIMEDOUT);
+                }
+                // Rounded up, so it doesn't wake up just before the deadline and spin.
+                let millis = (left.as_nanos() + 999_999) / 1_000_000;
+                millis.min(libc::c_int::MAX as u128) as libc::c_int
+            }
+            None => -1,
+        };
+        match unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) } {
+            -1 => match Error::last() {
+                Error::EINTR => {}
+                e => return Err(e),
+            },
+            0 => {}
+            _ => return Ok(()),
+        }
+    }
 }
 
-/// Register os signal handler.
-///
-/// Must be called before calling [`block_ctrl_c()`](fn.block_ctrl_c.html)
-/// and should only be called once.
+/// Close the channel and wake up its waiters, which get
+/// [`BlockOutcome::HandlerRemoved`](crate::BlockOutcome::HandlerRemoved).
+fn close_channel() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    Active::close();
+}
+
+/// Signals handled when no explicit signal set is requested: `SIGINT`, plus `SIGTERM` and
+/// `SIGHUP` with the `termination` feature.
+pub fn default_signals() -> Vec<Signal> {
+    #[cfg(not(feature = "termination"))]
+    let signals = vec![Signal::SIGINT];
+
+    #[cfg(feature = "termination")]
+    let signals = vec![Signal::SIGINT, Signal::SIGTERM, Signal::SIGHUP];
+
+    signals
+}
+
+/// Platform signals corresponding to a [`SignalType`](crate::SignalType).
+pub fn signals_of(kind: &SignalType) -> Vec<Signal> {
+    match *kind {
+        SignalType::Ctrlc => vec![Signal::SIGINT],
+        SignalType::Termination => vec![Signal::SIGTERM, Signal::SIGHUP],
+        SignalType::Other(signal) => from_signal(signal).into_iter().collect(),
+    }
+}
+
+/// The [`crate::Signal`] for a platform signal.
+pub fn to_signal(signal: Signal) -> crate::Signal {
+    match signal {
+        Signal::SIGINT => crate::Signal::CtrlC,
+        Signal::SIGQUIT => crate::Signal::Break,
+        Signal::SIGTERM => crate::Signal::Terminate,
+        Signal::SIGHUP => crate::Signal::Hangup,
+        Signal::SIGUSR1 => crate::Signal::User1,
+        Signal::SIGUSR2 => crate::Signal::User2,
+        signal => crate::Signal::Other(signal as i32),
+    }
+}
+
+/// The platform signal for a [`crate::Signal`], `None` if there is none.
+pub fn from_signal(signal: crate::Signal) -> Option<Signal> {
+    match signal {
+        crate::Signal::CtrlC => Some(Signal::SIGINT),
+        crate::Signal::Break => Some(Signal::SIGQUIT),
+        crate::Signal::Terminate => Some(Signal::SIGTERM),
+        crate::Signal::Hangup => Some(Signal::SIGHUP),
+        crate::Signal::User1 => Some(Signal::SIGUSR1),
+        crate::Signal::User2 => Some(Signal::SIGUSR2),
+        crate::Signal::Logoff | crate::Signal::Shutdown => None,
+        crate::Signal::Other(signal) => Signal::try_from(signal).ok(),
+    }
+}
+
+/// The usual Unix signal names.
+#[allow(missing_docs)]
+impl crate::Signal {
+    pub const SIGHUP: Self = Self::Hangup;
+    pub const SIGINT: Self = Self::CtrlC;
+    pub const SIGQUIT: Self = Self::Break;
+    pub const SIGILL: Self = Self::Other(nix::libc::SIGILL);
+    pub const SIGTRAP: Self = Self::Other(nix::libc::SIGTRAP);
+    pub const SIGABRT: Self = Self::Other(nix::libc::SIGABRT);
+    pub const SIGBUS: Self = Self::Other(nix::libc::SIGBUS);
+    pub const SIGFPE: Self = Self::Other(nix::libc::SIGFPE);
+    pub const SIGKILL: Self = Self::Other(nix::libc::SIGKILL);
+    pub const SIGUSR1: Self = Self::User1;
+    pub const SIGSEGV: Self = Self::Other(nix::libc::SIGSEGV);
+    pub const SIGUSR2: Self = Self::User2;
+    pub const SIGPIPE: Self = Self::Other(nix::libc::SIGPIPE);
+    pub const SIGALRM: Self = Self::Other(nix::libc::SIGALRM);
+    pub const SIGTERM: Self = Self::Terminate;
+    pub const SIGCHLD: Self = Self::Other(nix::libc::SIGCHLD);
+    pub const SIGCONT: Self = Self::Other(nix::libc::SIGCONT);
+    pub const SIGSTOP: Self = Self::Other(nix::libc::SIGSTOP);
+    pub const SIGTSTP: Self = Self::Other(nix::libc::SIGTSTP);
+    pub const SIGTTIN: Self = Self::Other(nix::libc::SIGTTIN);
+    pub const SIGTTOU: Self = Self::Other(nix::libc::SIGTTOU);
+    pub const SIGURG: Self = Self::Other(nix::libc::SIGURG);
+    pub const SIGXCPU: Self = Self::Other(nix::libc::SIGXCPU);
+    pub const SIGXFSZ: Self = Self::Other(nix::libc::SIGXFSZ);
+    pub const SIGVTALRM: Self = Self::Other(nix::libc::SIGVTALRM);
+    pub const SIGPROF: Self = Self::Other(nix::libc::SIGPROF);
+    pub const SIGWINCH: Self = Self::Other(nix::libc::SIGWINCH);
+    pub const SIGIO: Self = Self::Other(nix::libc::SIGIO);
+    pub const SIGSYS: Self = Self::Other(nix::libc::SIGSYS);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const SIGPWR: Self = Self::Other(nix::libc::SIGPWR);
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub const SIGINFO: Self = Self::Other(nix::libc::SIGINFO);
+}
+
+/// Parse a signal name such as `INT`, `SIGTERM` or `hup`.
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    let name = name.trim().to_ascii_uppercase();
+    if name.starts_with("SIG") {
+        name.parse().ok()
+    } else {
+        format!("SIG{}", name).parse().ok()
+    }
+}
+
+/// Every signal a handler can be installed for, i.e. all but `SIGKILL` and `SIGSTOP`.
+pub fn supported_signals() -> Vec<Signal> {
+    Signal::iterator()
+        .filter(|&signal| signal != Signal::SIGKILL && signal != Signal::SIGSTOP)
+        .collect()
+}
+
+/// Whether `signal` asks the process to terminate.
+pub fn is_termination(signal: Signal) -> bool {
+    matches!(
+        signal,
+        Signal::SIGINT | Signal::SIGTERM | Signal::SIGHUP | Signal::SIGQUIT
+    )
+}
+
+/// Exit status of a shell for a process killed by `signal`, `128 + signal`.
+pub fn exit_code(signal: Signal) -> u8 {
+    128 + signal as u8
+}
+
+/// Size of the terminal as `(cols, rows)` if `signal` is `SIGWINCH`, from `TIOCGWINSZ` on the
+/// first of stdout, stdin and stderr that is a terminal.
+pub fn window_size(signal: Signal) -> Option<(u16, u16)> {
+    use nix::libc;
+
+    if signal != Signal::SIGWINCH {
+        return None;
+    }
+    [libc::STDOUT_FILENO, libc::STDIN_FILENO, libc::STDERR_FILENO]
+        .into_iter()
+        .find_map(|fd| {
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+            let ret = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) };
+            (ret == 0).then_some((size.ws_col, size.ws_row))
+        })
+}
+
+/// Name of `signal`, accepted by [`parse_signal()`](fn.parse_signal.html).
+pub fn signal_name(signal: Signal) -> String {
+    signal.as_str().to_string()
+}
+
+/// Terminate the process without running `atexit` handlers or destructors.
+pub fn exit_immediately(code: i32) -> ! {
+    unsafe { nix::libc::_exit(code) }
+}
+
+/// Terminate the process with the default action of `signal`, falling back to
+/// `_exit(128 + signal)` if that action doesn't terminate.
+pub fn exit_with_signal(signal: Signal) -> ! {
+    use nix::sys::signal::{pthread_sigmask, raise, sigaction, SigSet, SigmaskHow};
+
+    let _ = unsafe { sigaction(signal, &sig_handler_to_sig_action(SigHandler::SigDfl)) };
+    // Blocked with the `signalfd` and `sigwait` features.
+    let mut unblocked = SigSet::empty();
+    unblocked.add(signal);
+    let _ = pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&unblocked), None);
+    let _ = raise(signal);
+    exit_immediately(128 + signal as i32)
+}
+
+/// Set the nice value of the current thread.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_thread_priority(priority: ThreadPriority) -> io::Result<()> {
+    use nix::libc;
+
+    let nice = match priority {
+        ThreadPriority::Normal => 0,
+        ThreadPriority::AboveNormal => -5,
+        ThreadPriority::Highest => -10,
+    };
+    // The nice value is per thread on Linux, `PRIO_PROCESS` with a thread id sets it.
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, nice) };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Set the nice value of the current thread, not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_thread_priority(_priority: ThreadPriority) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread priorities are only supported on Linux and Android",
+    ))
+}
+
+/// Restrict the current thread to `cpus`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_thread_affinity(cpus: &[usize]) -> io::Result<()> {
+    use nix::libc;
+
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "CPU index out of range",
+                ));
+            }
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Restrict the current thread to `cpus`, not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_thread_affinity(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread affinity is only supported on Linux and Android",
+    ))
+}
+
+/// Signals sent when the controlling terminal goes away.
+pub fn hangup_signals() -> Vec<Signal> {
+    vec![Signal::SIGHUP]
+}
+
+/// Ignore [`hangup_signals()`](fn.hangup_signals.html), like `nohup(1)`.
 ///
 /// # Errors
 /// Will return an error if a system error occurred.
-///
-#[inline]
-pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
-    use nix::fcntl;
-    use nix::sys::signal;
-    
-    PIPE = pipe2(fcntl::OFlag::O_CLOEXEC)?;
+pub unsafe fn ignore_hangup() -> Result<(), Error> {
+    let action = sig_handler_to_sig_action(SigHandler::SigIgn);
+    nix::sys::signal::sigaction(Signal::SIGHUP, &action)?;
+    Ok(())
+}
+
+/// Name of the process `pid`, from `/proc/<pid>/comm`. `None` if it doesn't exist (anymore).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn process_name(pid: u32) -> Option<String> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end_matches('\n').to_string())
+}
 
-    // Make sure we never block on write in the os handler.
-    if let Err(e) = fcntl::fcntl(PIPE.1, fcntl::FcntlArg::F_SETFL(fcntl::OFlag::O_NONBLOCK)) {
-        close_pipe();
-        return Err(e);
+/// Process names can't be looked up on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn process_name(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Whether the process is being traced, e.g. by gdb or lldb.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn is_debugger_attached() -> bool {
+    let status = match std::fs::read_to_string("/proc/self/status") {
+        Ok(status) => status,
+        Err(_) => return false,
+    };
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("TracerPid:"))
+        .map_or(false, |pid| pid.trim() != "0")
+}
+
+/// Whether the process is being traced, e.g. by gdb or lldb.
+#[cfg(all(target_vendor = "apple", target_pointer_width = "64"))]
+pub fn is_debugger_attached() -> bool {
+    use nix::libc;
+
+    /// The start of `struct kinfo_proc`, which the libc crate does not expose, up to
+    /// `kp_proc.p_flag`.
+    #[repr(C)]
+    struct KinfoProcHead {
+        /// `p_un`, a union of two pointers and a `timeval`, then `p_vmspace` and `p_sigacts`.
+        _pointers: [*mut libc::c_void; 4],
+        p_flag: libc::c_int,
     }
 
-    let handler = signal::SigHandler::Handler(os_handler);
-    let new_action = sig_handler_to_sig_action(handler);
+    const KINFO_PROC_SIZE: usize = 648;
+    const P_TRACED: libc::c_int = 0x0000_0800;
+    // `p_flag` is at offset 32, right after the pointers.
+    const _: () = assert!(std::mem::size_of::<KinfoProcHead>() == 40);
 
-    let sigint_old = match signal::sigaction(signal::Signal::SIGINT, &new_action) {
-        Ok(old) => old,
-        Err(e) => {
-            close_pipe();
-            return Err(e)
-        }
+    // Aligned for the pointers of `KinfoProcHead`.
+    let mut info = [0u64; KINFO_PROC_SIZE / 8];
+    let mut size = KINFO_PROC_SIZE;
+    let mut mib = [
+        libc::CTL_KERN,
+        libc::KERN_PROC,
+        libc::KERN_PROC_PID,
+        std::process::id() as libc::c_int,
+    ];
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            info.as_mut_ptr().cast(),
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
     };
-    if !overwrite && sigint_old.handler() != signal::SigHandler::SigDfl {
-        signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-        close_pipe();
-        return Err(nix::Error::EEXIST);
+    // Any other size means a layout this code doesn't know, don't guess.
+    if ret != 0 || size != KINFO_PROC_SIZE {
+        return false;
     }
+    let head = unsafe { &*(info.as_ptr() as *const KinfoProcHead) };
+    head.p_flag & P_TRACED != 0
+}
 
-    #[cfg(feature = "termination")]
-    {
-        let sigterm_old = match signal::sigaction(signal::Signal::SIGTERM, &new_action) {
+/// Whether the process is being traced. Always `false` where detection is not implemented.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    all(target_vendor = "apple", target_pointer_width = "64"),
+)))]
+pub fn is_debugger_attached() -> bool {
+    false
+}
+
+/// Register os signal handler for `signals`.
+///
+/// Must be called before calling [`block_ctrl_c()`](fn.block_ctrl_c.html)
+/// and should only be called once, except to replace a handler installed by
+/// [`prearm()`](../../fn.prearm.html). In that case the pipe, and any signals already written
+/// to it, is kept.
+///
+/// `policy` decides what happens to the actions already installed. Returns what was installed
+/// before for each of `signals`, signals left alone by [`InstallPolicy::ReplaceIfDefault`] are
+/// not handled.
+///
+/// # Errors
+/// Will return an error if a system error occurred. If restoring the previous handlers fails
+/// as well, the error is [`Error::Rollback`](../../enum.Error.html#variant.Rollback).
+///
+#[inline]
+pub unsafe fn init_os_handler(
+    signals: &[Signal],
+    policy: InstallPolicy,
+) -> Result<Vec<Displaced>, CtrlcError> {
+    let mut previously_installed = INSTALLED.lock().unwrap();
+    let prearmed = is_handler_init();
+    if !prearmed {
+        Active::open()?;
+    }
+
+    let mut installed: Vec<(Signal, SigAction)> = Vec::with_capacity(signals.len());
+    let mut displaced = Vec::with_capacity(signals.len());
+    for &sig in signals {
+        let old = match hook(sig) {
             Ok(old) => old,
-            Err(e) => {
-                signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-                close_pipe();
-                return Err(e);
-            }
+            Err(e) => return Err(roll_back(&installed, prearmed, e)),
         };
-        if !overwrite && sigterm_old.handler() != signal::SigHandler::SigDfl {
-            signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-            signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
-            close_pipe();
-            return Err(nix::Error::EEXIST);
+        let existing = old.handler() != SigHandler::SigDfl && !is_ours(sig, old.handler());
+        match policy {
+            InstallPolicy::ErrorIfExists if existing => {
+                installed.push((sig, old));
+                return Err(roll_back(&installed, prearmed, nix::Error::EEXIST));
+            }
+            InstallPolicy::ReplaceIfDefault if existing && old.handler() != SigHandler::SigIgn => {
+                // Put the handler back and leave the signal alone.
+                if let Err(e) = unhook(sig, &old) {
+                    installed.push((sig, old));
+                    return Err(roll_back(&installed, prearmed, e));
+                }
+                displaced.push(Displaced::new(sig, previous_action(&old), false));
+            }
+            _ => installed.push((sig, old)),
         }
-        let sighup_old = match signal::sigaction(signal::Signal::SIGHUP, &new_action) {
-            Ok(old) => old,
-            Err(e) => {
-                signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-                signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
-                close_pipe();
-                return Err(e);
+    }
+    CHAIN.store(policy == InstallPolicy::Chain, Ordering::Relaxed);
+    let handled: Vec<Signal> = installed.iter().map(|&(sig, _)| sig).collect();
+
+    let mut previous = PREVIOUS.lock().unwrap();
+    for &sig in previously_installed.iter() {
+        if !handled.contains(&sig) {
+            let _ = unhook(sig, &take_previous(&mut previous, sig));
+        }
+    }
+    for (sig, old) in installed {
+        // Reinstalling over a prearmed handler keeps the action from before it.
+        let old = match previous.iter().find(|&&(previous, _)| previous == sig) {
+            Some((_, before)) => *before,
+            None => {
+                previous.push((sig, old));
+                old
             }
         };
-        if !overwrite && sighup_old.handler() != signal::SigHandler::SigDfl {
-            signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-            signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
-            signal::sigaction(signal::Signal::SIGHUP, &sighup_old).unwrap();
-            close_pipe();
-            return Err(nix::Error::EEXIST);
-        }
+        displaced.push(Displaced::new(sig, previous_action(&old), true));
     }
+    for (sig, old) in previous.iter() {
+        set_chained(*sig, Some(old));
+    }
+    *previously_installed = handled;
+
+    displaced.sort_by_key(|displaced| {
+        signals
+            .iter()
+            .position(|&sig| sig == displaced.platform_signal())
+    });
+    Ok(displaced)
+}
 
+/// Start handling `signal` in addition to the signals passed to
+/// [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if no handler is registered or a system error occurred.
+pub unsafe fn add_signal(signal: Signal) -> Result<(), Error> {
+    let mut installed = INSTALLED.lock().unwrap();
+    if !is_handler_init() {
+        return Err(nix::Error::ENOENT);
+    }
+    if !installed.contains(&signal) {
+        let old = hook(signal)?;
+        installed.push(signal);
+        set_chained(signal, Some(&old));
+        PREVIOUS.lock().unwrap().push((signal, old));
+    }
     Ok(())
 }
 
-#[allow(dead_code)]
-pub unsafe fn deinit_os_handler() -> Result<(), Error> {
-    use nix::sys::signal;
+/// Stop handling `signal`, restoring the action in place before it was handled.
+///
+/// # Errors
+/// Will return an error if no handler is registered or a system error occurred.
+pub unsafe fn remove_signal(signal: Signal) -> Result<(), Error> {
+    let mut installed = INSTALLED.lock().unwrap();
     if !is_handler_init() {
         return Err(nix::Error::ENOENT);
     }
+    if let Some(index) = installed.iter().position(|&sig| sig == signal) {
+        let mut previous = PREVIOUS.lock().unwrap();
+        let action = take_previous(&mut previous, signal);
+        if let Err(e) = unhook(signal, &action) {
+            set_chained(signal, Some(&action));
+            previous.push((signal, action));
+            return Err(e);
+        }
+        installed.remove(index);
+    }
+    Ok(())
+}
+
+/// Remove and return the action in place before `signal` was handled, the default action if
+/// it is unknown.
+unsafe fn take_previous(previous: &mut Vec<(Signal, SigAction)>, signal: Signal) -> SigAction {
+    set_chained(signal, None);
+    match previous.iter().position(|&(sig, _)| sig == signal) {
+        Some(index) => previous.remove(index).1,
+        None => sig_handler_to_sig_action(SigHandler::SigDfl),
+    }
+}
 
-    let new_action = sig_handler_to_sig_action(signal::SigHandler::SigDfl);
+/// Undo a partial [`init_os_handler()`](fn.init_os_handler.html) that failed with `error`.
+unsafe fn roll_back(installed: &[(Signal, SigAction)], prearmed: bool, error: Error) -> CtrlcError {
+    let mut failures = Vec::new();
+    for (sig, old) in installed.iter().rev() {
+        if let Err(e) = unhook(*sig, old) {
+            failures.push(io::Error::from(e));
+        }
+    }
+    if !prearmed {
+        close_channel();
+    }
+    if failures.is_empty() {
+        error.into()
+    } else {
+        CtrlcError::Rollback(Box::new(error.into()), failures)
+    }
+}
 
-    let _ = signal::sigaction(signal::Signal::SIGINT, &new_action);
+/// Unregisters the handler set by [`init_os_handler()`](fn.init_os_handler.html), restoring the
+/// actions that were in place before it.
+#[allow(dead_code)]
+pub unsafe fn deinit_os_handler() -> Result<(), Error> {
+    let mut installed = INSTALLED.lock().unwrap();
+    if !is_handler_init() {
+        return Err(nix::Error::ENOENT);
+    }
 
-    #[cfg(feature = "termination")]
-    {
-        let _ = signal::sigaction(signal::Signal::SIGTERM, &new_action);
-        let _ = signal::sigaction(signal::Signal::SIGHUP, &new_action);
+    let mut previous = PREVIOUS.lock().unwrap();
+    for sig in installed.drain(..) {
+        let _ = unhook(sig, &take_previous(&mut previous, sig));
     }
-    close_pipe();
+    previous.clear();
+    CHAIN.store(false, Ordering::Relaxed);
+    close_channel();
 
     Ok(())
 }
 
 #[allow(dead_code)]
 pub unsafe fn is_handler_init() -> bool {
-    return PIPE.0 != -1 && PIPE.1 != -1;
+    Active::is_open()
 }
 
 unsafe fn sig_handler_to_sig_action(handler: SigHandler) -> SigAction {
@@ -207,6 +935,113 @@ unsafe fn sig_handler_to_sig_action(handler: SigHandler) -> SigAction {
     action
 }
 
+/// Pipe readable once the readers were interrupted, see [`interrupt_readers()`].
+static INTERRUPT_PIPE: Mutex<Option<(RawFd, RawFd)>> = Mutex::new(None);
+/// Whether the interrupt pipe holds a byte, until [`resume_readers()`] reads it back.
+static INTERRUPTED: Mutex<bool> = Mutex::new(false);
+
+fn interrupt_pipe() -> Result<(RawFd, RawFd), Error> {
+    let mut pipe = INTERRUPT_PIPE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(pipe) = *pipe {
+        return Ok(pipe);
+    }
+    let created = pipe2(nix::fcntl::OFlag::O_CLOEXEC)?;
+    *pipe = Some(created);
+    Ok(created)
+}
+
+/// Set up what [`interrupt_readers()`](fn.interrupt_readers.html) signals. Must be called
+/// before deciding to wait in [`wait_readable()`](fn.wait_readable.html), so an interrupt
+/// in between is not lost.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn prepare_interruptible() -> Result<(), Error> {
+    interrupt_pipe().map(|_| ())
+}
+
+/// Wake up all [`wait_readable()`](fn.wait_readable.html) calls, current and future.
+pub fn interrupt_readers() {
+    let mut interrupted = INTERRUPTED.lock().unwrap_or_else(|e| e.into_inner());
+    if *interrupted {
+        return;
+    }
+    if let Ok((_, tx)) = interrupt_pipe() {
+        *interrupted = unistd::write(unsafe { BorrowedFd::borrow_raw(tx) }, &[0u8]).is_ok();
+    }
+}
+
+/// Let [`wait_readable()`](fn.wait_readable.html) block again after
+/// [`interrupt_readers()`](fn.interrupt_readers.html).
+pub fn resume_readers() {
+    let mut interrupted = INTERRUPTED.lock().unwrap_or_else(|e| e.into_inner());
+    if !*interrupted {
+        return;
+    }
+    if let Ok((rx, _)) = interrupt_pipe() {
+        *interrupted = unistd::read(rx, &mut [0u8]).is_err();
+    }
+}
+
+/// Block until `stream` is readable, returns `false` if woken up by
+/// [`interrupt_readers()`](fn.interrupt_readers.html) instead.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn wait_readable(stream: &std::net::TcpStream) -> Result<bool, Error> {
+    use nix::libc;
+    use std::os::unix::io::AsRawFd;
+
+    let (rx, _) = interrupt_pipe()?;
+    let mut fds = [
+        libc::pollfd {
+            fd: stream.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: rx,
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } >= 0 {
+            // Errors and hang-ups of the socket are reported by the following read.
+            return Ok(fds[1].revents == 0);
+        }
+        match nix::errno::Errno::last() {
+            nix::errno::Errno::EINTR => {}
+            e => return Err(e),
+        }
+    }
+}
+
+/// Wake up [`block_ctrl_c()`](fn.block_ctrl_c.html) as if `signal` was received, or without a
+/// signal if `None`.
+///
+/// # Errors
+/// Will return an error if no handler is registered or a system error occurred.
+pub unsafe fn inject_signal(signal: Option<Signal>) -> Result<(), Error> {
+    // Keeps the channel from being closed while sending on it.
+    let _installed = INSTALLED.lock().unwrap();
+    let sig = signal.map_or(0, |signal| signal as nix::libc::c_int);
+    record_sender(sig, None);
+    Active::send(sig)
+}
+
+/// Identifies the current installation of the OS handler. Changes when it is removed, so a
+/// handler installed again later has a different generation.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// Number of signals dropped since the process started because
+/// [`block_ctrl_c()`](fn.block_ctrl_c.html) fell too far behind and the channel was full.
+pub fn missed_signals() -> u64 {
+    MISSED.load(Ordering::Relaxed)
+}
+
 /// Blocks until a Ctrl-C signal is received.
 ///
 /// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
@@ -215,25 +1050,101 @@ unsafe fn sig_handler_to_sig_action(handler: SigHandler) -> SigAction {
 /// Will return an error if a system error occurred.
 ///
 #[inline]
+#[allow(dead_code)]
 pub unsafe fn block_ctrl_c() -> Result<BlockOutcome, CtrlcError> {
-    let mut buf = [0u8];
+    block_ctrl_c_for(generation())
+}
 
-    // TODO: Can we safely convert the pipe fd into a std::io::Read
-    // with std::os::unix::io::FromRawFd, this would handle EINTR
-    // and everything for us.
-    loop {
-        let pipe = std::ptr::read_volatile(&raw const PIPE);
-        match unistd::read(pipe.0, &mut buf[..]) {
-            Ok(1) => break,
+/// Same as [`block_ctrl_c()`](fn.block_ctrl_c.html), but returns
+/// [`BlockOutcome::HandlerRemoved`] once the OS handler of `generation` was removed, even if
+/// it was installed again since. Keeps waiters of a removed handler from taking the signals of
+/// the next one.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn block_ctrl_c_for(generation: u64) -> Result<BlockOutcome, CtrlcError> {
+    Ok(Active::recv(generation, None)?.map_or(BlockOutcome::HandlerRemoved, outcome))
+}
+
+/// Same as [`block_ctrl_c()`](fn.block_ctrl_c.html), but returns [`BlockOutcome::TimedOut`]
+/// once `timeout` passed without a signal. A signal already received is still taken with a zero
+/// `timeout`.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c_timeout(timeout: Duration) -> Result<BlockOutcome, CtrlcError> {
+    block_ctrl_c_timeout_for(generation(), timeout)
+}
 
-            Ok(_) |
-            Err(nix::errno::Errno::EBADF)
-                => return Ok(BlockOutcome::HandlerRemoved),
+/// Same as [`block_ctrl_c_timeout()`](fn.block_ctrl_c_timeout.html), but for the OS handler of
+/// `generation`, see [`block_ctrl_c_for()`](fn.block_ctrl_c_for.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn block_ctrl_c_timeout_for(
+    generation: u64,
+    timeout: Duration,
+) -> Result<BlockOutcome, CtrlcError> {
+    // A deadline too far away for an `Instant` is never reached.
+    let deadline = Instant::now().checked_add(timeout);
+    match Active::recv(generation, deadline) {
+        Ok(sig) => Ok(sig.map_or(BlockOutcome::HandlerRemoved, outcome)),
+        Err(Error::ETIMEDOUT) => Ok(BlockOutcome::TimedOut),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Blocks until a Ctrl-C signal is received, then also takes the signals already received
+/// behind it without blocking.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c_all() -> Result<Vec<BlockOutcome>, CtrlcError> {
+    block_ctrl_c_all_for(generation())
+}
 
-            Err(nix::errno::Errno::EINTR) => {}
-            Err(e) => return Err(e.into()),
+/// Same as [`block_ctrl_c_all()`](fn.block_ctrl_c_all.html), but for the OS handler of
+/// `generation`, see [`block_ctrl_c_for()`](fn.block_ctrl_c_for.html). Returns the outcomes in
+/// the order taken, at most [`MAX_BATCH`], or only [`BlockOutcome::HandlerRemoved`].
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn block_ctrl_c_all_for(generation: u64) -> Result<Vec<BlockOutcome>, CtrlcError> {
+    let mut outcomes = vec![block_ctrl_c_for(generation)?];
+    if outcomes[0] == BlockOutcome::HandlerRemoved {
+        return Ok(outcomes);
+    }
+    while outcomes.len() < MAX_BATCH {
+        match Active::try_recv(generation)? {
+            Some(sig) => outcomes.push(outcome(sig)),
+            None => break,
         }
     }
+    Ok(outcomes)
+}
 
-    Ok(BlockOutcome::Awaited)
+/// A descriptor that is readable while a signal for the OS handler of `generation` waits to be
+/// taken, and once that handler was removed, `None` if it already was. Only the self-pipe and
+/// the eventfd have one, the other channels fail with `ENOTSUP`.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn notification_fd(generation: u64) -> Result<Option<OwnedFd>, CtrlcError> {
+    Ok(Active::notification_fd(generation)?)
+}
+
+/// What a signal number read by a waiter stands for, 0 for a wakeup without a signal.
+fn outcome(sig: nix::libc::c_int) -> BlockOutcome {
+    match sig {
+        0 => BlockOutcome::Awaited,
+        sig => Signal::try_from(sig).map_or(BlockOutcome::Awaited, BlockOutcome::Signal),
+    }
 }
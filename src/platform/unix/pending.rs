@@ -0,0 +1,50 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Received signals by signal number, for the channels that only count wakeups.
+//!
+//! A signal is counted before its wakeup is sent, so a waiter that got a wakeup finds it here.
+//! Signals that arrived together are taken lowest number first, not in the order they arrived.
+
+use super::MAX_SIGNAL;
+use nix::libc::c_int;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// Only used to initialize the array below.
+#[allow(clippy::declare_interior_mutable_const)]
+const NONE_PENDING: AtomicU32 = AtomicU32::new(0);
+/// Signals sent but not taken yet, by signal number. 0 counts the wakeups without a signal.
+static PENDING: [AtomicU32; MAX_SIGNAL] = [NONE_PENDING; MAX_SIGNAL];
+
+/// Count `sig`, 0 for a wakeup without a signal. Async-signal-safe.
+pub(super) fn count(sig: c_int) {
+    let index = match sig as usize {
+        index if index < MAX_SIGNAL => index,
+        _ => 0,
+    };
+    PENDING[index].fetch_add(1, Ordering::Release);
+}
+
+/// Take the lowest pending signal, 0 for a wakeup without a signal.
+pub(super) fn take() -> Option<c_int> {
+    let mut indices = (1..MAX_SIGNAL).chain(Some(0));
+    indices.find_map(|index| {
+        PENDING[index]
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .ok()
+            .map(|_| index as c_int)
+    })
+}
+
+/// Forget the signals nobody took, done when the handler is removed.
+pub(super) fn clear() {
+    for pending in PENDING.iter() {
+        pending.store(0, Ordering::Relaxed);
+    }
+}
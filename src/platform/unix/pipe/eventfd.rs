@@ -0,0 +1,97 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! `eventfd(2)` in place of the self-pipe on Linux and Android, with the `eventfd` feature.
+//!
+//! One descriptor serves as both ends of the pipe. Its counter never fills up, every received
+//! signal adds one and every read takes one with `EFD_SEMAPHORE`. The counter doesn't carry
+//! which signal was received, so they are also counted in `pending`.
+
+use super::super::{pending, poll};
+use super::Error;
+use nix::libc::{self, c_int};
+use std::os::unix::io::RawFd;
+use std::time::Instant;
+
+/// Added by [`wake_all()`], leaves the counter readable for every waiter there can be.
+const WAKE_ALL: u64 = u64::MAX / 2;
+
+/// Create the eventfd, `ENOSYS` or `EINVAL` if the kernel doesn't support it.
+pub(super) fn open() -> Result<RawFd, Error> {
+    let flags = libc::EFD_SEMAPHORE | libc::EFD_CLOEXEC | libc::EFD_NONBLOCK;
+    let fd = unsafe { libc::eventfd(0, flags) };
+    if fd == -1 {
+        return Err(Error::last());
+    }
+    Ok(fd)
+}
+
+/// Count `sig` and wake up one waiter, 0 wakes it up without a signal. Async-signal-safe.
+pub(super) fn post(fd: RawFd, sig: c_int) -> Result<(), Error> {
+    pending::count(sig);
+    add(fd, 1)
+}
+
+/// Wake up every waiter, current and future, and forget the signals nobody took. Done when the
+/// handler is removed.
+pub(super) fn wake_all(fd: RawFd) {
+    let _ = add(fd, WAKE_ALL);
+    pending::clear();
+}
+
+fn add(fd: RawFd, value: u64) -> Result<(), Error> {
+    let bytes = value.to_ne_bytes();
+    let written = unsafe { libc::write(fd, bytes.as_ptr().cast(), bytes.len()) };
+    if written == -1 {
+        return Err(Error::last());
+    }
+    Ok(())
+}
+
+/// Block until a signal is counted on `fd` and return its number, 0 for a wakeup without a
+/// signal. Returns `None` once `removed()` tells the handler of `fd` was removed, fails with
+/// `ETIMEDOUT` once `deadline` passed.
+pub(super) fn wait(
+    fd: RawFd,
+    deadline: Option<Instant>,
+    removed: impl Fn() -> bool,
+) -> Result<Option<c_int>, Error> {
+    let mut fds = [libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    }];
+    loop {
+        if removed() {
+            return Ok(None);
+        }
+        match try_take(fd, &removed)? {
+            Some(sig) => return Ok(Some(sig)),
+            None if removed() => return Ok(None),
+            None => poll(&mut fds, deadline)?,
+        }
+    }
+}
+
+/// Take a signal counted on `fd` without blocking, `None` if there is none or `removed()`.
+pub(super) fn try_take(fd: RawFd, removed: impl Fn() -> bool) -> Result<Option<c_int>, Error> {
+    // The descriptor is non-blocking, another waiter may have taken the count.
+    let mut value = [0u8; 8];
+    if unsafe { libc::read(fd, value.as_mut_ptr().cast(), value.len()) } == -1 {
+        return match Error::last() {
+            Error::EAGAIN | Error::EINTR => Ok(None),
+            e => Err(e),
+        };
+    }
+    // Every signal is counted before it is added, only `wake_all()` adds without one.
+    if removed() {
+        return Ok(None);
+    }
+    Ok(pending::take())
+}
@@ -0,0 +1,195 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The self-pipe, the default [`Channel`]. With the `eventfd` feature an eventfd takes its place
+//! on Linux and Android.
+
+use super::{pipe2, poll, Channel, Error, GENERATION, INSTALLED};
+use nix::libc::{self, c_int};
+use nix::unistd;
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::Instant;
+
+#[cfg(ctrlc_eventfd)]
+mod eventfd;
+#[cfg(ctrlc_eventfd)]
+use std::sync::atomic::AtomicBool;
+
+/// Read end of the self-pipe, -1 while no handler is installed.
+static PIPE_READ: AtomicI32 = AtomicI32::new(-1);
+/// Write end of the self-pipe, the only state the signal handler reads.
+static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+/// Whether `PIPE_READ` and `PIPE_WRITE` hold one eventfd instead of the ends of a pipe.
+#[cfg(ctrlc_eventfd)]
+static EVENTFD: AtomicBool = AtomicBool::new(false);
+
+pub(super) struct Pipe;
+
+/// Publish a new pipe, the write end last so the signal handler never sees half of it.
+fn open_pipe(pipe: (RawFd, RawFd)) {
+    PIPE_READ.store(pipe.0, Ordering::Release);
+    PIPE_WRITE.store(pipe.1, Ordering::Release);
+}
+
+/// Duplicate the read end of the pipe of `generation`, `None` if that handler was removed.
+///
+/// Waiters read from their own descriptor, so removing the handler can't close it under them,
+/// and a descriptor number reused by someone else is never read. Closing the write end still
+/// wakes them up.
+fn reader(generation: u64) -> Result<Option<OwnedFd>, Error> {
+    use nix::fcntl;
+
+    let _installed = INSTALLED.lock().unwrap();
+    let fd = PIPE_READ.load(Ordering::Acquire);
+    if fd == -1 || GENERATION.load(Ordering::Acquire) != generation {
+        return Ok(None);
+    }
+    let reader = fcntl::fcntl(fd, fcntl::FcntlArg::F_DUPFD_CLOEXEC(0))?;
+    Ok(Some(unsafe { OwnedFd::from_raw_fd(reader) }))
+}
+
+impl Channel for Pipe {
+    fn open() -> Result<(), Error> {
+        use nix::fcntl;
+
+        #[cfg(ctrlc_eventfd)]
+        match eventfd::open() {
+            Ok(fd) => {
+                EVENTFD.store(true, Ordering::Relaxed);
+                open_pipe((fd, fd));
+                return Ok(());
+            }
+            // Kernels before 2.6.30 lack eventfd or EFD_SEMAPHORE, the pipe works everywhere.
+            Err(nix::Error::ENOSYS) | Err(nix::Error::EINVAL) => {
+                EVENTFD.store(false, Ordering::Relaxed)
+            }
+            Err(e) => return Err(e),
+        }
+
+        // Make sure we never block on write in the os handler. Waiters poll the read end, so
+        // they can also take a signal without blocking.
+        open_pipe(pipe2(fcntl::OFlag::O_CLOEXEC | fcntl::OFlag::O_NONBLOCK)?);
+        Ok(())
+    }
+
+    fn close() {
+        let write = PIPE_WRITE.swap(-1, Ordering::AcqRel);
+        let read = PIPE_READ.swap(-1, Ordering::AcqRel);
+
+        // Closing an eventfd doesn't wake up the waiters reading their duplicates of it.
+        #[cfg(ctrlc_eventfd)]
+        if EVENTFD.load(Ordering::Relaxed) {
+            eventfd::wake_all(write);
+            let _ = unistd::close(write);
+            return;
+        }
+
+        // Try to close the pipes. close() should not fail,
+        // but if it does, there isn't much we can do
+        let _ = unistd::close(write);
+        let _ = unistd::close(read);
+    }
+
+    fn is_open() -> bool {
+        PIPE_READ.load(Ordering::Acquire) != -1 && PIPE_WRITE.load(Ordering::Acquire) != -1
+    }
+
+    fn send(sig: c_int) -> Result<(), Error> {
+        let fd = PIPE_WRITE.load(Ordering::Acquire);
+        if fd == -1 {
+            return Err(Error::ENOENT);
+        }
+        #[cfg(ctrlc_eventfd)]
+        if EVENTFD.load(Ordering::Relaxed) {
+            return eventfd::post(fd, sig);
+        }
+        // Signal numbers fit in a byte.
+        unistd::write(unsafe { BorrowedFd::borrow_raw(fd) }, &[sig as u8])?;
+        Ok(())
+    }
+
+    fn recv(generation: u64, deadline: Option<Instant>) -> Result<Option<c_int>, Error> {
+        let reader = match reader(generation)? {
+            Some(reader) => reader,
+            None => return Ok(None),
+        };
+
+        #[cfg(ctrlc_eventfd)]
+        if EVENTFD.load(Ordering::Relaxed) {
+            let removed = || GENERATION.load(Ordering::Acquire) != generation;
+            return eventfd::wait(reader.as_raw_fd(), deadline, removed);
+        }
+
+        loop {
+            match read(reader.as_raw_fd())? {
+                Read::Signal(sig) => return Ok(Some(sig)),
+                Read::Closed => return Ok(None),
+                Read::Empty => readable(reader.as_raw_fd(), deadline)?,
+            }
+        }
+    }
+
+    fn try_recv(generation: u64) -> Result<Option<c_int>, Error> {
+        let reader = match reader(generation)? {
+            Some(reader) => reader,
+            None => return Ok(None),
+        };
+
+        #[cfg(ctrlc_eventfd)]
+        if EVENTFD.load(Ordering::Relaxed) {
+            let removed = || GENERATION.load(Ordering::Acquire) != generation;
+            return eventfd::try_take(reader.as_raw_fd(), removed);
+        }
+
+        match read(reader.as_raw_fd())? {
+            Read::Signal(sig) => Ok(Some(sig)),
+            Read::Closed | Read::Empty => Ok(None),
+        }
+    }
+
+    fn notification_fd(generation: u64) -> Result<Option<OwnedFd>, Error> {
+        // Readable as long as a signal is left, an eventfd with `EFD_SEMAPHORE` as well.
+        reader(generation)
+    }
+}
+
+/// What a read from the self-pipe found.
+enum Read {
+    Signal(c_int),
+    /// The write end was closed, the handler was removed.
+    Closed,
+    /// Nothing to read, or another waiter was faster.
+    Empty,
+}
+
+fn read(reader: RawFd) -> Result<Read, Error> {
+    let mut buf = [0u8];
+    loop {
+        match unistd::read(reader, &mut buf[..]) {
+            Ok(1) => return Ok(Read::Signal(buf[0] as c_int)),
+            Ok(_) | Err(nix::errno::Errno::EBADF) => return Ok(Read::Closed),
+            Err(nix::errno::Errno::EAGAIN) => return Ok(Read::Empty),
+            Err(nix::errno::Errno::EINTR) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Block until `fd` is readable or its write end was closed, fails with `ETIMEDOUT` once
+/// `deadline` passed.
+fn readable(fd: RawFd, deadline: Option<Instant>) -> Result<(), Error> {
+    let mut fds = [libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    }];
+    poll(&mut fds, deadline)
+}
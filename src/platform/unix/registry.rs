@@ -0,0 +1,77 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Registration through `signal-hook-registry`, shared with tokio and other signal-hook users.
+//!
+//! The registry installs one handler per signal, calls the action in place before it and then
+//! every registered action. Actions are removed again, but the registry never puts the previous
+//! action back, so a signal that is no longer handled is ignored unless something else handles
+//! it.
+
+use super::{current_action, notify, record_sender, siginfo_sender, Error, Signal};
+use nix::libc::{self, c_int};
+use nix::sys::signal::{SigAction, SigHandler};
+use signal_hook_registry::SigId;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+static HOOKS: Mutex<Vec<(Signal, SigId)>> = Mutex::new(Vec::new());
+/// Address of the handler the registry installs, 0 until the first signal is hooked.
+static REGISTRY_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+fn lock() -> MutexGuard<'static, Vec<(Signal, SigId)>> {
+    HOOKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn address(handler: SigHandler) -> usize {
+    match handler {
+        SigHandler::Handler(handler) => handler as usize,
+        SigHandler::SigAction(handler) => handler as usize,
+        _ => 0,
+    }
+}
+
+/// Register an action for `sig` waking up the handler thread, returns the action in place
+/// before. Hooking a signal twice keeps the first registration.
+pub(super) unsafe fn hook(sig: Signal) -> Result<SigAction, Error> {
+    let mut hooks = lock();
+    let old = current_action(sig)?;
+    if hooks.iter().any(|&(hooked, _)| hooked == sig) {
+        return Ok(old);
+    }
+    // The registry panics on these.
+    if signal_hook_registry::FORBIDDEN.contains(&(sig as c_int)) {
+        return Err(Error::EINVAL);
+    }
+    let id = signal_hook_registry::register_sigaction(sig as c_int, move |info| {
+        record_sender(sig as c_int, siginfo_sender(info));
+        notify(sig as c_int)
+    })
+    .map_err(|e| Error::from_raw(e.raw_os_error().unwrap_or(libc::EINVAL)))?;
+    hooks.push((sig, id));
+    if let Ok(installed) = current_action(sig) {
+        REGISTRY_HANDLER.store(address(installed.handler()), Ordering::Relaxed);
+    }
+    Ok(old)
+}
+
+/// Unregister the action for `sig`. `previous` is not put back, see the module documentation.
+pub(super) unsafe fn unhook(sig: Signal, _previous: &SigAction) -> Result<(), Error> {
+    let mut hooks = lock();
+    if let Some(index) = hooks.iter().position(|&(hooked, _)| hooked == sig) {
+        signal_hook_registry::unregister(hooks.remove(index).1);
+    }
+    Ok(())
+}
+
+/// Whether `handler` is the one installed by the registry, shared with other registry users.
+pub(super) fn is_ours(_sig: Signal, handler: SigHandler) -> bool {
+    let registry = REGISTRY_HANDLER.load(Ordering::Relaxed);
+    registry != 0 && address(handler) == registry
+}
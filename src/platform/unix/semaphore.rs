@@ -0,0 +1,152 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! A POSIX semaphore in place of the self-pipe, with the `posix-semaphore` feature.
+//!
+//! `sem_post()` is async-signal-safe and takes no file descriptor. The semaphore only counts
+//! wakeups, so the signals are counted in `pending`. A semaphore can't be destroyed while threads
+//! wait on it, so there is only one, initialized on first use and kept for the next handlers.
+
+use super::{pending, Channel, Error, GENERATION};
+use nix::libc::{self, c_int};
+use std::cell::UnsafeCell;
+use std::mem::{self, MaybeUninit};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+struct Sem(UnsafeCell<MaybeUninit<libc::sem_t>>);
+
+// Only ever used through the `sem_*()` functions, which synchronize.
+unsafe impl Sync for Sem {}
+
+static SEM: Sem = Sem(UnsafeCell::new(MaybeUninit::uninit()));
+/// Whether `SEM` was initialized, it never goes back.
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// Whether a handler is installed.
+static OPEN: AtomicBool = AtomicBool::new(false);
+/// Threads waiting on `SEM` or about to, each is woken up once when the handler is removed.
+static WAITERS: AtomicUsize = AtomicUsize::new(0);
+/// How often a waiter checks whether its handler was removed. The wakeup posted for it by
+/// `close()` may be taken by a waiter of the next handler.
+const POLL: Duration = Duration::from_millis(100);
+
+pub(super) struct Semaphore;
+
+fn sem() -> *mut libc::sem_t {
+    SEM.0.get().cast()
+}
+
+impl Channel for Semaphore {
+    fn open() -> Result<(), Error> {
+        if !INITIALIZED.load(Ordering::Acquire) {
+            if unsafe { libc::sem_init(sem(), 0, 0) } == -1 {
+                return Err(Error::last());
+            }
+            INITIALIZED.store(true, Ordering::Release);
+        }
+        OPEN.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    fn close() {
+        OPEN.store(false, Ordering::Release);
+        pending::clear();
+        // Waiters registered after this see the new generation instead. Wakeups left over
+        // are taken for spurious ones by the next waiters, the waiters missing theirs notice
+        // within `POLL`.
+        for _ in 0..WAITERS.load(Ordering::SeqCst) {
+            unsafe { libc::sem_post(sem()) };
+        }
+    }
+
+    fn is_open() -> bool {
+        OPEN.load(Ordering::Acquire)
+    }
+
+    fn send(sig: c_int) -> Result<(), Error> {
+        if !OPEN.load(Ordering::Acquire) {
+            return Err(Error::ENOENT);
+        }
+        pending::count(sig);
+        if unsafe { libc::sem_post(sem()) } == -1 {
+            return Err(Error::last());
+        }
+        Ok(())
+    }
+
+    fn recv(generation: u64, deadline: Option<Instant>) -> Result<Option<c_int>, Error> {
+        loop {
+            let poll = Instant::now() + POLL;
+            let abstime = abstime(deadline.map_or(poll, |deadline| deadline.min(poll)));
+            match take(generation, |sem| unsafe {
+                libc::sem_timedwait(sem, &abstime)
+            }) {
+                Err(Error::ETIMEDOUT)
+                    if deadline.map_or(true, |deadline| Instant::now() < deadline) => {}
+                taken => return taken,
+            }
+        }
+    }
+
+    fn try_recv(generation: u64) -> Result<Option<c_int>, Error> {
+        take(generation, |sem| unsafe { libc::sem_trywait(sem) })
+    }
+}
+
+/// `deadline` on the `CLOCK_REALTIME` clock of `sem_timedwait()`.
+fn abstime(deadline: Instant) -> libc::timespec {
+    let left = deadline.saturating_duration_since(Instant::now());
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        + left;
+    let mut abstime: libc::timespec = unsafe { mem::zeroed() };
+    abstime.tv_sec = at.as_secs().min(libc::time_t::MAX as u64) as libc::time_t;
+    abstime.tv_nsec = at.subsec_nanos() as _;
+    abstime
+}
+
+/// Take a wakeup from `SEM` as one of the `WAITERS`, a concurrent `close()` wakes it up too.
+fn take(generation: u64, wait: impl Fn(*mut libc::sem_t) -> c_int) -> Result<Option<c_int>, Error> {
+    WAITERS.fetch_add(1, Ordering::SeqCst);
+    let taken = take_registered(generation, wait);
+    WAITERS.fetch_sub(1, Ordering::SeqCst);
+    taken
+}
+
+/// Take a wakeup from `SEM` with `sem_wait()`, `sem_timedwait()` or `sem_trywait()`, skipping
+/// those left over from removed handlers.
+fn take_registered(
+    generation: u64,
+    wait: impl Fn(*mut libc::sem_t) -> c_int,
+) -> Result<Option<c_int>, Error> {
+    // `SEM` stays initialized once a handler was installed.
+    let removed =
+        || !OPEN.load(Ordering::Acquire) || GENERATION.load(Ordering::SeqCst) != generation;
+    loop {
+        if removed() {
+            return Ok(None);
+        }
+        if wait(sem()) == -1 {
+            match Error::last() {
+                Error::EINTR => continue,
+                Error::EAGAIN => return Ok(None),
+                e => return Err(e),
+            }
+        }
+        if removed() {
+            // The wakeup may have been posted for a waiter of the current handler, pass it on.
+            unsafe { libc::sem_post(sem()) };
+            return Ok(None);
+        }
+        if let Some(sig) = pending::take() {
+            return Ok(Some(sig));
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! `signalfd(2)` in place of a signal handler and the self-pipe, with the `signalfd` feature on
+//! Linux and Android.
+//!
+//! The handled signals are blocked and read from a signalfd, so no code runs in signal context
+//! and the actions in place are neither replaced nor restored. See `mask` for what blocking
+//! affects.
+//!
+//! Injected wakeups and the removal of the handler go through `control`.
+
+use super::mask::{self, sigset};
+use super::{control, current_action, record_sender, sent_by_process, Channel, Error, Signal};
+use nix::libc::{self, c_int};
+use nix::sys::signal::{SigAction, SigHandler};
+use nix::unistd;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+/// The signalfd, -1 while no handler is installed.
+static SIGNALFD: AtomicI32 = AtomicI32::new(-1);
+/// Signals blocked by [`hook()`], the mask of the signalfd.
+static BLOCKED: Mutex<Vec<Signal>> = Mutex::new(Vec::new());
+
+pub(super) struct Signalfd;
+
+fn blocked() -> MutexGuard<'static, Vec<Signal>> {
+    BLOCKED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Create the signalfd for `blocked`, or change the signals `fd` reads if it isn't -1.
+fn signalfd(fd: RawFd, blocked: &[Signal]) -> Result<RawFd, Error> {
+    let fd = unsafe {
+        libc::signalfd(
+            fd,
+            sigset(blocked).as_ref(),
+            libc::SFD_CLOEXEC | libc::SFD_NONBLOCK,
+        )
+    };
+    if fd == -1 {
+        return Err(Error::last());
+    }
+    Ok(fd)
+}
+
+fn update(blocked: &[Signal]) -> Result<(), Error> {
+    let fd = SIGNALFD.load(Ordering::Acquire);
+    if fd != -1 {
+        signalfd(fd, blocked)?;
+    }
+    Ok(())
+}
+
+/// Block `sig` in the calling thread and read it from the signalfd, returns the action in
+/// place, which is left alone.
+pub(super) unsafe fn hook(sig: Signal) -> Result<SigAction, Error> {
+    let old = current_action(sig)?;
+    let mut blocked = blocked();
+    if blocked.contains(&sig) {
+        return Ok(old);
+    }
+    mask::block(sig)?;
+    blocked.push(sig);
+    if let Err(e) = update(&blocked) {
+        blocked.pop();
+        let _ = mask::unblock(sig);
+        return Err(e);
+    }
+    Ok(old)
+}
+
+/// Stop reading `sig` and unblock it in the calling thread. The action was never replaced, so
+/// `previous` isn't put back.
+pub(super) unsafe fn unhook(sig: Signal, _previous: &SigAction) -> Result<(), Error> {
+    let mut blocked = blocked();
+    let index = match blocked.iter().position(|&blocked| blocked == sig) {
+        Some(index) => index,
+        None => return Ok(()),
+    };
+    blocked.remove(index);
+    update(&blocked)?;
+    mask::unblock(sig)
+}
+
+/// Whether `handler` is the one installed by [`hook()`], which installs none.
+pub(super) fn is_ours(_sig: Signal, _handler: SigHandler) -> bool {
+    false
+}
+
+/// Read a signal from `fd`, `None` if there is none.
+fn read(fd: RawFd) -> Result<Option<c_int>, Error> {
+    let mut info: libc::signalfd_siginfo = unsafe { mem::zeroed() };
+    let size = mem::size_of::<libc::signalfd_siginfo>();
+    let read = unsafe { libc::read(fd, (&mut info as *mut libc::signalfd_siginfo).cast(), size) };
+    if read != -1 {
+        let sig = info.ssi_signo as c_int;
+        let sent = sent_by_process(info.ssi_code) && info.ssi_pid > 0;
+        record_sender(sig, sent.then_some((info.ssi_pid, info.ssi_uid)));
+        return Ok(Some(sig));
+    }
+    match Error::last() {
+        Error::EAGAIN | Error::EINTR => Ok(None),
+        e => Err(e),
+    }
+}
+
+impl Channel for Signalfd {
+    fn open() -> Result<(), Error> {
+        control::open()?;
+        match signalfd(-1, &blocked()) {
+            Ok(fd) => SIGNALFD.store(fd, Ordering::Release),
+            Err(e) => {
+                control::close();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn close() {
+        let _ = unistd::close(SIGNALFD.swap(-1, Ordering::AcqRel));
+        control::close();
+    }
+
+    fn is_open() -> bool {
+        SIGNALFD.load(Ordering::Acquire) != -1
+    }
+
+    fn send(sig: c_int) -> Result<(), Error> {
+        control::send(sig)
+    }
+
+    fn recv(generation: u64, deadline: Option<Instant>) -> Result<Option<c_int>, Error> {
+        control::wait(generation, deadline, &SIGNALFD, read)
+    }
+
+    fn try_recv(generation: u64) -> Result<Option<c_int>, Error> {
+        control::try_take(generation, &SIGNALFD, read)
+    }
+}
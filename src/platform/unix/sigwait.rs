@@ -0,0 +1,132 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! A dispatcher thread taking the handled signals with `sigtimedwait(2)` in place of a signal
+//! handler, with the `sigwait` feature on Linux, Android, FreeBSD, DragonFly and NetBSD.
+//!
+//! The handled signals are blocked and the dispatcher wakes up
+//! [`block_ctrl_c()`](super::block_ctrl_c) through the channel like a signal handler would, so
+//! no code runs in signal context and the actions in place are neither replaced nor restored.
+//! See `mask` for what blocking affects. The dispatcher is started with the first handled signal
+//! and idles while none is handled.
+
+use super::mask::{self, sigset};
+use super::{current_action, notify, record_sender, siginfo_sender, Error, Signal};
+use nix::libc::{self, c_int};
+use nix::sys::signal::{pthread_sigmask, SigAction, SigHandler, SigSet, SigmaskHow};
+use std::mem;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+
+struct Dispatcher {
+    /// Signals blocked by [`hook()`], the ones the dispatcher waits for.
+    signals: Vec<Signal>,
+    started: bool,
+}
+
+static DISPATCHER: Mutex<Dispatcher> = Mutex::new(Dispatcher {
+    signals: Vec::new(),
+    started: false,
+});
+/// Notified when `signals` changes.
+static CHANGED: Condvar = Condvar::new();
+
+/// How long the dispatcher waits before picking up a change to `signals`. Signals handled
+/// meanwhile stay pending, the dispatcher blocks all signals.
+const RELOAD: libc::timespec = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 100_000_000,
+};
+
+fn dispatcher() -> MutexGuard<'static, Dispatcher> {
+    DISPATCHER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Runs with all signals blocked, the other threads get the signals not waited for.
+fn dispatch() {
+    let mut dispatcher = dispatcher();
+    loop {
+        if dispatcher.signals.is_empty() {
+            dispatcher = CHANGED.wait(dispatcher).unwrap_or_else(|e| e.into_inner());
+            continue;
+        }
+        let set = sigset(&dispatcher.signals);
+        drop(dispatcher);
+
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        let sig = unsafe { libc::sigtimedwait(set.as_ref(), &mut info, &RELOAD) };
+        dispatcher = self::dispatcher();
+        if sig <= 0 {
+            continue;
+        }
+        if dispatcher
+            .signals
+            .iter()
+            .any(|&signal| signal as c_int == sig)
+        {
+            record_sender(sig, siginfo_sender(&info));
+            notify(sig);
+        } else {
+            // No longer handled, send it again for the action in place.
+            unsafe { libc::kill(libc::getpid(), sig) };
+        }
+    }
+}
+
+/// Start the dispatcher with all signals blocked, threads start with the mask of their parent.
+fn spawn() -> Result<(), Error> {
+    let mut mask = SigSet::empty();
+    pthread_sigmask(
+        SigmaskHow::SIG_SETMASK,
+        Some(&SigSet::all()),
+        Some(&mut mask),
+    )?;
+    let spawned = thread::Builder::new()
+        .name("ctrl-c-sigwait".into())
+        .spawn(dispatch);
+    pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(&mask), None)?;
+    spawned.map_err(|e| Error::from_raw(e.raw_os_error().unwrap_or(libc::EAGAIN)))?;
+    Ok(())
+}
+
+/// Block `sig` in the calling thread and leave it to the dispatcher, returns the action in
+/// place, which is left alone.
+pub(super) unsafe fn hook(sig: Signal) -> Result<SigAction, Error> {
+    let old = current_action(sig)?;
+    let mut dispatcher = dispatcher();
+    if dispatcher.signals.contains(&sig) {
+        return Ok(old);
+    }
+    if !dispatcher.started {
+        spawn()?;
+        dispatcher.started = true;
+    }
+    mask::block(sig)?;
+    dispatcher.signals.push(sig);
+    CHANGED.notify_all();
+    Ok(old)
+}
+
+/// Stop dispatching `sig` and unblock it in the calling thread. The action was never replaced,
+/// so `previous` isn't put back.
+pub(super) unsafe fn unhook(sig: Signal, _previous: &SigAction) -> Result<(), Error> {
+    let mut dispatcher = dispatcher();
+    let index = match dispatcher.signals.iter().position(|&signal| signal == sig) {
+        Some(index) => index,
+        None => return Ok(()),
+    };
+    dispatcher.signals.remove(index);
+    CHANGED.notify_all();
+    mask::unblock(sig)
+}
+
+/// Whether `handler` is the one installed by [`hook()`], which installs none.
+pub(super) fn is_ours(_sig: Signal, _handler: SigHandler) -> bool {
+    false
+}
@@ -7,17 +7,42 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::collections::VecDeque;
+use std::ffi::c_void;
 use std::io;
 use std::io::ErrorKind;
+use std::os::windows::io::{AsRawHandle, BorrowedHandle, OwnedHandle};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
-use windows_sys::Win32::Foundation::{CloseHandle, BOOL, HANDLE, WAIT_FAILED, WAIT_OBJECT_0};
-use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+use windows_sys::Win32::Foundation::{
+    CloseHandle, BOOL, HANDLE, INVALID_HANDLE_VALUE, STATUS_CONTROL_C_EXIT, WAIT_FAILED,
+    WAIT_OBJECT_0, WAIT_TIMEOUT,
+};
+use windows_sys::Win32::Networking::WinSock::{
+    ioctlsocket, WSACloseEvent, WSACreateEvent, WSAEventSelect, FD_CLOSE, FD_READ, FIONBIO, SOCKET,
+};
+use windows_sys::Win32::Storage::FileSystem::{
+    FileNameInfo, GetFileInformationByHandleEx, GetFileType, FILE_NAME_INFO, FILE_TYPE_PIPE,
+};
+use windows_sys::Win32::System::Console::{
+    AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, GetConsoleMode, GetConsoleProcessList,
+    GetConsoleWindow, GetStdHandle, SetConsoleCtrlHandler, SetConsoleMode, ATTACH_PARENT_PROCESS,
+    CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT,
+    ENABLE_PROCESSED_INPUT, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+};
+use windows_sys::Win32::System::Diagnostics::Debug::IsDebuggerPresent;
 use windows_sys::Win32::System::Threading::{
-    CreateSemaphoreA, ReleaseSemaphore, WaitForSingleObject, INFINITE,
+    CreateEventW, CreateSemaphoreA, GetCurrentProcess, GetCurrentThread, ReleaseSemaphore,
+    ResetEvent, SetEvent, SetThreadAffinityMask, SetThreadPriority, TerminateProcess,
+    WaitForMultipleObjects, INFINITE, THREAD_PRIORITY_ABOVE_NORMAL, THREAD_PRIORITY_HIGHEST,
+    THREAD_PRIORITY_NORMAL,
 };
 
-use crate::block_outcome::BlockOutcome;
+use crate::block_outcome::{BlockOutcome, MAX_BATCH};
+use crate::{Displaced, InstallPolicy, PreviousAction, SignalType, ThreadPriority};
 
 /// Platform specific error type
 pub type Error = io::Error;
@@ -29,18 +54,48 @@ const MAX_SEM_COUNT: i32 = 65535;
 const TRUE: BOOL = 1;
 const FALSE: BOOL = 0;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct OsHandler {
-    semaphore: HANDLE,
-}
+/// Semaphore counting the received events, null while no handler is installed.
+static SEMAPHORE: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+/// Manual-reset event set when the handler is removed, wakes up the waiters.
+static REMOVED: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+/// Serializes installing and removing the handler with waiters duplicating its handles.
+static INSTALL: Mutex<()> = Mutex::new(());
+
+/// Bitmask of the control events we handle, indexed by event number.
+static WATCHED: AtomicU32 = AtomicU32::new(0);
+/// Bitmask of the control events setting the flag of `set_flag()`.
+static FLAGGED: AtomicU32 = AtomicU32::new(0);
+/// Whether the next handler routine runs after ours, see [`InstallPolicy::Chain`].
+static CHAIN: AtomicBool = AtomicBool::new(false);
+static RECEIVED: Mutex<VecDeque<Option<Signal>>> = Mutex::new(VecDeque::new());
+/// Changes with every [`deinit_os_handler()`](fn.deinit_os_handler.html), see
+/// [`generation()`](fn.generation.html).
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Control events dropped because the semaphore was at its maximum count, see
+/// [`missed_signals()`](fn.missed_signals.html).
+static MISSED: AtomicU64 = AtomicU64::new(0);
 
-static mut HANDLER: Option<OsHandler> = None;
+unsafe extern "system" fn os_handler(ctrl_type: u32) -> BOOL {
+    if !is_watched(ctrl_type) {
+        // Let the next handler in the chain (or the default one) deal with it.
+        return FALSE;
+    }
 
-unsafe extern "system" fn os_handler(_: u32) -> BOOL {
-    if let Some(handler) = HANDLER {
-        // Assuming this always succeeds. Can't really handle errors in any meaningful way.
-        ReleaseSemaphore(handler.semaphore, 1, ptr::null_mut());
-        TRUE
+    let semaphore = semaphore();
+    if !semaphore.is_null() {
+        let mut received = received();
+        received.push_back(Some(ctrl_type));
+        // The semaphore is at its maximum count when nobody waits during an event storm.
+        if ReleaseSemaphore(semaphore, 1, ptr::null_mut()) == FALSE {
+            received.pop_back();
+            MISSED.fetch_add(1, Ordering::Relaxed);
+        }
+        drop(received);
+        if CHAIN.load(Ordering::Relaxed) {
+            FALSE
+        } else {
+            TRUE
+        }
     } else {
         // We have no handler set. Not sure how the hell this function was even called then.
         // But okay, just mark this as not handled (FALSE).
@@ -48,28 +103,254 @@ unsafe extern "system" fn os_handler(_: u32) -> BOOL {
     }
 }
 
-/// Register OS signal handler.
+/// Handler routine of [`init_flag_handler()`].
+unsafe extern "system" fn flag_handler(ctrl_type: u32) -> BOOL {
+    if ctrl_type >= 32 || FLAGGED.load(Ordering::Relaxed) & (1 << ctrl_type) == 0 {
+        return FALSE;
+    }
+    crate::flag::record_signal();
+    TRUE
+}
+
+/// Wakeups not yet seen by [`block_ctrl_c()`](fn.block_ctrl_c.html), `None` for injected ones.
+/// Console control handlers run on their own thread, so a lock is fine here.
+fn install_lock() -> MutexGuard<'static, ()> {
+    INSTALL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn semaphore() -> HANDLE {
+    SEMAPHORE.load(Ordering::Acquire)
+}
+
+fn received() -> MutexGuard<'static, VecDeque<Option<Signal>>> {
+    RECEIVED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn mask(signals: &[Signal]) -> u32 {
+    signals
+        .iter()
+        .filter(|&&s| s < 32)
+        .fold(0, |mask, &s| mask | (1 << s))
+}
+
+fn set_watched(signals: &[Signal]) {
+    WATCHED.store(mask(signals), Ordering::Relaxed);
+}
+
+fn is_watched(ctrl_type: u32) -> bool {
+    ctrl_type < 32 && WATCHED.load(Ordering::Relaxed) & (1 << ctrl_type) != 0
+}
+
+/// Signals handled when no explicit signal set is requested: all console control events.
+pub fn default_signals() -> Vec<Signal> {
+    vec![
+        CTRL_C_EVENT,
+        CTRL_BREAK_EVENT,
+        CTRL_CLOSE_EVENT,
+        CTRL_LOGOFF_EVENT,
+        CTRL_SHUTDOWN_EVENT,
+    ]
+}
+
+/// Platform signals corresponding to a [`SignalType`](crate::SignalType).
+pub fn signals_of(kind: &SignalType) -> Vec<Signal> {
+    match *kind {
+        SignalType::Ctrlc => vec![CTRL_C_EVENT, CTRL_BREAK_EVENT],
+        SignalType::Termination => vec![CTRL_CLOSE_EVENT],
+        SignalType::Other(signal) => from_signal(signal).into_iter().collect(),
+    }
+}
+
+/// The [`crate::Signal`] for a console control event.
+pub fn to_signal(signal: Signal) -> crate::Signal {
+    match signal {
+        CTRL_C_EVENT => crate::Signal::CtrlC,
+        CTRL_BREAK_EVENT => crate::Signal::Break,
+        CTRL_CLOSE_EVENT => crate::Signal::Terminate,
+        CTRL_LOGOFF_EVENT => crate::Signal::Logoff,
+        CTRL_SHUTDOWN_EVENT => crate::Signal::Shutdown,
+        signal => crate::Signal::Other(signal as i32),
+    }
+}
+
+/// The console control event for a [`crate::Signal`], `None` if there is none.
+pub fn from_signal(signal: crate::Signal) -> Option<Signal> {
+    match signal {
+        crate::Signal::CtrlC => Some(CTRL_C_EVENT),
+        crate::Signal::Break => Some(CTRL_BREAK_EVENT),
+        crate::Signal::Terminate => Some(CTRL_CLOSE_EVENT),
+        crate::Signal::Logoff => Some(CTRL_LOGOFF_EVENT),
+        crate::Signal::Shutdown => Some(CTRL_SHUTDOWN_EVENT),
+        crate::Signal::Hangup | crate::Signal::User1 | crate::Signal::User2 => None,
+        crate::Signal::Other(signal) => Signal::try_from(signal).ok(),
+    }
+}
+
+/// Parse a control event name such as `C`, `BREAK` or `CTRL_CLOSE_EVENT`, or its number.
+///
+/// The Unix names `INT` and `TERM` are accepted as aliases of `C` and `CLOSE`.
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    let name = name.trim().to_ascii_uppercase();
+    let name = name.strip_prefix("CTRL_").unwrap_or(&name);
+    let name = name.strip_suffix("_EVENT").unwrap_or(name);
+    match name {
+        "C" | "INT" | "SIGINT" => Some(CTRL_C_EVENT),
+        "BREAK" => Some(CTRL_BREAK_EVENT),
+        "CLOSE" | "TERM" | "SIGTERM" => Some(CTRL_CLOSE_EVENT),
+        "LOGOFF" => Some(CTRL_LOGOFF_EVENT),
+        "SHUTDOWN" => Some(CTRL_SHUTDOWN_EVENT),
+        _ => name.parse().ok(),
+    }
+}
+
+/// Every console control event a handler can be installed for.
+pub fn supported_signals() -> Vec<Signal> {
+    default_signals()
+}
+
+/// Whether `signal` asks the process to terminate, true for all console control events.
+pub fn is_termination(signal: Signal) -> bool {
+    default_signals().contains(&signal)
+}
+
+/// Exit status of a Unix shell for the C runtime signal `signal` is delivered as, `SIGINT` for
+/// `CTRL_C_EVENT`, `SIGBREAK` for `CTRL_BREAK_EVENT` and `SIGTERM` for the others.
+pub fn exit_code(signal: Signal) -> u8 {
+    match signal {
+        CTRL_C_EVENT => 128 + 2,
+        CTRL_BREAK_EVENT => 128 + 21,
+        _ => 128 + 15,
+    }
+}
+
+/// There is no resize signal on this platform.
+pub fn window_size(_signal: Signal) -> Option<(u16, u16)> {
+    None
+}
+
+/// The sender of a signal is not known on this platform.
+pub fn sender(_signal: Signal) -> Option<(u32, u32)> {
+    None
+}
+
+/// Process names can't be looked up on this platform.
+pub fn process_name(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Name of `signal`, accepted by [`parse_signal()`](fn.parse_signal.html).
+pub fn signal_name(signal: Signal) -> String {
+    match signal {
+        CTRL_C_EVENT => "CTRL_C_EVENT".to_string(),
+        CTRL_BREAK_EVENT => "CTRL_BREAK_EVENT".to_string(),
+        CTRL_CLOSE_EVENT => "CTRL_CLOSE_EVENT".to_string(),
+        CTRL_LOGOFF_EVENT => "CTRL_LOGOFF_EVENT".to_string(),
+        CTRL_SHUTDOWN_EVENT => "CTRL_SHUTDOWN_EVENT".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Terminate the process without running `atexit` handlers or destructors.
+pub fn exit_immediately(code: i32) -> ! {
+    unsafe {
+        TerminateProcess(GetCurrentProcess(), code as u32);
+    }
+    // TerminateProcess does not return for the current process.
+    std::process::abort()
+}
+
+/// Terminate the process like the default handler routine does for control events, with
+/// `STATUS_CONTROL_C_EXIT`.
+pub fn exit_with_signal(_signal: Signal) -> ! {
+    exit_immediately(STATUS_CONTROL_C_EXIT)
+}
+
+/// Set the priority of the current thread.
+pub fn set_thread_priority(priority: ThreadPriority) -> io::Result<()> {
+    let priority = match priority {
+        ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+        ThreadPriority::AboveNormal => THREAD_PRIORITY_ABOVE_NORMAL,
+        ThreadPriority::Highest => THREAD_PRIORITY_HIGHEST,
+    };
+    if unsafe { SetThreadPriority(GetCurrentThread(), priority) } == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Restrict the current thread to `cpus` of its processor group.
+pub fn set_thread_affinity(cpus: &[usize]) -> io::Result<()> {
+    let mut mask = 0usize;
+    for &cpu in cpus {
+        if cpu >= usize::BITS as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "CPU index out of range",
+            ));
+        }
+        mask |= 1 << cpu;
+    }
+    if unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Signals sent when the controlling terminal goes away. Closing the console can't be ignored
+/// on Windows, so there are none.
+pub fn hangup_signals() -> Vec<Signal> {
+    Vec::new()
+}
+
+/// Does nothing, see [`hangup_signals()`](fn.hangup_signals.html).
+pub unsafe fn ignore_hangup() -> Result<(), Error> {
+    Ok(())
+}
+
+/// Whether a user mode debugger is attached to the process.
+pub fn is_debugger_attached() -> bool {
+    unsafe { IsDebuggerPresent() != FALSE }
+}
+
+/// Register OS signal handler for `signals`.
 ///
 /// Must be called before calling [`block_ctrl_c()`](fn.block_ctrl_c.html)
-/// and should only be called once.
+/// and should only be called once, except to replace a handler installed by
+/// [`prearm()`](../../fn.prearm.html). In that case the events already received are kept.
+///
+/// The handler routines registered before can't be listed, so every signal is reported as
+/// replaced with an unknown previous action.
 ///
 /// # Errors
 /// Will return an error if a system error occurred.
 #[inline]
-pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
+pub unsafe fn init_os_handler(
+    signals: &[Signal],
+    policy: InstallPolicy,
+) -> Result<Vec<Displaced>, Error> {
+    let _install = install_lock();
+    // Handler routines never replace each other, chaining is the only policy that matters.
+    CHAIN.store(policy == InstallPolicy::Chain, Ordering::Relaxed);
+    let displaced = signals
+        .iter()
+        .map(|&signal| Displaced::new(signal, PreviousAction::Unknown, true))
+        .collect();
     if is_handler_init() {
-        if !overwrite {
-            return Err(ErrorKind::AlreadyExists.into())
-        } else {
-            deinit_os_handler()?;
-        }
+        // Installed by `prearm()`, keep the semaphore and the events already counted in it.
+        set_watched(signals);
+        return Ok(displaced);
     }
-    assert!(!is_handler_init());
 
     let semaphore = CreateSemaphoreA(ptr::null_mut(), 0, MAX_SEM_COUNT, ptr::null());
     if semaphore.is_null() {
         return Err(io::Error::last_os_error());
     }
+    let removed = CreateEventW(ptr::null(), TRUE, FALSE, ptr::null());
+    if removed.is_null() {
+        let e = io::Error::last_os_error();
+        CloseHandle(semaphore);
+        return Err(e);
+    }
 
     
     // Remove OUR handlers if those exist
@@ -90,33 +371,324 @@ pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
     // Set our custom handler
     if SetConsoleCtrlHandler(Some(os_handler), TRUE) == FALSE {
         let e = io::Error::last_os_error();
+        CloseHandle(removed);
         CloseHandle(semaphore);
         return Err(e);
     }
 
-    HANDLER = Some(OsHandler { semaphore });
+    REMOVED.store(removed, Ordering::Release);
+    SEMAPHORE.store(semaphore, Ordering::Release);
+    set_watched(signals);
+
+    Ok(displaced)
+}
 
+/// Add a handler routine for `signals` that only sets the flag of `set_flag()`, without the
+/// semaphore.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn init_flag_handler(signals: &[Signal]) -> Result<(), Error> {
+    FLAGGED.store(mask(signals), Ordering::Relaxed);
+    if SetConsoleCtrlHandler(Some(flag_handler), TRUE) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Start handling `signal` in addition to the signals passed to
+/// [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if no handler is registered or `signal` is not a control event.
+pub unsafe fn add_signal(signal: Signal) -> Result<(), Error> {
+    let bit = watched_bit(signal)?;
+    WATCHED.fetch_or(bit, Ordering::Relaxed);
     Ok(())
 }
 
+/// Stop handling `signal`, leaving it to the next handler in the chain.
+///
+/// # Errors
+/// Will return an error if no handler is registered or `signal` is not a control event.
+pub unsafe fn remove_signal(signal: Signal) -> Result<(), Error> {
+    let bit = watched_bit(signal)?;
+    WATCHED.fetch_and(!bit, Ordering::Relaxed);
+    Ok(())
+}
+
+unsafe fn watched_bit(signal: Signal) -> Result<u32, Error> {
+    if !is_handler_init() {
+        return Err(ErrorKind::NotFound.into());
+    }
+    if signal >= 32 {
+        return Err(ErrorKind::InvalidInput.into());
+    }
+    Ok(1 << signal)
+}
+
+/// Manual-reset event set once the readers were interrupted, see [`interrupt_readers()`].
+static INTERRUPT_EVENT: Mutex<Option<usize>> = Mutex::new(None);
+
+fn interrupt_event() -> Result<HANDLE, Error> {
+    let mut event = INTERRUPT_EVENT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(event) = *event {
+        return Ok(event as HANDLE);
+    }
+    let created = unsafe { CreateEventW(ptr::null(), TRUE, FALSE, ptr::null()) };
+    if created.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    *event = Some(created as usize);
+    Ok(created)
+}
+
+/// Set up what [`interrupt_readers()`](fn.interrupt_readers.html) signals. Must be called
+/// before deciding to wait in [`wait_readable()`](fn.wait_readable.html), so an interrupt
+/// in between is not lost.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn prepare_interruptible() -> Result<(), Error> {
+    interrupt_event().map(|_| ())
+}
+
+/// Wake up all [`wait_readable()`](fn.wait_readable.html) calls, current and future.
+pub fn interrupt_readers() {
+    if let Ok(event) = interrupt_event() {
+        unsafe { SetEvent(event) };
+    }
+}
+
+/// Let [`wait_readable()`](fn.wait_readable.html) block again after
+/// [`interrupt_readers()`](fn.interrupt_readers.html).
+pub fn resume_readers() {
+    if let Ok(event) = interrupt_event() {
+        unsafe { ResetEvent(event) };
+    }
+}
+
+/// Block until `stream` is readable, returns `false` if woken up by
+/// [`interrupt_readers()`](fn.interrupt_readers.html) instead.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn wait_readable(stream: &std::net::TcpStream) -> Result<bool, Error> {
+    use std::os::windows::io::AsRawSocket;
+
+    let interrupt = interrupt_event()?;
+    let socket = stream.as_raw_socket() as SOCKET;
+    unsafe {
+        let readable = WSACreateEvent();
+        if readable == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Recorded right away if data is already pending.
+        if WSAEventSelect(socket, readable, (FD_READ | FD_CLOSE) as i32) != 0 {
+            let e = io::Error::last_os_error();
+            WSACloseEvent(readable);
+            return Err(e);
+        }
+        let handles = [readable as HANDLE, interrupt];
+        let ret = WaitForMultipleObjects(handles.len() as u32, handles.as_ptr(), FALSE, INFINITE);
+        let e = io::Error::last_os_error();
+
+        // WSAEventSelect made the socket non-blocking, restore it.
+        let mut nonblocking = 0u32;
+        WSAEventSelect(socket, 0, 0);
+        ioctlsocket(socket, FIONBIO, &mut nonblocking);
+        WSACloseEvent(readable);
+
+        match ret {
+            WAIT_OBJECT_0 => Ok(true),
+            ret if ret == WAIT_OBJECT_0 + 1 => Ok(false),
+            _ => Err(e),
+        }
+    }
+}
+
 /// Unregisters OS signal handler set by [`ctrlc::platform::init_os_handler`].
 #[inline]
 pub unsafe fn deinit_os_handler() -> Result<(), Error> {
-    if let Some(handler) = HANDLER {
-        HANDLER = None;
-        CloseHandle(handler.semaphore);
-        SetConsoleCtrlHandler(Some(os_handler), FALSE); // Remove the handler callback
-        Ok(())
+    let _install = install_lock();
+    let semaphore = SEMAPHORE.swap(ptr::null_mut(), Ordering::AcqRel);
+    if semaphore.is_null() {
+        return Err(ErrorKind::NotFound.into());
+    }
+    WATCHED.store(0, Ordering::Relaxed);
+    CHAIN.store(false, Ordering::Relaxed);
+    received().clear();
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    // Waiters hold their own handles, closing ours doesn't affect them.
+    let removed = REMOVED.swap(ptr::null_mut(), Ordering::AcqRel);
+    SetEvent(removed);
+    CloseHandle(removed);
+    CloseHandle(semaphore);
+    SetConsoleCtrlHandler(Some(os_handler), FALSE); // Remove the handler callback
+    Ok(())
+}
+
+/// Make the process ignore (`ignore == true`) or process Ctrl-C again, see
+/// `SetConsoleCtrlHandler(NULL, ...)`.
+pub unsafe fn set_ignore_ctrl_c(ignore: bool) -> Result<(), Error> {
+    if SetConsoleCtrlHandler(None, if ignore { TRUE } else { FALSE }) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Set whether the console input turns Ctrl-C into a `CTRL_C_EVENT` (`ENABLE_PROCESSED_INPUT`),
+/// returns the previous console mode.
+pub unsafe fn set_processed_input(processed: bool) -> Result<u32, Error> {
+    let input = GetStdHandle(STD_INPUT_HANDLE);
+    let mut mode = 0;
+    if GetConsoleMode(input, &mut mode) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    let new_mode = if processed {
+        mode | ENABLE_PROCESSED_INPUT
     } else {
-        Err(ErrorKind::NotFound.into())
+        mode & !ENABLE_PROCESSED_INPUT
+    };
+    set_console_mode(new_mode)?;
+    Ok(mode)
+}
+
+/// Set the mode of the console input.
+pub unsafe fn set_console_mode(mode: u32) -> Result<(), Error> {
+    if SetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), mode) == FALSE {
+        return Err(io::Error::last_os_error());
     }
+    Ok(())
+}
+
+/// Attaching to a console is process wide, so only one console event can be sent at a time.
+static CONSOLE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Run `f` attached to the console of process `pid`, to send it console control events.
+///
+/// # Errors
+/// Will return an error if the console could not be attached, or the error of `f`.
+pub unsafe fn with_console_of<T, F>(pid: u32, f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    let _guard = CONSOLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Children inherit our console by default, in which case there is no need to detach
+    // from it (and lose it, as a process cannot reattach to an arbitrary console).
+    let shared = shares_console(pid);
+    if !shared {
+        FreeConsole();
+        if AttachConsole(pid) == FALSE {
+            let e = io::Error::last_os_error();
+            AttachConsole(ATTACH_PARENT_PROCESS);
+            return Err(e);
+        }
+    }
+
+    let result = f();
+
+    if !shared {
+        FreeConsole();
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+    result
+}
+
+unsafe fn shares_console(pid: u32) -> bool {
+    let mut pids = [0u32; 64];
+    let count = GetConsoleProcessList(pids.as_mut_ptr(), pids.len() as u32) as usize;
+    pids[..count.min(pids.len())].contains(&pid)
+}
+
+/// Send `CTRL_BREAK_EVENT` to the process group `group`.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn send_ctrl_break(group: u32) -> Result<(), Error> {
+    with_console_of(group, || {
+        if GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, group) == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+/// Whether any of the standard handles is a Cygwin/MSYS2 pty, which is how mintty (Git Bash)
+/// connects native programs.
+pub unsafe fn is_msys_pty() -> bool {
+    [STD_INPUT_HANDLE, STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+        .iter()
+        .any(|&std_handle| is_msys_pty_handle(GetStdHandle(std_handle)))
+}
+
+unsafe fn is_msys_pty_handle(handle: HANDLE) -> bool {
+    if handle.is_null() || handle == INVALID_HANDLE_VALUE || GetFileType(handle) != FILE_TYPE_PIPE {
+        return false;
+    }
+
+    // FILE_NAME_INFO is a u32 length in bytes followed by the UTF-16 name, so use a u32 buffer
+    // to get the alignment right.
+    let mut buf = [0u32; 256];
+    if GetFileInformationByHandleEx(
+        handle,
+        FileNameInfo,
+        buf.as_mut_ptr().cast(),
+        std::mem::size_of_val(&buf) as u32,
+    ) == FALSE
+    {
+        return false;
+    }
+
+    let info = &*(buf.as_ptr() as *const FILE_NAME_INFO);
+    let capacity = (std::mem::size_of_val(&buf) - std::mem::size_of::<u32>()) / 2;
+    let len = (info.FileNameLength as usize / 2).min(capacity);
+    let name = String::from_utf16_lossy(std::slice::from_raw_parts(info.FileName.as_ptr(), len));
+
+    // Pipe names look like \msys-dd50a72ab4668b33-pty0-to-master
+    (name.contains("msys-") || name.contains("cygwin-")) && name.contains("-pty")
+}
+
+/// Whether the process is attached to a console that can deliver control events.
+pub unsafe fn has_console() -> bool {
+    !GetConsoleWindow().is_null()
 }
 
 pub unsafe fn is_handler_init() -> bool {
-    #[allow(static_mut_refs)]
-    return HANDLER.is_some();
+    !semaphore().is_null()
+}
+
+
+/// Wake up [`block_ctrl_c()`](fn.block_ctrl_c.html) as if `signal` was received, or without a
+/// signal if `None`.
+///
+/// # Errors
+/// Will return an error if no handler is registered or a system error occurred.
+pub unsafe fn inject_signal(signal: Option<Signal>) -> Result<(), Error> {
+    let semaphore = semaphore();
+    if semaphore.is_null() {
+        return Err(ErrorKind::NotFound.into());
+    }
+    received().push_back(signal);
+    if ReleaseSemaphore(semaphore, 1, ptr::null_mut()) == FALSE {
+        received().pop_back();
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Identifies the current installation of the OS handler. Changes when it is removed, so a
+/// handler installed again later has a different generation.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
 }
 
+/// Number of control events dropped since the process started because
+/// [`block_ctrl_c()`](fn.block_ctrl_c.html) fell too far behind.
+pub fn missed_signals() -> u64 {
+    MISSED.load(Ordering::Relaxed)
+}
 
 /// Blocks until a Ctrl-C signal is received.
 ///
@@ -126,24 +698,144 @@ pub unsafe fn is_handler_init() -> bool {
 /// Will return an error if a system error occurred.
 ///
 #[inline]
+#[allow(dead_code)]
 pub unsafe fn block_ctrl_c() -> Result<BlockOutcome, Error> {
-    let handler = HANDLER.ok_or::<Error>(ErrorKind::NotFound.into())?;
-
-    match WaitForSingleObject(handler.semaphore, INFINITE) {
-        WAIT_OBJECT_0 => Ok(BlockOutcome::Awaited),
-        WAIT_FAILED => {
-            if Some(handler) != HANDLER {
-                Ok(BlockOutcome::HandlerRemoved)
-            } else {
-                Err(io::Error::last_os_error())
-            }
+    block_ctrl_c_for(generation())
+}
+
+/// Same as [`block_ctrl_c()`](fn.block_ctrl_c.html), but returns
+/// [`BlockOutcome::HandlerRemoved`] once the OS handler of `generation` was removed, even if
+/// it was installed again since.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn block_ctrl_c_for(generation: u64) -> Result<BlockOutcome, Error> {
+    // Only a finite timeout runs out.
+    Ok(wait(generation, INFINITE)?.unwrap_or(BlockOutcome::Awaited))
+}
+
+/// Same as [`block_ctrl_c()`](fn.block_ctrl_c.html), but returns [`BlockOutcome::TimedOut`]
+/// once `timeout` passed without a signal. A signal already received is still taken with a zero
+/// `timeout`.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c_timeout(timeout: Duration) -> Result<BlockOutcome, Error> {
+    block_ctrl_c_timeout_for(generation(), timeout)
+}
+
+/// Same as [`block_ctrl_c_timeout()`](fn.block_ctrl_c_timeout.html), but for the OS handler of
+/// `generation`, see [`block_ctrl_c_for()`](fn.block_ctrl_c_for.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn block_ctrl_c_timeout_for(
+    generation: u64,
+    timeout: Duration,
+) -> Result<BlockOutcome, Error> {
+    // A deadline too far away for an `Instant` is never reached.
+    let deadline = match Instant::now().checked_add(timeout) {
+        Some(deadline) => deadline,
+        None => return block_ctrl_c_for(generation),
+    };
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        // Rounded up, and longer waits are split since `INFINITE` never runs out.
+        let millis = ((left.as_nanos() + 999_999) / 1_000_000).min((INFINITE - 1) as u128);
+        if let Some(outcome) = wait(generation, millis as u32)? {
+            return Ok(outcome);
+        }
+        if Instant::now() >= deadline {
+            return Ok(BlockOutcome::TimedOut);
+        }
+    }
+}
+
+/// Blocks until a Ctrl-C signal is received, then also takes the signals already received
+/// behind it without blocking.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+#[inline]
+#[allow(dead_code)]
+pub unsafe fn block_ctrl_c_all() -> Result<Vec<BlockOutcome>, Error> {
+    block_ctrl_c_all_for(generation())
+}
+
+/// Same as [`block_ctrl_c_all()`](fn.block_ctrl_c_all.html), but for the OS handler of
+/// `generation`, see [`block_ctrl_c_for()`](fn.block_ctrl_c_for.html). Returns the outcomes in
+/// the order taken, at most [`MAX_BATCH`], or only [`BlockOutcome::HandlerRemoved`].
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub unsafe fn block_ctrl_c_all_for(generation: u64) -> Result<Vec<BlockOutcome>, Error> {
+    let mut outcomes = vec![block_ctrl_c_for(generation)?];
+    if outcomes[0] == BlockOutcome::HandlerRemoved {
+        return Ok(outcomes);
+    }
+    while outcomes.len() < MAX_BATCH {
+        match wait(generation, 0)? {
+            Some(BlockOutcome::HandlerRemoved) | None => break,
+            Some(outcome) => outcomes.push(outcome),
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Wait up to `timeout` milliseconds for the OS handler of `generation`, `None` if it ran out.
+fn wait(generation: u64, timeout: u32) -> Result<Option<BlockOutcome>, Error> {
+    let (removed, semaphore) = match waiter_handles(generation)? {
+        Some(handles) => handles,
+        None => return Ok(Some(BlockOutcome::HandlerRemoved)),
+    };
+
+    // The removal comes first, so signals left over from a removed handler are not reported.
+    let handles = [removed.as_raw_handle(), semaphore.as_raw_handle()];
+    let count = handles.len() as u32;
+    match unsafe { WaitForMultipleObjects(count, handles.as_ptr(), FALSE, timeout) } {
+        WAIT_OBJECT_0 => Ok(Some(BlockOutcome::HandlerRemoved)),
+        ret if ret == WAIT_OBJECT_0 + 1 => match received().pop_front() {
+            Some(Some(signal)) => Ok(Some(BlockOutcome::Signal(signal))),
+            _ => Ok(Some(BlockOutcome::Awaited)),
         },
+        WAIT_TIMEOUT => Ok(None),
+        WAIT_FAILED => Err(io::Error::last_os_error()),
         ret => Err(io::Error::new(
             io::ErrorKind::Other,
             format!(
-                "WaitForSingleObject(), unexpected return value \"{:x}\"",
+                "WaitForMultipleObjects(), unexpected return value \"{:x}\"",
                 ret
             ),
         )),
     }
 }
+
+/// Duplicate the removal event and the semaphore of `generation`, `None` if that handler was
+/// removed.
+///
+/// Waiters wait on their own handles, so removing the handler can't close them under them,
+/// and a handle value reused by someone else is never waited on.
+fn waiter_handles(generation: u64) -> Result<Option<(OwnedHandle, OwnedHandle)>, Error> {
+    let _install = install_lock();
+    let semaphore = semaphore();
+    if semaphore.is_null() || GENERATION.load(Ordering::Acquire) != generation {
+        return Ok(None);
+    }
+    // Both stay open while the lock is held.
+    let (removed, semaphore) = unsafe {
+        (
+            BorrowedHandle::borrow_raw(REMOVED.load(Ordering::Acquire)),
+            BorrowedHandle::borrow_raw(semaphore),
+        )
+    };
+    Ok(Some((
+        removed.try_clone_to_owned()?,
+        semaphore.try_clone_to_owned()?,
+    )))
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+/// What to do with the handlers already installed for a signal, see
+/// [`Builder::install_policy()`](struct.Builder.html#method.install_policy).
+///
+/// On Unix this is about the `sigaction` in place before. On Windows, console handler routines
+/// never replace each other, so only [`Chain`](#variant.Chain) makes a difference there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum InstallPolicy {
+    /// Fail with [`Error::MultipleHandlers`](enum.Error.html#variant.MultipleHandlers) if any of
+    /// the signals is ignored or has a handler, see [`try_set_handler()`](fn.try_set_handler.html).
+    ErrorIfExists,
+    /// Replace default and ignore actions, but leave signals with a handler alone. Those
+    /// signals are not handled, which is no error.
+    ReplaceIfDefault,
+    /// Replace whatever is installed.
+    #[default]
+    ForceReplace,
+    /// Replace whatever is installed, and keep calling a displaced handler after waking up the
+    /// handler thread. Default and ignore actions are not chained on Unix. On Windows, the
+    /// handler routines registered before run as well, ending with the default one, which
+    /// terminates the process.
+    Chain,
+}
+
+impl From<bool> for InstallPolicy {
+    /// The meaning of the `overwrite` flag: `true` is [`ForceReplace`](#variant.ForceReplace),
+    /// `false` is [`ErrorIfExists`](#variant.ErrorIfExists).
+    fn from(overwrite: bool) -> InstallPolicy {
+        if overwrite {
+            InstallPolicy::ForceReplace
+        } else {
+            InstallPolicy::ErrorIfExists
+        }
+    }
+}
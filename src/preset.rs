@@ -0,0 +1,60 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Ready-made [`Builder`](crate::Builder) configurations for common deployment environments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Preset {
+    /// Graceful shutdown of a Kubernetes pod.
+    ///
+    /// Handles `SIGTERM` (and Ctrl-C for local runs). On the first signal the
+    /// [`Readiness`] flag flips to not ready immediately, then the handler waits `drain_delay`
+    /// so endpoints can be removed from load balancers before it starts shutting down.
+    ///
+    /// The grace period defaults to 30 seconds, the default `terminationGracePeriodSeconds`;
+    /// call [`Builder::grace_period`](crate::Builder::grace_period) after applying the preset
+    /// to match your pod spec.
+    Kubernetes {
+        /// Delay between the termination signal and running the handler.
+        drain_delay: Duration,
+    },
+}
+
+/// Default `terminationGracePeriodSeconds` of a Kubernetes pod.
+pub(crate) const KUBERNETES_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Readiness flag that flips to not ready as soon as the first signal is received.
+///
+/// Obtained from [`Builder::readiness`](crate::Builder::readiness), typically reported by a
+/// readiness probe endpoint.
+#[derive(Debug, Clone)]
+pub struct Readiness {
+    ready: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    pub(crate) fn new() -> Readiness {
+        Readiness {
+            ready: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// `false` once a signal has been received.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_not_ready(&self) {
+        self.ready.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::platform;
+
+/// Scheduling priority of the handler threads, see
+/// [`Builder::thread_priority()`](struct.Builder.html#method.thread_priority).
+///
+/// On Linux and Android this is the nice value of the thread (0, -5 and -10), raising it
+/// requires `CAP_SYS_NICE`. On Windows it is the thread priority (`THREAD_PRIORITY_NORMAL`,
+/// `THREAD_PRIORITY_ABOVE_NORMAL` and `THREAD_PRIORITY_HIGHEST`). Other platforms don't
+/// support it.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThreadPriority {
+    /// The priority threads are created with.
+    #[default]
+    Normal,
+    /// Above normal priority.
+    AboveNormal,
+    /// The highest priority that is not real-time.
+    Highest,
+}
+
+/// Scheduling configuration of the handler threads.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scheduling {
+    pub(crate) priority: Option<ThreadPriority>,
+    pub(crate) affinity: Option<Vec<usize>>,
+}
+
+impl Scheduling {
+    /// Apply to the current thread, reporting failures on stderr.
+    pub(crate) fn apply(&self) {
+        if let Some(priority) = self.priority {
+            if let Err(e) = platform::set_thread_priority(priority) {
+                eprintln!("[ctrlc] Could not set the handler thread priority: {}", e);
+            }
+        }
+        if let Some(ref cpus) = self.affinity {
+            if let Err(e) = platform::set_thread_affinity(cpus) {
+                eprintln!("[ctrlc] Could not set the handler thread affinity: {}", e);
+            }
+        }
+    }
+}
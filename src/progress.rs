@@ -0,0 +1,178 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Hook = Arc<dyn Fn(&TaskProgress) + Send + Sync>;
+
+struct Registry {
+    next_id: u64,
+    tasks: BTreeMap<u64, TaskProgress>,
+    hooks: Vec<Hook>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    next_id: 0,
+    tasks: BTreeMap::new(),
+    hooks: Vec::new(),
+});
+
+fn lock() -> MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Progress of a single shutdown task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskProgress {
+    task: String,
+    step: String,
+    current: u32,
+    total: u32,
+    finished: bool,
+}
+
+impl TaskProgress {
+    /// Name of the task.
+    pub fn task(&self) -> &str {
+        &self.task
+    }
+
+    /// Description of the current step, empty before the first step.
+    pub fn step(&self) -> &str {
+        &self.step
+    }
+
+    /// Number of the current step.
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Total number of steps, 0 if unknown.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Whether the task has finished.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl fmt::Display for TaskProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.task)?;
+        if self.finished {
+            write!(f, "done")
+        } else if self.total > 0 {
+            write!(f, "{} ({}/{})", self.step, self.current, self.total)
+        } else {
+            write!(f, "{}", self.step)
+        }
+    }
+}
+
+/// Reports the progress of a shutdown task, so a slow shutdown can be told apart from a hung one.
+///
+/// Every update is visible in [`progress()`] and passed to the hooks registered with
+/// [`on_progress()`]. The task is marked finished when the reporter is dropped.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::on_progress(ctrlc::progress_to_stderr);
+///
+/// let reporter = ctrlc::ProgressReporter::new("storage");
+/// reporter.step("flushing WAL", 3, 7);
+/// ```
+#[derive(Debug)]
+pub struct ProgressReporter {
+    id: u64,
+}
+
+impl ProgressReporter {
+    /// Register a task named `task`.
+    pub fn new<S: Into<String>>(task: S) -> ProgressReporter {
+        let mut registry = lock();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.tasks.insert(
+            id,
+            TaskProgress {
+                task: task.into(),
+                step: String::new(),
+                current: 0,
+                total: 0,
+                finished: false,
+            },
+        );
+        ProgressReporter { id }
+    }
+
+    /// Report that the task is at step `current` of `total`, described by `step`.
+    pub fn step(&self, step: &str, current: u32, total: u32) {
+        self.update(|progress| {
+            progress.step.clear();
+            progress.step.push_str(step);
+            progress.current = current;
+            progress.total = total;
+        });
+    }
+
+    /// Mark the task as finished.
+    pub fn finish(self) {}
+
+    fn update<F: FnOnce(&mut TaskProgress)>(&self, f: F) {
+        let (progress, hooks) = {
+            let mut registry = lock();
+            let progress = match registry.tasks.get_mut(&self.id) {
+                Some(progress) => progress,
+                None => return,
+            };
+            f(progress);
+            (progress.clone(), registry.hooks.clone())
+        };
+
+        for hook in hooks {
+            hook(&progress);
+        }
+    }
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        self.update(|progress| progress.finished = true);
+    }
+}
+
+/// Snapshot of the progress of all tasks, in registration order.
+pub fn progress() -> Vec<TaskProgress> {
+    lock().tasks.values().cloned().collect()
+}
+
+/// Call `hook` on every progress update. Hooks run on the thread reporting the progress.
+pub fn on_progress<F>(hook: F)
+where
+    F: Fn(&TaskProgress) + Send + Sync + 'static,
+{
+    lock().hooks.push(Arc::new(hook));
+}
+
+/// Progress hook printing updates to stderr.
+pub fn progress_to_stderr(progress: &TaskProgress) {
+    eprintln!("[ctrlc] {}", progress);
+}
+
+/// Progress hook reporting updates to systemd as the service status (`STATUS=`).
+///
+/// Does nothing when not running under systemd, see [`unix::sd_notify()`](unix/fn.sd_notify.html).
+#[cfg(all(unix, not(target_os = "espidf")))]
+pub fn progress_to_systemd(progress: &TaskProgress) {
+    let _ = crate::unix::sd_notify(&format!("STATUS={}", progress));
+}
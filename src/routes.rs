@@ -0,0 +1,253 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::error::Error;
+use crate::handlers;
+use crate::platform::{self, Signal};
+use crate::{Builder, SignalType};
+use arc_swap::ArcSwapOption;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Route = Arc<Mutex<dyn FnMut() + Send>>;
+
+#[derive(Clone)]
+struct Entry {
+    id: u64,
+    /// `None` for handlers running along with the main handler.
+    signal: Option<Signal>,
+    handler: Route,
+}
+
+struct Writer {
+    next_id: u64,
+    /// Signals installed only for routing, removed again with their last route.
+    added: Vec<Signal>,
+}
+
+// The dispatch loads the current snapshot of the routes without locking. Writers serialize on
+// `WRITER` and publish a new snapshot.
+static ENTRIES: ArcSwapOption<Vec<Entry>> = ArcSwapOption::const_empty();
+static WRITER: Mutex<Writer> = Mutex::new(Writer {
+    next_id: 0,
+    added: Vec::new(),
+});
+
+fn lock() -> MutexGuard<'static, Writer> {
+    WRITER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn entries() -> Vec<Entry> {
+    ENTRIES
+        .load_full()
+        .map_or_else(Vec::new, |entries| entries.to_vec())
+}
+
+/// Identifies a handler registered with [`on()`] or [`add_handler()`], see [`remove()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+/// Run `handler` whenever `signal` is received.
+///
+/// All signals share the OS handler and the signal handling thread of the crate. If no handler
+/// is registered yet, one doing nothing else is registered for `signal`, otherwise `signal` is
+/// added to it like with [`add_signal()`](fn.add_signal.html). Several handlers may be
+/// registered for the same signal, they run in registration order.
+///
+/// The handler registered that way is not replaced by a later
+/// [`set_handler()`](fn.set_handler.html), which fails with [`Error::MultipleHandlers`], so
+/// register the main handler first if the application has one.
+///
+/// Routed signals run their handlers on the signal handling thread instead of the main handler,
+/// so they neither start the shutdown nor count towards `force_after` or storm protection. Keep
+/// the handlers short, signals are not processed while they run.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::Signal;
+///
+/// ctrlc::on(Signal::Terminate, || std::process::exit(0)).unwrap();
+/// # #[cfg(unix)]
+/// ctrlc::on(Signal::Hangup, || println!("Reloading configuration...")).unwrap();
+/// ```
+///
+/// # Errors
+/// Will return [`Error::NoSuchSignal`] if `signal` has no equivalent on the current platform,
+/// or an error if a system error occurred while installing the OS handler for `signal`, the
+/// handler is not registered then.
+pub fn on<F>(signal: crate::Signal, handler: F) -> Result<HandlerId, Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    let signal = match platform::from_signal(signal) {
+        Some(signal) => signal,
+        None => return Err(Error::NoSuchSignal(SignalType::Other(signal))),
+    };
+    let id = push(Some(signal), Arc::new(Mutex::new(handler)));
+    match install(Some(signal)) {
+        Ok(()) => Ok(id),
+        Err(e) => {
+            remove(id);
+            Err(e)
+        }
+    }
+}
+
+/// Run `handler` on every signal that reaches the main handler, after it.
+///
+/// Every registration is independent, so libraries can add their own cleanup without taking
+/// over the handler of the application. Handlers run in registration order on the handler
+/// thread. If no handler is registered yet, a handler doing nothing else is registered for the
+/// default signals, so register the main handler first if the application has one.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Shutting down...")).expect("Error setting Ctrl-C handler");
+///
+/// let id = ctrlc::add_handler(|| println!("Flushing the library cache...")).unwrap();
+/// // ... when the library is shut down early ...
+/// ctrlc::remove(id);
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while registering the handler.
+pub fn add_handler<F>(handler: F) -> Result<HandlerId, Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    let id = push(None, Arc::new(Mutex::new(handler)));
+    match install(None) {
+        Ok(()) => Ok(id),
+        Err(e) => {
+            remove(id);
+            Err(e)
+        }
+    }
+}
+
+/// Remove a handler registered with [`on()`] or [`add_handler()`]. Returns whether it was still
+/// registered.
+///
+/// Signals installed by [`on()`] stop being handled with their last handler.
+pub fn remove(id: HandlerId) -> bool {
+    let unused = {
+        let mut writer = lock();
+        let mut entries = entries();
+        let index = match entries.iter().position(|entry| entry.id == id.0) {
+            Some(index) => index,
+            None => return false,
+        };
+        let signal = entries.remove(index).signal;
+        let still_routed = entries.iter().any(|entry| entry.signal == signal);
+        ENTRIES.store(Some(Arc::new(entries)));
+        match signal {
+            Some(signal) if !still_routed && writer.added.contains(&signal) => {
+                writer.added.retain(|&added| added != signal);
+                Some(signal)
+            }
+            _ => None,
+        }
+    };
+    if let Some(signal) = unused {
+        let _ = crate::remove_signal(SignalType::Other(platform::to_signal(signal)));
+    }
+    true
+}
+
+fn push(signal: Option<Signal>, handler: Route) -> HandlerId {
+    let mut writer = lock();
+    let id = writer.next_id;
+    writer.next_id += 1;
+    let mut entries = entries();
+    entries.push(Entry {
+        id,
+        signal,
+        handler,
+    });
+    ENTRIES.store(Some(Arc::new(entries)));
+    HandlerId(id)
+}
+
+/// Make sure a handler is registered, handling `signal` if given.
+fn install(signal: Option<Signal>) -> Result<(), Error> {
+    loop {
+        let handled = handlers::snapshot().and_then(|handlers| {
+            handlers
+                .first()
+                .map(|handler| handler.platform_signals().to_vec())
+        });
+        match handled {
+            Some(handled) => {
+                let signal = match signal {
+                    Some(signal) if !handled.contains(&signal) => signal,
+                    _ => return Ok(()),
+                };
+                match crate::add_signal(SignalType::Other(platform::to_signal(signal))) {
+                    Ok(()) => {
+                        lock().added.push(signal);
+                        return Ok(());
+                    }
+                    Err(Error::HandlerRemoved) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            None => {
+                let builder = match signal {
+                    Some(signal) => {
+                        Builder::new().signals([SignalType::Other(platform::to_signal(signal))])
+                    }
+                    None => Builder::new(),
+                };
+                match builder.set_handler(|| {}) {
+                    Ok(_) => {
+                        lock().added.extend(signal);
+                        return Ok(());
+                    }
+                    // Raced with another registration, use that one instead.
+                    Err(Error::MultipleHandlers) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+fn routes_of(signal: Option<Signal>) -> Vec<Route> {
+    let entries = ENTRIES.load();
+    entries.as_deref().map_or_else(Vec::new, |entries| {
+        entries
+            .iter()
+            .filter(|entry| entry.signal == signal)
+            .map(|entry| entry.handler.clone())
+            .collect()
+    })
+}
+
+fn run(routes: &[Route]) {
+    for route in routes {
+        (*route.lock().unwrap_or_else(|e| e.into_inner()))();
+    }
+}
+
+/// Run the handlers registered for `signal`, returns whether there were any.
+pub(crate) fn dispatch(signal: Signal) -> bool {
+    let routes = routes_of(Some(signal));
+    run(&routes);
+    !routes.is_empty()
+}
+
+/// Run the handlers added with [`add_handler()`].
+pub(crate) fn run_added() {
+    run(&routes_of(None));
+}
+
+pub(crate) fn clear() {
+    let mut writer = lock();
+    ENTRIES.store(None);
+    writer.added.clear();
+}
@@ -0,0 +1,314 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::error::Error;
+use crate::lifecycle::{lifecycle, LifecycleState};
+use crate::{ack, platform};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+static REASON: Mutex<Option<ShutdownReason>> = Mutex::new(None);
+
+fn lock() -> MutexGuard<'static, Option<ShutdownReason>> {
+    REASON.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Why the shutdown was initiated.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShutdownReason {
+    /// A signal was received from outside of the process.
+    Signal,
+    /// [`ShutdownController::shutdown()`] was called with this reason.
+    Requested(String),
+}
+
+/// Initiates the shutdown from within the process.
+///
+/// [`shutdown()`](#method.shutdown) runs the same pipeline as a received signal: the handler,
+/// the orchestrator tasks, `force_after` counting and the grace period. Internal fatal errors
+/// and administrative requests can use it instead of a separate shutdown path.
+///
+/// On ESP-IDF, where there are no POSIX signals, this is how firmware triggers the handler.
+///
+/// # Example
+/// ```no_run
+/// let controller = ctrlc::ShutdownController::new();
+/// ctrlc::set_handler({
+///     let controller = controller.clone();
+///     move || println!("Shutting down: {:?}", controller.reason())
+/// })
+/// .expect("Error setting Ctrl-C handler");
+///
+/// controller.shutdown("lost database connection").unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownController {
+    _private: (),
+}
+
+impl ShutdownController {
+    /// Create a controller for the handler of the process.
+    pub fn new() -> ShutdownController {
+        ShutdownController { _private: () }
+    }
+
+    /// Run the registered handler as if a signal was received.
+    ///
+    /// The reason of the first shutdown is kept, see [`reason()`](#method.reason). Signals
+    /// latched by [`prearm()`](fn.prearm.html) are also latched this way.
+    ///
+    /// # Errors
+    /// Will return [`Error::HandlerRemoved`] if no handler is registered, or an error if a
+    /// system error occurred.
+    pub fn shutdown<S: Into<String>>(&self, reason: S) -> Result<(), Error> {
+        if !unsafe { platform::is_handler_init() } {
+            return Err(Error::HandlerRemoved);
+        }
+        lock().get_or_insert(ShutdownReason::Requested(reason.into()));
+        unsafe { platform::inject_signal(None)? };
+        Ok(())
+    }
+
+    /// Reason of the first shutdown, `None` if none was initiated yet.
+    pub fn reason(&self) -> Option<ShutdownReason> {
+        lock().clone()
+    }
+}
+
+static WAKERS: Mutex<Vec<Waker>> = Mutex::new(Vec::new());
+static COMPLETED: Condvar = Condvar::new();
+
+fn lock_wakers() -> MutexGuard<'static, Vec<Waker>> {
+    WAKERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Completion of the shutdown pipeline, see [`shutdown_complete()`].
+///
+/// Also a [`Future`] resolving on completion, usable from any async runtime.
+#[derive(Debug, Clone, Default)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ShutdownComplete {
+    _private: (),
+}
+
+impl ShutdownComplete {
+    /// Whether the shutdown has completed.
+    pub fn is_complete(&self) -> bool {
+        lifecycle() == LifecycleState::Stopped && ack::all_acknowledged()
+    }
+
+    /// Block until the shutdown has completed.
+    pub fn wait(&self) {
+        wait_until(|| self.is_complete(), None);
+    }
+
+    /// Block until the shutdown has completed or `timeout` expires.
+    ///
+    /// Returns whether the shutdown has completed.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        wait_until(|| self.is_complete(), Some(timeout))
+    }
+}
+
+/// Block until `done` returns true or `timeout` expires, returns whether it did. `done` is
+/// checked again whenever [`notify_progress()`] is called.
+fn wait_until(done: impl Fn() -> bool, timeout: Option<Duration>) -> bool {
+    // Timeouts too long for an `Instant` wait forever.
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    let mut wakers = lock_wakers();
+    while !done() {
+        wakers = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return false;
+                }
+                COMPLETED
+                    .wait_timeout(wakers, remaining)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0
+            }
+            None => COMPLETED.wait(wakers).unwrap_or_else(|e| e.into_inner()),
+        };
+    }
+    true
+}
+
+impl Future for ShutdownComplete {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut wakers = lock_wakers();
+        if self.is_complete() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Resolves once the whole shutdown pipeline has finished: the handler and the orchestrator
+/// tasks have run for the first signal (the lifecycle is
+/// [`Stopped`](enum.LifecycleState.html#variant.Stopped)) and every
+/// [`AckHandle`](struct.AckHandle.html) has been acknowledged.
+///
+/// Lets a supervisor thread or an embedding host know when it is safe to unload or exit.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Shutting down...")).expect("Error setting Ctrl-C handler");
+///
+/// // Blocking:
+/// ctrlc::shutdown_complete().wait();
+///
+/// // Async:
+/// # async fn f() {
+/// ctrlc::shutdown_complete().await;
+/// # }
+/// ```
+pub fn shutdown_complete() -> ShutdownComplete {
+    ShutdownComplete::default()
+}
+
+/// Start of the shutdown, see [`shutdown()`].
+///
+/// A [`Future`] resolving once the shutdown has started, usable from any executor.
+#[derive(Debug, Clone, Default)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Shutdown {
+    _private: (),
+}
+
+impl Shutdown {
+    /// Whether the shutdown has started.
+    pub fn is_started(&self) -> bool {
+        lifecycle() != LifecycleState::Running
+    }
+}
+
+impl Future for Shutdown {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut wakers = lock_wakers();
+        if self.is_started() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Resolves once the shutdown has started, i.e. the lifecycle has left
+/// [`Running`](enum.LifecycleState.html#variant.Running) because a signal was received or
+/// [`ShutdownController::shutdown()`] was called.
+///
+/// The handler thread wakes the future, so it needs no runtime: hand-rolled executors and
+/// `pollster::block_on()` can await termination. A handler must be registered.
+///
+/// # Example
+/// ```no_run
+/// # async fn serve() {}
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// # async fn f() {
+/// let shutdown = ctrlc::shutdown();
+/// // ... `select` between `serve()` and `shutdown` ...
+/// shutdown.await;
+/// # }
+/// ```
+pub fn shutdown() -> Shutdown {
+    Shutdown::default()
+}
+
+/// Cancellation token for worker threads, set once the shutdown has started.
+///
+/// Cancelled when the lifecycle leaves [`Running`](enum.LifecycleState.html#variant.Running)
+/// because a signal was received or [`ShutdownController::shutdown()`] was called, like
+/// [`shutdown()`]. Every clone observes the same shutdown, so workers share it instead of each
+/// registering a handler. A handler must be registered for the token to be cancelled.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// let token = ctrlc::ShutdownToken::new();
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let token = token.clone();
+///         std::thread::spawn(move || {
+///             while !token.wait_timeout(Duration::from_secs(1)) {
+///                 // ... periodic work ...
+///             }
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownToken {
+    _private: (),
+}
+
+impl ShutdownToken {
+    /// Create a token for the shutdown of the process.
+    pub fn new() -> ShutdownToken {
+        ShutdownToken { _private: () }
+    }
+
+    /// Whether the shutdown has started. Only loads an atomic, cheap enough for hot loops.
+    pub fn is_cancelled(&self) -> bool {
+        lifecycle() != LifecycleState::Running
+    }
+
+    /// Block until the shutdown has started.
+    pub fn wait(&self) {
+        wait_until(|| self.is_cancelled(), None);
+    }
+
+    /// Block until the shutdown has started or `timeout` expires.
+    ///
+    /// Returns whether the shutdown has started.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        wait_until(|| self.is_cancelled(), Some(timeout))
+    }
+}
+
+/// Wake the waiters of [`shutdown()`] and [`shutdown_complete()`] to check for progress again.
+///
+/// Must not be called with the lifecycle or ack locks held.
+pub(crate) fn notify_progress() {
+    let wakers = std::mem::take(&mut *lock_wakers());
+    COMPLETED.notify_all();
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// Record a received signal as the reason, unless a shutdown was requested first.
+pub(crate) fn record_signal() {
+    lock().get_or_insert(ShutdownReason::Signal);
+}
+
+pub(crate) fn clear() {
+    *lock() = None;
+}
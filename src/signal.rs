@@ -8,16 +8,414 @@
 // according to those terms.
 
 use crate::platform;
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+/// A signal or Windows console control event, the same on every platform.
+///
+/// Each variant stands for the signal or event closest to it on the current platform, so code
+/// matching on signals needs no `#[cfg]`. Variants without an equivalent on the current platform,
+/// such as [`Signal::Hangup`] on Windows, are never received, and are left out where a set of
+/// signals is handled. Signals without a variant of their own are [`Signal::Other`] with their
+/// number, e.g. `SIGWINCH` on Unix.
+///
+/// On Unix the usual signal names are associated constants, e.g. `Signal::SIGTERM` is
+/// [`Signal::Terminate`] and `Signal::SIGWINCH` is `Signal::Other(28)` on Linux.
+///
+/// With the `serde` feature, signals are (de)serialized by variant, e.g. `"CtrlC"` or
+/// `{"Other":28}`.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::Signal;
+///
+/// ctrlc::set_handler_with_signal(|signal| match signal {
+///     Signal::CtrlC | Signal::Break => println!("Interrupted"),
+///     Signal::Terminate | Signal::Hangup => println!("Terminated"),
+///     signal => println!("Received {:?}", signal),
+/// })
+/// .expect("Error setting Ctrl-C handler");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Signal {
+    /// Ctrl-C: `SIGINT` on Unix, `CTRL_C_EVENT` on Windows.
+    CtrlC,
+    /// Ctrl-\\ or Ctrl-Break: `SIGQUIT` on Unix, `CTRL_BREAK_EVENT` on Windows.
+    Break,
+    /// A request to terminate: `SIGTERM` on Unix, `CTRL_CLOSE_EVENT` on Windows.
+    Terminate,
+    /// The controlling terminal went away: `SIGHUP` on Unix.
+    Hangup,
+    /// `SIGUSR1` on Unix.
+    User1,
+    /// `SIGUSR2` on Unix.
+    User2,
+    /// The user is logging off: `CTRL_LOGOFF_EVENT` on Windows.
+    Logoff,
+    /// The system is shutting down: `CTRL_SHUTDOWN_EVENT` on Windows.
+    Shutdown,
+    /// Any other signal or event by its number on the current platform.
+    ///
+    /// Signals with a variant of their own are never received as `Other`, but `Other` with
+    /// their number stands for them as well, e.g. `Signal::Other(2)` is handled like
+    /// [`Signal::CtrlC`] on Unix.
+    Other(i32),
+}
+
+impl Signal {
+    /// The signal number on Unix, the console control event on Windows. `None` if the signal
+    /// has no equivalent on the current platform.
+    pub fn as_raw(&self) -> Option<i32> {
+        platform::from_signal(*self).map(|signal| signal as i32)
+    }
+}
 
 /// A cross-platform way to represent Ctrl-C or program termination signal. Other
 /// signals/events are supported via `Other`-variant.
-#[derive(Debug)]
+///
+/// With the `serde` feature, `Other` signals are (de)serialized by name, e.g. `"SIGUSR1"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SignalType {
     /// Ctrl-C
     Ctrlc,
     /// Program termination
     /// Maps to `SIGTERM` and `SIGHUP` on *nix, `CTRL_CLOSE_EVENT` on Windows.
     Termination,
-    /// Other signal/event using platform-specific data
-    Other(platform::Signal),
+    /// Other signal/event. Not handled if it has no equivalent on the current platform.
+    #[cfg_attr(feature = "serde", serde(with = "signal_name"))]
+    Other(Signal),
+}
+
+impl SignalType {
+    /// Every signal supported on the current platform, e.g. to generate help text or validate
+    /// signal names without hardcoding platform lists.
+    ///
+    /// # Example
+    /// ```
+    /// let names: Vec<String> = ctrlc::SignalType::all()
+    ///     .filter(|info| info.is_termination())
+    ///     .map(|info| info.name().to_string())
+    ///     .collect();
+    /// println!("--handle-signals accepts: {}", names.join(", "));
+    /// ```
+    pub fn all() -> impl Iterator<Item = SignalInfo> {
+        platform::supported_signals()
+            .into_iter()
+            .map(|signal| SignalInfo {
+                signal,
+                name: platform::signal_name(signal),
+                termination: platform::is_termination(signal),
+                exit_code: platform::exit_code(signal),
+            })
+    }
+}
+
+/// A signal supported on the current platform, see [`SignalType::all()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalInfo {
+    signal: platform::Signal,
+    name: String,
+    termination: bool,
+    exit_code: u8,
+}
+
+impl SignalInfo {
+    /// The signal, handled with [`SignalType::Other`].
+    pub fn signal(&self) -> Signal {
+        platform::to_signal(self.signal)
+    }
+
+    /// Name of the signal, such as `SIGTERM` or `CTRL_CLOSE_EVENT`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the signal asks the process to terminate, like `SIGINT`, `SIGTERM`, `SIGHUP` and
+    /// `SIGQUIT` on Unix and all console control events on Windows.
+    pub fn is_termination(&self) -> bool {
+        self.termination
+    }
+
+    /// The exit status a shell reports for a process killed by the signal, `128 + signal`, e.g.
+    /// 130 for `SIGINT` and 143 for `SIGTERM`. Windows events map to the C runtime signal they
+    /// are delivered as: 130 for `CTRL_C_EVENT`, 149 for `CTRL_BREAK_EVENT` and 143 otherwise.
+    pub fn exit_code(&self) -> u8 {
+        self.exit_code
+    }
+}
+
+/// A received signal, passed to handler filters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignalEvent {
+    count: u32,
+    #[cfg_attr(feature = "serde", serde(default = "one"))]
+    coalesced: u32,
+    #[cfg_attr(feature = "serde", serde(with = "signal_name::option"))]
+    signal: Option<platform::Signal>,
+    received_at: SystemTime,
+    #[cfg_attr(feature = "serde", serde(default))]
+    resize: Option<Resize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    sender: Option<SignalSender>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    sender_name: Option<String>,
+}
+
+/// The size of the terminal when `SIGWINCH` was received, see [`SignalEvent::resize()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resize {
+    cols: u16,
+    rows: u16,
+}
+
+impl Resize {
+    /// Number of columns.
+    pub fn cols(&self) -> u16 {
+        self.cols
+    }
+
+    /// Number of rows.
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+}
+
+/// The process that sent a signal, see [`SignalEvent::sender()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignalSender {
+    pid: u32,
+    uid: u32,
+}
+
+impl SignalSender {
+    /// Process id of the sender.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Real user id of the sender.
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+}
+
+impl SignalEvent {
+    pub(crate) fn new(count: u32, signal: Option<platform::Signal>) -> SignalEvent {
+        SignalEvent {
+            count,
+            coalesced: 1,
+            signal,
+            received_at: SystemTime::now(),
+            resize: signal
+                .and_then(platform::window_size)
+                .map(|(cols, rows)| Resize { cols, rows }),
+            sender: signal
+                .and_then(platform::sender)
+                .map(|(pid, uid)| SignalSender { pid, uid }),
+            sender_name: None,
+        }
+    }
+
+    /// Look up the name of the sender, see [`sender_name()`](#method.sender_name).
+    pub(crate) fn resolve_sender_name(&mut self) {
+        self.sender_name = self
+            .sender
+            .and_then(|sender| platform::process_name(sender.pid));
+    }
+
+    /// Merge `next`, received while the handler was running, into this event.
+    pub(crate) fn coalesce(&mut self, next: SignalEvent) {
+        let coalesced = self.coalesced.saturating_add(next.coalesced);
+        *self = SignalEvent { coalesced, ..next };
+    }
+
+    /// This event, standing for `coalesced` signals.
+    #[cfg(feature = "futures")]
+    pub(crate) fn with_coalesced(self, coalesced: u32) -> SignalEvent {
+        SignalEvent { coalesced, ..self }
+    }
+
+    /// The received signal, `None` if the handler was run without one, e.g. by
+    /// [`ShutdownController::shutdown()`](struct.ShutdownController.html#method.shutdown).
+    pub fn signal(&self) -> Option<Signal> {
+        self.signal.map(platform::to_signal)
+    }
+
+    /// The received signal as the platform sees it.
+    pub(crate) fn platform_signal(&self) -> Option<platform::Signal> {
+        self.signal
+    }
+
+    /// Number of signals received so far, including this one.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Number of signals this event stands for, including this one. More than 1 only for items
+    /// of a [`stream()`](fn.stream.html) that fell behind. The event is the last of them.
+    ///
+    /// Filters see every signal on its own, before
+    /// [`Delivery::Coalesced`](enum.Delivery.html#variant.Coalesced) merges the signals received
+    /// while the handler was running into one run.
+    pub fn coalesced(&self) -> u32 {
+        self.coalesced
+    }
+
+    /// When the signal was received.
+    pub fn received_at(&self) -> SystemTime {
+        self.received_at
+    }
+
+    /// The new size of the terminal for `SIGWINCH`, `None` for other signals or without a
+    /// terminal.
+    ///
+    /// Handle `SIGWINCH` like any other signal to get resizes through the same channel as
+    /// Ctrl-C, e.g. a [`Handle`](struct.Handle.html) polled by the event loop of a TUI. It
+    /// isn't handled by default.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[cfg(unix)]
+    /// # {
+    /// use ctrlc::Signal;
+    ///
+    /// let handle = ctrlc::Handle::new(&[Signal::SIGINT, Signal::SIGWINCH], true)
+    ///     .expect("Error setting Ctrl-C handler");
+    /// for event in std::iter::from_fn(|| handle.try_recv().expect("Error polling")) {
+    ///     match event.resize() {
+    ///         Some(size) => println!("Resized to {}x{}", size.cols(), size.rows()),
+    ///         None => return,
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn resize(&self) -> Option<Resize> {
+        self.resize
+    }
+
+    /// The process that sent the signal with `kill(2)` or `sigqueue(3)`, `None` if the kernel
+    /// sent it, e.g. for Ctrl-C in the terminal, and on Windows.
+    ///
+    /// Taken from the `siginfo_t` of the signal, except with the `kqueue` feature, which never
+    /// reports a sender. Of signals arriving faster than they are handled, the event may report
+    /// the sender of a later one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+    ///
+    /// let event = ctrlc::signals().next().expect("Ctrl-C handler removed");
+    /// match event.sender() {
+    ///     Some(sender) => eprintln!("Stopped by pid {} (uid {})", sender.pid(), sender.uid()),
+    ///     None => eprintln!("Interrupted"),
+    /// }
+    /// ```
+    pub fn sender(&self) -> Option<SignalSender> {
+        self.sender
+    }
+
+    /// Name of the process that sent the signal, such as `kill` or `systemd`, if the handler was
+    /// set with [`Builder::sender_names()`](struct.Builder.html#method.sender_names). `None`
+    /// without a [`sender()`](#method.sender), if it exited before it could be looked up, and
+    /// on platforms other than Linux and Android.
+    pub fn sender_name(&self) -> Option<&str> {
+        self.sender_name.as_deref()
+    }
+
+    /// The exit status for the signal, see [`SignalInfo::exit_code()`]. Events without a signal
+    /// use the Ctrl-C signal.
+    pub fn exit_code(&self) -> u8 {
+        let signal = self
+            .signal
+            .unwrap_or_else(|| platform::signals_of(&SignalType::Ctrlc)[0]);
+        platform::exit_code(signal)
+    }
+}
+
+/// Exit with the status a shell reports for the signal, so `main` can return it.
+///
+/// # Example
+/// ```no_run
+/// use std::process::ExitCode;
+///
+/// fn main() -> ExitCode {
+///     ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+///     // ... start the workers ...
+///
+///     let event = ctrlc::signals().next().expect("Ctrl-C handler removed");
+///     println!("Cleaning up...");
+///     event.into()
+/// }
+/// ```
+impl From<SignalEvent> for ExitCode {
+    fn from(event: SignalEvent) -> ExitCode {
+        ExitCode::from(event.exit_code())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn one() -> u32 {
+    1
+}
+
+#[cfg(feature = "serde")]
+mod signal_name {
+    use crate::platform;
+    use serde::de::Error;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(signal: &crate::Signal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let signal = platform::from_signal(*signal)
+            .ok_or_else(|| S::Error::custom(format!("unsupported signal {:?}", signal)))?;
+        serializer.serialize_str(&platform::signal_name(signal))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<crate::Signal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        platform::parse_signal(&name)
+            .map(platform::to_signal)
+            .ok_or_else(|| D::Error::custom(format!("unknown signal {:?}", name)))
+    }
+
+    pub mod option {
+        use crate::platform;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S>(
+            signal: &Option<platform::Signal>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match *signal {
+                Some(signal) => serializer.serialize_some(&platform::signal_name(signal)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<platform::Signal>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct Named(#[serde(with = "super")] crate::Signal);
+
+            Ok(Option::<Named>::deserialize(deserializer)?
+                .and_then(|Named(signal)| platform::from_signal(signal)))
+        }
+    }
 }
@@ -0,0 +1,92 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Helpers for testing how programs react to Ctrl-C.
+
+use std::io;
+use std::process::Child;
+
+/// Send Ctrl-C to a spawned child process.
+///
+/// On Unix this sends `SIGINT` to the child. On Windows a `CTRL_C_EVENT` is generated on the
+/// console of the child while the calling process ignores it. If the child has a console of its
+/// own, the calling process temporarily attaches to it and afterwards reattaches to the console
+/// of its parent. The event reaches every process attached to the console of the child, and the
+/// child must not have been created with `CREATE_NEW_PROCESS_GROUP`, which disables Ctrl-C.
+///
+/// This changes process wide console state while it runs, which makes it best suited to
+/// integration tests.
+///
+/// # Example
+/// ```no_run
+/// use std::process::Command;
+///
+/// let mut child = Command::new("my-cli").spawn().unwrap();
+/// ctrlc::testing::send_interrupt(&child).unwrap();
+/// let status = child.wait().unwrap();
+/// ```
+///
+/// # Errors
+/// Will return an error if the interrupt could not be delivered, e.g. because the child
+/// already exited.
+pub fn send_interrupt(child: &Child) -> io::Result<()> {
+    unsafe { imp::send_interrupt(child.id()) }
+}
+
+#[cfg(all(unix, not(target_os = "espidf")))]
+mod imp {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    use std::io;
+
+    pub unsafe fn send_interrupt(pid: u32) -> io::Result<()> {
+        kill(Pid::from_raw(pid as i32), Signal::SIGINT).map_err(io::Error::from)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+mod imp {
+    use std::io;
+
+    pub unsafe fn send_interrupt(_pid: u32) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "processes can't be interrupted on ESP-IDF",
+        ))
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use crate::platform;
+    use std::io;
+    use std::time::Duration;
+    use windows_sys::Win32::System::Console::{
+        GenerateConsoleCtrlEvent, SetConsoleCtrlHandler, CTRL_C_EVENT,
+    };
+
+    /// How long to keep ignoring Ctrl-C after generating it. The event is delivered to the
+    /// attached processes asynchronously.
+    const DELIVERY_DELAY: Duration = Duration::from_millis(100);
+
+    pub unsafe fn send_interrupt(pid: u32) -> io::Result<()> {
+        platform::with_console_of(pid, || {
+            // Ignore the event ourselves, we are attached to the same console.
+            SetConsoleCtrlHandler(None, 1);
+            let result = if GenerateConsoleCtrlEvent(CTRL_C_EVENT, 0) == 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(())
+            };
+            std::thread::sleep(DELIVERY_DELAY);
+            SetConsoleCtrlHandler(None, 0);
+            result
+        })
+    }
+}
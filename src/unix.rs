@@ -0,0 +1,766 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Unix specific functionality.
+
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::UnixDatagram;
+
+/// Send a notification such as `STATUS=Draining connections` or `STOPPING=1` to systemd.
+///
+/// Implements the `sd_notify(3)` protocol over the socket given by `NOTIFY_SOCKET`. Returns
+/// `Ok(false)` without doing anything if the variable is not set, i.e. the process is not
+/// running as a systemd service with `Type=notify`.
+///
+/// # Errors
+/// Will return an error if the notification could not be sent.
+pub fn sd_notify(state: &str) -> io::Result<bool> {
+    let path = match std::env::var_os("NOTIFY_SOCKET") {
+        Some(path) if !path.is_empty() => path,
+        _ => return Ok(false),
+    };
+
+    match path.as_bytes().strip_prefix(b"@") {
+        Some(name) => send_abstract(name, state.as_bytes())?,
+        None => {
+            UnixDatagram::unbound()?.send_to(state.as_bytes(), &path)?;
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn send_abstract(name: &[u8], msg: &[u8]) -> io::Result<()> {
+    use nix::sys::socket::{self, AddressFamily, MsgFlags, SockFlag, SockType, UnixAddr};
+    use std::os::fd::AsRawFd;
+
+    let addr = UnixAddr::new_abstract(name)?;
+    let fd = socket::socket(
+        AddressFamily::Unix,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )?;
+    socket::sendto(fd.as_raw_fd(), msg, &addr, MsgFlags::empty())?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn send_abstract(_name: &[u8], _msg: &[u8]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "abstract socket addresses are only supported on Linux",
+    ))
+}
+
+/// A system call blocked by a seccomp filter with `SECCOMP_RET_TRAP`.
+///
+/// Passed to the callback registered with [`on_seccomp_violation()`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeccompViolation {
+    syscall: i32,
+    arch: u32,
+    call_addr: usize,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl SeccompViolation {
+    /// Number of the blocked system call.
+    pub fn syscall(&self) -> i32 {
+        self.syscall
+    }
+
+    /// `AUDIT_ARCH_*` value of the calling convention used for the system call.
+    pub fn arch(&self) -> u32 {
+        self.arch
+    }
+
+    /// Address of the instruction that made the system call.
+    pub fn call_addr(&self) -> usize {
+        self.call_addr
+    }
+}
+
+/// Layout of `siginfo_t` for `SIGSYS`, which the libc crate does not expose.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct SigsysInfo {
+    si_signo: nix::libc::c_int,
+    si_errno: nix::libc::c_int,
+    si_code: nix::libc::c_int,
+    call_addr: *mut nix::libc::c_void,
+    syscall: nix::libc::c_int,
+    arch: nix::libc::c_uint,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static SECCOMP_CALLBACK: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Call `callback` when a seccomp filter blocks a system call, before the default action of
+/// `SIGSYS` terminates the process with a core dump.
+///
+/// The callback runs inside the signal handler, so it must only do async-signal-safe work: no
+/// allocation, locking or `println!`. [`seccomp_violation_to_stderr`] is such a callback.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::unix::on_seccomp_violation(ctrlc::unix::seccomp_violation_to_stderr)
+///     .expect("Error setting SIGSYS handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn on_seccomp_violation(callback: fn(&SeccompViolation)) -> io::Result<()> {
+    use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+    use std::sync::atomic::Ordering;
+
+    SECCOMP_CALLBACK.store(callback as usize, Ordering::SeqCst);
+    let action = SigAction::new(
+        SigHandler::SigAction(sigsys_handler),
+        SaFlags::SA_SIGINFO | SaFlags::SA_RESETHAND,
+        SigSet::empty(),
+    );
+    unsafe { signal::sigaction(Signal::SIGSYS, &action)? };
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+extern "C" fn sigsys_handler(
+    signum: nix::libc::c_int,
+    info: *mut nix::libc::siginfo_t,
+    _: *mut nix::libc::c_void,
+) {
+    use std::sync::atomic::Ordering;
+
+    let callback = SECCOMP_CALLBACK.load(Ordering::SeqCst);
+    if callback != 0 && !info.is_null() {
+        let callback: fn(&SeccompViolation) = unsafe { std::mem::transmute(callback) };
+        let info = unsafe { &*(info as *const SigsysInfo) };
+        callback(&SeccompViolation {
+            syscall: info.syscall,
+            arch: info.arch,
+            call_addr: info.call_addr as usize,
+        });
+    }
+
+    // SA_RESETHAND restored the default action, which runs once the handler returns and the
+    // signal is unblocked.
+    unsafe {
+        nix::libc::raise(signum);
+    }
+}
+
+/// [`on_seccomp_violation()`] callback writing the blocked system call number to stderr.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn seccomp_violation_to_stderr(violation: &SeccompViolation) {
+    let mut buf = [0u8; 96];
+    let mut len = 0;
+    for part in [
+        &b"[ctrlc] seccomp blocked syscall "[..],
+        format_decimal(&mut [0u8; 20], violation.syscall().unsigned_abs() as u64),
+        b" (arch 0x",
+        format_hex(&mut [0u8; 16], violation.arch() as u64),
+        b")\n",
+    ] {
+        buf[len..len + part.len()].copy_from_slice(part);
+        len += part.len();
+    }
+    unsafe {
+        nix::libc::write(nix::libc::STDERR_FILENO, buf.as_ptr().cast(), len);
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn format_decimal(buf: &mut [u8; 20], mut value: u64) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            return &buf[start..];
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn format_hex(buf: &mut [u8; 16], mut value: u64) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b"0123456789abcdef"[(value % 16) as usize];
+        value /= 16;
+        if value == 0 {
+            return &buf[start..];
+        }
+    }
+}
+
+type JobHook = std::sync::Arc<dyn Fn() + Send + Sync>;
+
+struct JobControl {
+    enabled: bool,
+    suspend: Vec<JobHook>,
+    resume: Vec<JobHook>,
+}
+
+static JOB_CONTROL: std::sync::Mutex<JobControl> = std::sync::Mutex::new(JobControl {
+    enabled: false,
+    suspend: Vec::new(),
+    resume: Vec::new(),
+});
+
+static JOB_CONTROL_FD: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(-1);
+
+fn job_control() -> std::sync::MutexGuard<'static, JobControl> {
+    JOB_CONTROL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Run `hook` before the process is suspended with `SIGTSTP`, e.g. to restore the terminal.
+///
+/// Hooks only run once [`enable_job_control()`] has been called. They run on a dedicated
+/// thread, in registration order.
+pub fn on_suspend<F>(hook: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    job_control().suspend.push(std::sync::Arc::new(hook));
+}
+
+/// Run `hook` after the process is resumed with `SIGCONT`, e.g. to re-enter raw mode and redraw.
+///
+/// Hooks only run once [`enable_job_control()`] has been called. They run on a dedicated
+/// thread, in registration order.
+pub fn on_resume<F>(hook: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    job_control().resume.push(std::sync::Arc::new(hook));
+}
+
+/// Manage job control: on `SIGTSTP` run the [`on_suspend()`] hooks and stop the process, on
+/// `SIGCONT` run the [`on_resume()`] hooks.
+///
+/// The process is stopped by raising `SIGTSTP` again with its default action, so the shell
+/// sees it stopped by `SIGTSTP` and, like without a handler, the signal is discarded in an
+/// orphaned process group that nobody could resume. The handler is put back once the process
+/// continues.
+///
+/// Calling this more than once has no effect.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::unix::on_suspend(|| println!("leaving raw mode"));
+/// ctrlc::unix::on_resume(|| println!("entering raw mode, redrawing"));
+/// ctrlc::unix::enable_job_control().expect("Error setting job control handlers");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handlers.
+pub fn enable_job_control() -> io::Result<()> {
+    use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+    use std::io::Read;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+    use std::sync::atomic::Ordering;
+
+    let mut state = job_control();
+    if state.enabled {
+        return Ok(());
+    }
+
+    let (mut rx, tx) = UnixStream::pair()?;
+    // Make sure we never block on write in the signal handler.
+    tx.set_nonblocking(true)?;
+
+    std::thread::Builder::new()
+        .name("ctrl-c-job-control".into())
+        .spawn(move || {
+            let mut buf = [0u8];
+            while rx.read_exact(&mut buf).is_ok() {
+                let (hooks, stop) = {
+                    let state = job_control();
+                    if buf[0] == b'T' {
+                        (state.suspend.clone(), true)
+                    } else {
+                        (state.resume.clone(), false)
+                    }
+                };
+                for hook in hooks {
+                    hook();
+                }
+                // The resume hooks run when SIGCONT arrives.
+                if stop {
+                    stop_process();
+                }
+            }
+        })?;
+
+    JOB_CONTROL_FD.store(tx.as_raw_fd(), Ordering::SeqCst);
+    // The write end lives as long as the process.
+    std::mem::forget(tx);
+
+    let action = SigAction::new(
+        SigHandler::Handler(job_control_handler),
+        SaFlags::SA_RESTART,
+        SigSet::empty(),
+    );
+    unsafe {
+        signal::sigaction(Signal::SIGTSTP, &action)?;
+        signal::sigaction(Signal::SIGCONT, &action)?;
+    }
+    state.enabled = true;
+    Ok(())
+}
+
+/// Stop the process with the default action of `SIGTSTP`, returns once it continues.
+fn stop_process() {
+    use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, SigmaskHow, Signal};
+
+    let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    let previous = unsafe { signal::sigaction(Signal::SIGTSTP, &default) };
+    let mut tstp = SigSet::empty();
+    tstp.add(Signal::SIGTSTP);
+    let _ = signal::pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&tstp), None);
+    // Stops this thread and with it the whole process until SIGCONT.
+    let _ = signal::raise(Signal::SIGTSTP);
+    if let Ok(previous) = previous {
+        let _ = unsafe { signal::sigaction(Signal::SIGTSTP, &previous) };
+    }
+}
+
+extern "C" fn job_control_handler(signum: nix::libc::c_int) {
+    use std::sync::atomic::Ordering;
+
+    let byte: u8 = if signum == nix::libc::SIGTSTP {
+        b'T'
+    } else {
+        b'C'
+    };
+    let fd = JOB_CONTROL_FD.load(Ordering::SeqCst);
+    unsafe {
+        nix::libc::write(fd, &byte as *const u8 as *const nix::libc::c_void, 1);
+    }
+}
+
+/// Run `handler` whenever `SIGUSR1` is received, e.g. to reopen log files after rotation.
+///
+/// Same as [`on(Signal::SIGUSR1, handler)`](crate::on): the handler runs on the signal handling
+/// thread and, unlike termination signals, never starts the shutdown.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::unix::on_usr1(|| println!("Reopening log files...")).unwrap();
+/// ctrlc::unix::on_usr2(|| println!("Dumping diagnostics...")).unwrap();
+/// ```
+///
+/// # Errors
+/// Same as [`on()`](crate::on).
+pub fn on_usr1<F>(handler: F) -> Result<crate::HandlerId, crate::Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    crate::on(crate::Signal::SIGUSR1, handler)
+}
+
+/// Run `handler` whenever `SIGUSR2` is received, e.g. to dump diagnostics, see [`on_usr1()`].
+///
+/// # Errors
+/// Same as [`on()`](crate::on).
+pub fn on_usr2<F>(handler: F) -> Result<crate::HandlerId, crate::Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    crate::on(crate::Signal::SIGUSR2, handler)
+}
+
+/// Treat `SIGHUP` as a request to reload the configuration: run `handler` whenever it is
+/// received instead of shutting down.
+///
+/// Daemons conventionally reload on `SIGHUP`. Like with [`on()`](crate::on), the handler takes
+/// the signal from the main handler, also when it handles [`SignalType::Termination`] or the
+/// default signals of the `termination` feature. After [`remove()`](crate::remove)ing the
+/// handler, `SIGHUP` starts the shutdown again if the main handler handles it.
+///
+/// [`SignalType::Termination`]: crate::SignalType::Termination
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Shutting down...")).expect("Error setting Ctrl-C handler");
+/// ctrlc::unix::on_reload(|| println!("Reloading configuration...")).unwrap();
+/// ```
+///
+/// # Errors
+/// Same as [`on()`](crate::on).
+pub fn on_reload<F>(handler: F) -> Result<crate::HandlerId, crate::Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    crate::on(crate::Signal::SIGHUP, handler)
+}
+
+/// Run `handler` whenever `SIGCHLD` is received, i.e. a child process exited or stopped.
+///
+/// Signals of several children may arrive as one, so collect them all with
+/// [`reap_children()`] in the handler.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::unix::on_child_exit(|| {
+///     for (pid, status) in ctrlc::unix::reap_children() {
+///         println!("Worker {} exited with {}", pid, status);
+///     }
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Errors
+/// Same as [`on()`](crate::on).
+pub fn on_child_exit<F>(handler: F) -> Result<crate::HandlerId, crate::Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    crate::on(crate::Signal::SIGCHLD, handler)
+}
+
+/// Reap every child process that exited, without blocking. Returns their process ids and exit
+/// statuses.
+///
+/// Calls `waitpid(-1, WNOHANG)` until no exited child is left, so it also reaps children
+/// spawned with [`std::process::Command`], whose [`wait()`](std::process::Child::wait) then
+/// fails.
+pub fn reap_children() -> Vec<(u32, std::process::ExitStatus)> {
+    use nix::libc;
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut reaped = Vec::new();
+    loop {
+        let mut status = 0;
+        let pid = unsafe { libc::waitpid(-1, &mut status, libc::WNOHANG) };
+        if pid > 0 {
+            reaped.push((pid as u32, std::process::ExitStatus::from_raw(status)));
+        } else if pid == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            // No exited child left, or no children at all.
+            return reaped;
+        }
+    }
+}
+
+/// Run `handler` whenever `SIGINFO` is received, i.e. the user pressed Ctrl-T to ask for the
+/// status of a long running command, like `dd` and `fetch` print their progress.
+///
+/// `SIGINFO` only exists on the BSDs and Apple platforms.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+///
+/// let copied = Arc::new(AtomicU64::new(0));
+/// ctrlc::unix::on_status_request({
+///     let copied = copied.clone();
+///     move || eprintln!("{} bytes copied", copied.load(Ordering::Relaxed))
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Errors
+/// Same as [`on()`](crate::on).
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub fn on_status_request<F>(handler: F) -> Result<crate::HandlerId, crate::Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    crate::on(crate::Signal::SIGINFO, handler)
+}
+
+/// A real-time signal passed to the handler registered with [`on_realtime()`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RealtimeSignal {
+    offset: i32,
+    value: usize,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RealtimeSignal {
+    /// Number of the signal above `SIGRTMIN`, as passed to [`on_realtime()`].
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// The integer sent with `sigqueue(3)`, 0 if the signal was sent with `kill(2)`.
+    pub fn int(&self) -> i32 {
+        let bytes = self.value.to_ne_bytes();
+        i32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// The pointer sent with `sigqueue(3)` as an address, only meaningful in the sending process.
+    pub fn ptr(&self) -> usize {
+        self.value
+    }
+}
+
+/// Layout of `siginfo_t` for signals sent with `sigqueue(3)`, which the libc crate does not
+/// expose on all targets.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct SigqueueInfo {
+    si_signo: nix::libc::c_int,
+    si_errno: nix::libc::c_int,
+    si_code: nix::libc::c_int,
+    // A struct of its own, so it gets the alignment of the union in `siginfo_t`.
+    rt: SigqueueFields,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct SigqueueFields {
+    si_pid: nix::libc::pid_t,
+    si_uid: nix::libc::uid_t,
+    si_value: *mut nix::libc::c_void,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+type RealtimeHandler = std::sync::Arc<std::sync::Mutex<dyn FnMut(RealtimeSignal) + Send>>;
+
+/// Handlers registered with [`on_realtime()`] by signal number.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static REALTIME: std::sync::Mutex<Vec<(nix::libc::c_int, RealtimeHandler)>> =
+    std::sync::Mutex::new(Vec::new());
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static REALTIME_FD: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(-1);
+
+/// A signal number followed by the value sent with it.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const REALTIME_RECORD: usize = 4 + std::mem::size_of::<usize>();
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn realtime() -> std::sync::MutexGuard<'static, Vec<(nix::libc::c_int, RealtimeHandler)>> {
+    REALTIME.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Run `handler` whenever the real-time signal `SIGRTMIN + offset` is received, with the value
+/// sent along with `sigqueue(3)`.
+///
+/// Unlike standard signals, real-time signals are queued, so every one sent runs the handler.
+/// Handlers run on a dedicated thread, in registration order. The OS handler is installed with
+/// `SA_SIGINFO` for the first handler of a signal and stays installed for the rest of the
+/// process.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::unix::on_realtime(1, |signal| println!("Cancelling task {}", signal.int()))
+///     .expect("Error setting real-time signal handler");
+/// ```
+///
+/// # Errors
+/// Will return an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) if
+/// `SIGRTMIN + offset` is no real-time signal, or an error if a system error occurred while
+/// setting the handler.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn on_realtime<F>(offset: i32, handler: F) -> io::Result<()>
+where
+    F: FnMut(RealtimeSignal) + Send + 'static,
+{
+    use nix::libc;
+    use std::os::unix::io::IntoRawFd;
+    use std::sync::atomic::Ordering;
+
+    let signum = libc::SIGRTMIN().saturating_add(offset);
+    if offset < 0 || signum > libc::SIGRTMAX() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no such real-time signal",
+        ));
+    }
+
+    let mut handlers = realtime();
+    if REALTIME_FD.load(Ordering::SeqCst) == -1 {
+        let (rx, tx) = UnixDatagram::pair()?;
+        // Make sure we never block on send in the signal handler.
+        tx.set_nonblocking(true)?;
+
+        std::thread::Builder::new()
+            .name("ctrl-c-realtime".into())
+            .spawn(move || {
+                let mut buf = [0u8; REALTIME_RECORD];
+                while let Ok(len) = rx.recv(&mut buf) {
+                    if len != REALTIME_RECORD {
+                        continue;
+                    }
+                    let (number, value) = buf.split_at(4);
+                    let signum = libc::c_int::from_ne_bytes(number.try_into().unwrap());
+                    let signal = RealtimeSignal {
+                        offset: signum - libc::SIGRTMIN(),
+                        value: usize::from_ne_bytes(value.try_into().unwrap()),
+                    };
+                    let matching: Vec<_> = realtime()
+                        .iter()
+                        .filter(|(registered, _)| *registered == signum)
+                        .map(|(_, handler)| handler.clone())
+                        .collect();
+                    for handler in matching {
+                        (*handler.lock().unwrap_or_else(|e| e.into_inner()))(signal);
+                    }
+                }
+            })?;
+
+        // The write end lives as long as the process.
+        REALTIME_FD.store(tx.into_raw_fd(), Ordering::SeqCst);
+    }
+
+    if !handlers.iter().any(|(registered, _)| *registered == signum) {
+        let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+        action.sa_sigaction = realtime_handler as *const () as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+        unsafe {
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(signum, &action, std::ptr::null_mut()) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+    handlers.push((signum, std::sync::Arc::new(std::sync::Mutex::new(handler))));
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+extern "C" fn realtime_handler(
+    signum: nix::libc::c_int,
+    info: *mut nix::libc::siginfo_t,
+    _: *mut nix::libc::c_void,
+) {
+    use std::sync::atomic::Ordering;
+
+    let value = if info.is_null() {
+        0
+    } else {
+        unsafe { (*(info as *const SigqueueInfo)).rt.si_value as usize }
+    };
+    let mut record = [0u8; REALTIME_RECORD];
+    record[..4].copy_from_slice(&signum.to_ne_bytes());
+    record[4..].copy_from_slice(&value.to_ne_bytes());
+    let fd = REALTIME_FD.load(Ordering::SeqCst);
+    unsafe {
+        nix::libc::write(fd, record.as_ptr().cast(), record.len());
+    }
+}
+
+/// The notification descriptor of the low-level signal handling layer, to wait for signals in
+/// an event loop of your own, with `epoll`, `poll` or `select`.
+///
+/// Like a [`Handle`](crate::Handle), which it wraps, creating a `SignalNotifier` installs the OS
+/// handler without a handler thread and dropping it removes the OS handler again. Its
+/// descriptor is readable while a signal waits to be taken with
+/// [`try_recv()`](#method.try_recv), level-triggered, and stays readable once the handler was
+/// removed. Only the self-pipe and the `eventfd` feature provide one: with the
+/// `posix-semaphore`, `signalfd` or `kqueue` features [`new()`](#method.new) fails with
+/// `ENOTSUP`.
+///
+/// With the `mio` feature it is a `mio::event::Source`. mio reports readiness edge-triggered, so
+/// take signals with [`try_recv()`](#method.try_recv) until it returns `None` after each event.
+///
+/// # Example
+/// ```no_run
+/// use std::os::fd::AsRawFd;
+///
+/// let notifier = ctrlc::unix::SignalNotifier::new(&ctrlc::default_signals(), true)
+///     .expect("Error setting Ctrl-C handler");
+///
+/// // Register `notifier.as_raw_fd()` for reading with the event loop, then once it is readable:
+/// while let Some(event) = notifier.try_recv().expect("Error taking Ctrl-C") {
+///     println!("Got {:?}", event.signal());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SignalNotifier {
+    handle: crate::Handle,
+    fd: std::os::fd::OwnedFd,
+}
+
+impl SignalNotifier {
+    /// Install the OS handler for `signals`, see [`Handle::new()`](crate::Handle::new).
+    ///
+    /// # Errors
+    /// Same as [`Handle::new()`](crate::Handle::new), and an error if the channel has no
+    /// notification descriptor.
+    #[track_caller]
+    pub fn new<P>(signals: &[crate::Signal], policy: P) -> Result<SignalNotifier, crate::Error>
+    where
+        P: Into<crate::InstallPolicy>,
+    {
+        let handle = crate::Handle::new(signals, policy)?;
+        let fd = handle.notification_fd()?;
+        Ok(SignalNotifier { handle, fd })
+    }
+
+    /// Take a signal already received without blocking, `None` if there is none, see
+    /// [`Handle::try_recv()`](crate::Handle::try_recv).
+    ///
+    /// # Errors
+    /// Same as [`Handle::try_recv()`](crate::Handle::try_recv).
+    pub fn try_recv(&self) -> Result<Option<crate::SignalEvent>, crate::Error> {
+        self.handle.try_recv()
+    }
+}
+
+impl std::os::fd::AsFd for SignalNotifier {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl std::os::fd::AsRawFd for SignalNotifier {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "mio")]
+impl mio::event::Source for SignalNotifier {
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        mio::unix::SourceFd(&self.fd.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        mio::unix::SourceFd(&self.fd.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        mio::unix::SourceFd(&self.fd.as_raw_fd()).deregister(registry)
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use arc_swap::ArcSwapOption;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+trait WeakHandler: Send {
+    /// Run the handler, returns `false` if the owner is gone.
+    fn call(&mut self) -> bool;
+
+    fn is_alive(&self) -> bool;
+}
+
+struct Entry<T, F> {
+    owner: Weak<T>,
+    handler: F,
+}
+
+impl<T, F> WeakHandler for Entry<T, F>
+where
+    T: Send + Sync,
+    F: FnMut(&T) + Send,
+{
+    fn call(&mut self) -> bool {
+        match self.owner.upgrade() {
+            Some(owner) => {
+                (self.handler)(&owner);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.owner.strong_count() > 0
+    }
+}
+
+type Handler = Arc<Mutex<dyn WeakHandler>>;
+
+// The dispatch loads the current snapshot of the handlers without locking the registry. Writers
+// serialize on `WRITER` and publish a new snapshot.
+static REGISTRY: ArcSwapOption<Vec<Handler>> = ArcSwapOption::const_empty();
+static WRITER: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    WRITER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The current handlers for which `keep` returns `true`.
+fn retain<F>(keep: F) -> Vec<Handler>
+where
+    F: Fn(&Handler) -> bool,
+{
+    REGISTRY.load_full().map_or_else(Vec::new, |handlers| {
+        handlers
+            .iter()
+            .filter(|&handler| keep(handler))
+            .cloned()
+            .collect()
+    })
+}
+
+/// Run `handler` on every signal for as long as `owner` is alive.
+///
+/// Only a weak reference to `owner` is kept: once its last strong reference is dropped, the
+/// handler is skipped and removed. Subsystems that are torn down therefore don't leave shutdown
+/// closures behind that capture dead state.
+///
+/// Handlers registered this way run on the handler thread, in registration order, after the
+/// handler passed to [`set_handler()`](fn.set_handler.html) or [`Builder`](struct.Builder.html).
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// struct Cache;
+///
+/// impl Cache {
+///     fn flush(&self) {}
+/// }
+///
+/// let cache = Arc::new(Cache);
+/// ctrlc::register_weak(&cache, |cache| cache.flush());
+/// ctrlc::set_handler(|| {}).expect("Error setting Ctrl-C handler");
+///
+/// // The handler is removed once `cache` is dropped.
+/// drop(cache);
+/// ```
+pub fn register_weak<T, F>(owner: &Arc<T>, handler: F)
+where
+    T: Send + Sync + 'static,
+    F: FnMut(&T) + Send + 'static,
+{
+    let _writer = lock();
+    // A handler that is running is alive, and may be the one registering.
+    let mut handlers = retain(|handler| handler.try_lock().map_or(true, |h| h.is_alive()));
+    handlers.push(Arc::new(Mutex::new(Entry {
+        owner: Arc::downgrade(owner),
+        handler,
+    })));
+    REGISTRY.store(Some(Arc::new(handlers)));
+}
+
+/// Run the handlers whose owner is alive and drop the others.
+pub(crate) fn dispatch() {
+    let handlers = match REGISTRY.load_full() {
+        Some(handlers) => handlers,
+        None => return,
+    };
+    let gone: Vec<Handler> = handlers
+        .iter()
+        .filter(|handler| !handler.lock().unwrap_or_else(|e| e.into_inner()).call())
+        .cloned()
+        .collect();
+    if gone.is_empty() {
+        return;
+    }
+    // Handlers may have registered new handlers meanwhile, keep those.
+    let _writer = lock();
+    let handlers = retain(|handler| !gone.iter().any(|gone| Arc::ptr_eq(gone, handler)));
+    REGISTRY.store(Some(Arc::new(handlers)));
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Windows specific functionality.
+
+use crate::platform;
+use std::io;
+use std::process::Child;
+
+/// Whether the process runs inside mintty (Git Bash, MSYS2, Cygwin) without a console.
+///
+/// mintty connects native programs to a Cygwin pty made of named pipes instead of a console, so
+/// `SetConsoleCtrlHandler` never fires: Ctrl-C makes the Cygwin runtime terminate the process
+/// instead. Cygwin signals are only emulated for Cygwin programs, so a native program has
+/// nothing to intercept and ctrlc provides no fallback: when this returns `true` the handler
+/// will not run. Applications can use it to warn the user and
+/// suggest one of the workarounds:
+///
+/// * run the program through `winpty`, which provides a hidden console, or
+/// * enable pseudo console support (`MSYS=enable_pcon` / `CYGWIN=enable_pcon`, the default in
+///   recent MSYS2 and Git for Windows releases), where Ctrl-C arrives as a regular
+///   `CTRL_C_EVENT`.
+///
+/// Both workarounds attach a console, so this returns `false` when they are in effect.
+///
+/// # Example
+/// ```no_run
+/// if ctrlc::windows::is_mintty() {
+///     eprintln!("warning: Ctrl-C handling is not available in mintty, run through winpty");
+/// }
+/// ```
+pub fn is_mintty() -> bool {
+    unsafe { platform::is_msys_pty() && !platform::has_console() }
+}
+
+/// Restores Ctrl-C processing when dropped, see [`ignore_ctrl_c()`] and [`literal_ctrl_c()`].
+#[derive(Debug)]
+#[must_use = "Ctrl-C processing is restored when the guard is dropped"]
+pub struct CtrlCGuard {
+    restore: Restore,
+}
+
+#[derive(Debug)]
+enum Restore {
+    Ignore,
+    ConsoleMode(u32),
+}
+
+impl Drop for CtrlCGuard {
+    fn drop(&mut self) {
+        let _ = unsafe {
+            match self.restore {
+                Restore::Ignore => platform::set_ignore_ctrl_c(false),
+                Restore::ConsoleMode(mode) => platform::set_console_mode(mode),
+            }
+        };
+    }
+}
+
+/// Ignore Ctrl-C in this process until the guard is dropped.
+///
+/// Uses `SetConsoleCtrlHandler(NULL, TRUE)`: no `CTRL_C_EVENT` reaches the handlers, while
+/// `CTRL_BREAK_EVENT` and the other events still do. The setting is inherited by child
+/// processes started meanwhile.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn ignore_ctrl_c() -> io::Result<CtrlCGuard> {
+    unsafe { platform::set_ignore_ctrl_c(true)? };
+    Ok(CtrlCGuard {
+        restore: Restore::Ignore,
+    })
+}
+
+/// Read Ctrl-C as a literal input character (`\x03`) until the guard is dropped.
+///
+/// Clears `ENABLE_PROCESSED_INPUT` on the console input, e.g. for an interactive sub-prompt
+/// that handles Ctrl-C itself. The previous console mode is restored afterwards.
+///
+/// # Example
+/// ```no_run
+/// let guard = ctrlc::windows::literal_ctrl_c().expect("stdin is not a console");
+/// // ... read keys, Ctrl-C arrives as '\x03' ...
+/// drop(guard);
+/// ```
+///
+/// # Errors
+/// Will return an error if stdin is not a console or a system error occurred.
+pub fn literal_ctrl_c() -> io::Result<CtrlCGuard> {
+    let mode = unsafe { platform::set_processed_input(false)? };
+    Ok(CtrlCGuard {
+        restore: Restore::ConsoleMode(mode),
+    })
+}
+
+/// Send `CTRL_BREAK_EVENT` to `child`, the Windows counterpart of sending `SIGINT` to a child
+/// on Unix for cooperative cancellation.
+///
+/// The child must have been spawned with `CREATE_NEW_PROCESS_GROUP`, the event is delivered to
+/// its process group. Unlike `CTRL_C_EVENT`, `CTRL_BREAK_EVENT` can't be disabled by the child.
+/// Children with their own console are reached by temporarily attaching to it.
+///
+/// # Example
+/// ```no_run
+/// use std::os::windows::process::CommandExt;
+/// use std::process::Command;
+///
+/// const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+///
+/// let mut child = Command::new("worker.exe")
+///     .creation_flags(CREATE_NEW_PROCESS_GROUP)
+///     .spawn()
+///     .unwrap();
+/// ctrlc::windows::break_child(&child).unwrap();
+/// child.wait().unwrap();
+/// ```
+///
+/// # Errors
+/// Will return an error if the event could not be delivered, e.g. because the child already
+/// exited.
+pub fn break_child(child: &Child) -> io::Result<()> {
+    unsafe { platform::send_ctrl_break(child.id()) }
+}
@@ -7,10 +7,185 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+// The signalfd and sigwait backends only see the signals sent to the process or to the thread
+// reading them, `raise_ctrl_c()` leaves them pending on the calling thread. They have their own
+// tests.
+#![cfg_attr(
+    any(ctrlc_hook = "signalfd", ctrlc_hook = "sigwait"),
+    allow(dead_code, unused_imports, unused_macros)
+)]
+
 #[macro_use]
 mod harness;
 use harness::{platform, run_harness};
 
+mod test_env;
+use test_env::test_env_overrides;
+
+mod test_preset;
+use test_preset::test_kubernetes_preset;
+
+mod test_signal_info;
+use test_signal_info::{test_portable_signal, test_signal_info};
+
+mod test_capabilities;
+use test_capabilities::test_capabilities;
+
+mod test_ack;
+use test_ack::test_ack_barrier;
+
+mod test_progress;
+use test_progress::test_progress_reporting;
+
+mod test_prearm;
+use test_prearm::test_prearm;
+
+mod test_missed;
+#[cfg(all(unix, ctrlc_channel = "pipe", not(ctrlc_eventfd)))]
+use test_missed::test_missed_signals;
+
+mod test_notifier;
+#[cfg(all(unix, ctrlc_channel = "pipe", feature = "tokio"))]
+use test_notifier::test_async_ctrl_c;
+#[cfg(all(unix, ctrlc_channel = "pipe", feature = "async-io"))]
+use test_notifier::test_async_io_notifier;
+#[cfg(all(unix, ctrlc_channel = "pipe", feature = "mio"))]
+use test_notifier::test_mio_notifier;
+#[cfg(all(unix, ctrlc_channel = "pipe"))]
+use test_notifier::test_signal_notifier;
+
+mod test_shutdown;
+use test_shutdown::test_shutdown_controller;
+
+mod test_macos;
+#[cfg(all(target_os = "macos", feature = "macos-app"))]
+use test_macos::test_terminate_reply;
+
+mod test_windows;
+#[cfg(windows)]
+use test_windows::{test_ignore_ctrl_c, test_literal_ctrl_c};
+
+mod test_serde;
+#[cfg(feature = "serde")]
+use test_serde::{test_serde, test_serde_signal};
+
+mod test_handle;
+use test_handle::{test_handle, test_handle_drop_after_remove, test_pump};
+#[cfg(unix)]
+use test_handle::{test_resize, test_sender};
+
+mod test_nohup;
+#[cfg(unix)]
+use test_nohup::test_nohup;
+
+mod test_add_signal;
+#[cfg(unix)]
+use test_add_signal::{test_add_signal, test_add_signal_no_shutdown, test_builder_add_signal};
+
+mod test_guard;
+use test_guard::{test_channel, test_handler_guard, test_with_handler};
+#[cfg(all(unix, not(feature = "signal-hook-registry")))]
+use test_guard::{test_guard_restores_action, test_remove_restores_action};
+
+mod test_install_policy;
+// kqueue ignores the signals instead of installing a handler, so nothing is chained.
+#[cfg(all(
+    unix,
+    not(feature = "signal-hook-registry"),
+    not(ctrlc_hook = "kqueue")
+))]
+use test_install_policy::test_chain;
+#[cfg(all(unix, not(feature = "signal-hook-registry")))]
+use test_install_policy::test_replace_if_default;
+
+mod test_registry;
+#[cfg(all(unix, feature = "signal-hook-registry"))]
+use test_registry::test_registry;
+
+mod test_error;
+use test_error::test_io_error;
+
+mod test_handlers;
+use test_handlers::test_handlers;
+
+mod test_priority;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use test_priority::test_thread_scheduling;
+
+mod test_semaphore;
+#[cfg(ctrlc_channel = "semaphore")]
+use test_semaphore::{test_semaphore_channel, test_semaphore_reinit};
+
+mod test_reinit;
+use test_reinit::{
+    test_reinit, test_reinit_concurrent, test_reinit_resets_shutdown, test_remove_wakes_waiter,
+};
+
+mod test_routes;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use test_routes::test_on_realtime;
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+use test_routes::test_on_status_request;
+#[cfg(unix)]
+use test_routes::{
+    test_add_handler, test_on_before_set_handler, test_on_reload, test_on_signal, test_on_usr,
+    test_reap_children, test_remove_route,
+};
+
+mod test_consumer;
+use test_consumer::test_consumer_stack;
+
+mod test_ffi;
+#[cfg(feature = "ffi")]
+use test_ffi::test_ffi_flag;
+
+mod test_listener;
+#[cfg(feature = "crossbeam")]
+use test_listener::test_channel_crossbeam;
+#[cfg(feature = "futures")]
+use test_listener::test_signal_stream;
+use test_listener::{test_signal_listener, test_signals, test_wait_for_count};
+
+mod test_delivery;
+use test_delivery::{test_delivery_coalesced, test_delivery_concurrent};
+
+mod test_external;
+use test_external::test_external;
+
+mod test_with_signal;
+use test_with_signal::test_handler_with_signal;
+#[cfg(unix)]
+use test_with_signal::test_signal_burst;
+
+mod test_filter;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use test_filter::test_sender_names;
+use test_filter::{test_filter, test_filter_rejected_shutdown};
+
+mod test_inflight;
+use test_inflight::test_inflight;
+
+mod test_complete;
+use test_complete::test_shutdown_complete;
+
+mod test_weak;
+use test_weak::test_register_weak;
+
+mod test_cleanup;
+use test_cleanup::test_on_shutdown;
+
+mod test_orchestrator;
+#[cfg(feature = "tokio")]
+use test_orchestrator::test_orchestrator_async;
+use test_orchestrator::{test_orchestrator_order, test_orchestrator_timeout};
+
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -24,6 +199,15 @@ fn test_set_handler() {
     })
     .unwrap();
 
+    let handlers = ctrlc::handlers();
+    assert_eq!(handlers.len(), 1);
+    assert!(!handlers[0].is_once());
+    assert!(!handlers[0].signals().is_empty());
+    if cfg!(debug_assertions) {
+        let location = handlers[0].location().unwrap();
+        assert!(location.file().ends_with("mod.rs"), "{}", location);
+    }
+
     unsafe {
         platform::raise_ctrl_c();
     }
@@ -38,9 +222,136 @@ fn test_set_handler() {
 }
 
 fn tests() {
+    run_tests!(test_env_overrides);
+    run_tests!(test_signal_info);
+    run_tests!(test_portable_signal);
+    run_tests!(test_capabilities);
+    run_tests!(test_ack_barrier);
+    run_tests!(test_progress_reporting);
+    run_tests!(test_orchestrator_order);
+    run_tests!(test_orchestrator_timeout);
+    run_tests!(test_on_shutdown);
+    #[cfg(feature = "tokio")]
+    run_tests!(test_orchestrator_async);
+    #[cfg(feature = "serde")]
+    run_tests!(test_serde);
+    #[cfg(feature = "serde")]
+    run_tests!(test_serde_signal);
+    run_tests!(test_handle);
+    run_tests!(test_handle_drop_after_remove);
+    run_tests!(test_pump);
+    #[cfg(unix)]
+    run_tests!(test_resize);
+    #[cfg(unix)]
+    run_tests!(test_sender);
+    #[cfg(all(unix, ctrlc_channel = "pipe"))]
+    run_tests!(test_signal_notifier);
+    #[cfg(all(unix, ctrlc_channel = "pipe", feature = "mio"))]
+    run_tests!(test_mio_notifier);
+    #[cfg(all(unix, ctrlc_channel = "pipe", feature = "tokio"))]
+    run_tests!(test_async_ctrl_c);
+    #[cfg(all(unix, ctrlc_channel = "pipe", feature = "async-io"))]
+    run_tests!(test_async_io_notifier);
+    #[cfg(unix)]
+    run_tests!(test_nohup);
+    #[cfg(unix)]
+    run_tests!(test_add_signal);
+    #[cfg(unix)]
+    run_tests!(test_builder_add_signal);
+    #[cfg(unix)]
+    run_tests!(test_add_signal_no_shutdown);
+    run_tests!(test_handler_guard);
+    run_tests!(test_with_handler);
+    run_tests!(test_channel);
+    #[cfg(all(unix, not(feature = "signal-hook-registry")))]
+    run_tests!(test_guard_restores_action);
+    #[cfg(all(unix, not(feature = "signal-hook-registry")))]
+    run_tests!(test_remove_restores_action);
+    #[cfg(all(
+        unix,
+        not(feature = "signal-hook-registry"),
+        not(ctrlc_hook = "kqueue")
+    ))]
+    run_tests!(test_chain);
+    #[cfg(all(unix, not(feature = "signal-hook-registry")))]
+    run_tests!(test_replace_if_default);
+    #[cfg(all(unix, feature = "signal-hook-registry"))]
+    run_tests!(test_registry);
+    #[cfg(unix)]
+    run_tests!(test_on_signal);
+    #[cfg(unix)]
+    run_tests!(test_add_handler);
+    #[cfg(unix)]
+    run_tests!(test_remove_route);
+    #[cfg(unix)]
+    run_tests!(test_on_before_set_handler);
+    #[cfg(unix)]
+    run_tests!(test_on_usr);
+    #[cfg(unix)]
+    run_tests!(test_on_reload);
+    #[cfg(unix)]
+    run_tests!(test_reap_children);
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    run_tests!(test_on_status_request);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    run_tests!(test_on_realtime);
+    run_tests!(test_io_error);
+    run_tests!(test_handlers);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    run_tests!(test_thread_scheduling);
+    run_tests!(test_reinit);
+    run_tests!(test_reinit_resets_shutdown);
+    run_tests!(test_reinit_concurrent);
+    #[cfg(ctrlc_channel = "semaphore")]
+    run_tests!(test_semaphore_channel);
+    #[cfg(ctrlc_channel = "semaphore")]
+    run_tests!(test_semaphore_reinit);
+    run_tests!(test_remove_wakes_waiter);
+    run_tests!(test_consumer_stack);
+    #[cfg(feature = "ffi")]
+    run_tests!(test_ffi_flag);
+    run_tests!(test_signal_listener);
+    run_tests!(test_wait_for_count);
+    run_tests!(test_signals);
+    #[cfg(feature = "futures")]
+    run_tests!(test_signal_stream);
+    #[cfg(feature = "crossbeam")]
+    run_tests!(test_channel_crossbeam);
+    run_tests!(test_delivery_coalesced);
+    run_tests!(test_delivery_concurrent);
+    run_tests!(test_external);
+    run_tests!(test_handler_with_signal);
+    #[cfg(unix)]
+    run_tests!(test_signal_burst);
+    run_tests!(test_filter);
+    run_tests!(test_filter_rejected_shutdown);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    run_tests!(test_sender_names);
+    run_tests!(test_register_weak);
+    run_tests!(test_inflight);
+    run_tests!(test_shutdown_complete);
+    run_tests!(test_prearm);
+    #[cfg(all(unix, ctrlc_channel = "pipe", not(ctrlc_eventfd)))]
+    run_tests!(test_missed_signals);
+    run_tests!(test_shutdown_controller);
+    #[cfg(all(target_os = "macos", feature = "macos-app"))]
+    run_tests!(test_terminate_reply);
+    #[cfg(windows)]
+    run_tests!(test_ignore_ctrl_c);
+    #[cfg(windows)]
+    run_tests!(test_literal_ctrl_c);
+    run_tests!(test_kubernetes_preset);
     run_tests!(test_set_handler);
 }
 
 fn main() {
+    #[cfg(not(any(ctrlc_hook = "signalfd", ctrlc_hook = "sigwait")))]
     run_harness(tests);
 }
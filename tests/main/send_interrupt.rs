@@ -0,0 +1,785 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+#[allow(dead_code)]
+mod harness;
+use harness::run_harness;
+
+use ctrlc::{EscalationStage, ExitMechanism, LifecycleState};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+const CHILD_ENV: &str = "CTRLC_TEST_CHILD";
+const CHILD_EXIT_CODE: i32 = 42;
+const CHILD_TIMEOUT_EXIT_CODE: i32 = 43;
+
+/// Runs in the spawned child: exit with a known code on Ctrl-C.
+fn child(mode: &str) {
+    if mode == "force-quit" {
+        ctrlc::Builder::new()
+            .force_after(3)
+            .grace_period(Duration::from_secs(5))
+            .force_quit_message("{remaining} more to force quit, {grace} grace")
+            .set_handler(|| std::process::exit(CHILD_EXIT_CODE))
+            .unwrap();
+    } else if mode == "storm" {
+        // A wedged handler, only storm protection ends the process.
+        ctrlc::Builder::new()
+            .storm_protection(3, Duration::from_secs(5))
+            .exit_mechanism(EscalationStage::Storm, ExitMechanism::Immediate)
+            .set_handler(|| std::thread::sleep(Duration::from_secs(10)))
+            .unwrap();
+    } else if mode == "repeat" {
+        // A hanging graceful shutdown, only the repeated signal ends the process.
+        ctrlc::Builder::new()
+            .force_on_repeat(None)
+            .set_handler(|| {
+                println!("handler");
+                std::thread::sleep(Duration::from_secs(10));
+            })
+            .unwrap();
+    } else if mode == "watchdog" {
+        // A cleanup path that never finishes.
+        ctrlc::Builder::new()
+            .watchdog(Duration::from_millis(100))
+            .set_handler(|| std::thread::sleep(Duration::from_secs(10)))
+            .unwrap();
+    } else if mode == "flush" {
+        // _exit(2) skips the flush at exit, only the one before the forced exit saves the output.
+        ctrlc::Builder::new()
+            .grace_period(Duration::from_millis(100))
+            .exit_mechanism(EscalationStage::GracePeriod, ExitMechanism::Immediate)
+            .set_handler(|| std::thread::sleep(Duration::from_secs(10)))
+            .unwrap();
+        println!("ready");
+        print!("stdout without newline");
+        eprint!("stderr without newline");
+        std::thread::sleep(Duration::from_secs(10));
+        std::process::exit(CHILD_TIMEOUT_EXIT_CODE);
+    } else if mode == "reraise" {
+        ctrlc::Builder::new()
+            .reraise(true)
+            .set_handler(|| println!("handler"))
+            .unwrap();
+        ctrlc::on_shutdown(0, || println!("cleanup"));
+    } else if mode == "exit-on" {
+        ctrlc::set_handler(|| {}).unwrap();
+        let mut signals = ctrlc::signals();
+        println!("ready");
+        ctrlc::exit_on(&signals.next().unwrap());
+    } else if mode == "lifecycle" {
+        ctrlc::on_lifecycle_change(|state| println!("{:?}", state));
+        ctrlc::set_handler(|| println!("handler {:?}", ctrlc::lifecycle())).unwrap();
+        println!("ready");
+        while ctrlc::lifecycle() != LifecycleState::Stopped {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "shutdown-future" {
+        ctrlc::set_handler(|| {}).unwrap();
+        println!("ready");
+        block_on(ctrlc::shutdown());
+        assert_ne!(ctrlc::lifecycle(), LifecycleState::Running);
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "flag" {
+        ctrlc::set_flag().unwrap();
+        ctrlc::set_flag().unwrap();
+        assert!(matches!(
+            ctrlc::set_handler(|| {}),
+            Err(ctrlc::Error::MultipleHandlers)
+        ));
+        assert!(!ctrlc::shutdown_requested());
+        println!("ready");
+        while !ctrlc::shutdown_requested() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "shutdown-token" {
+        ctrlc::set_handler(|| {}).unwrap();
+        let token = ctrlc::ShutdownToken::new();
+        assert!(!token.is_cancelled());
+        assert!(!token.wait_timeout(Duration::from_millis(10)));
+        let worker = std::thread::spawn({
+            let token = token.clone();
+            move || while !token.wait_timeout(Duration::from_millis(10)) {}
+        });
+        println!("ready");
+        token.wait();
+        worker.join().unwrap();
+        assert!(token.is_cancelled());
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "job-control" {
+        #[cfg(unix)]
+        {
+            ctrlc::unix::on_suspend(|| println!("suspend"));
+            ctrlc::unix::on_resume(|| {
+                println!("resume");
+                std::process::exit(CHILD_EXIT_CODE);
+            });
+            ctrlc::unix::enable_job_control().unwrap();
+        }
+    } else if mode == "harden-panic-path" {
+        ctrlc::harden_panic_path();
+        ctrlc::set_handler(|| std::process::exit(CHILD_EXIT_CODE)).unwrap();
+        let _ = std::thread::spawn(|| panic!("worker failed")).join();
+    } else if mode == "seccomp" {
+        #[cfg(target_os = "linux")]
+        unsafe {
+            use nix::libc;
+
+            // No core dump from the default action of SIGSYS.
+            let no_core = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+            ctrlc::unix::on_seccomp_violation(ctrlc::unix::seccomp_violation_to_stderr).unwrap();
+            seccomp_filter(libc::SYS_getppid, libc::SECCOMP_RET_TRAP, 0);
+            libc::syscall(libc::SYS_getppid);
+        }
+        std::process::exit(CHILD_TIMEOUT_EXIT_CODE);
+    } else if mode == "priority-denied" {
+        #[cfg(target_os = "linux")]
+        {
+            use nix::libc;
+
+            let eperm = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+            unsafe { seccomp_filter(libc::SYS_setpriority, eperm, 0) };
+            ctrlc::Builder::new()
+                .thread_priority(ctrlc::ThreadPriority::Highest)
+                .set_handler(|| std::process::exit(CHILD_EXIT_CODE))
+                .unwrap();
+        }
+    } else if mode == "debugger" {
+        ctrlc::Builder::new()
+            .debugger_passthrough(true)
+            .set_handler(|| {})
+            .unwrap();
+        let signals = ctrlc::handlers()[0].signals();
+        println!(
+            "{} {}",
+            ctrlc::is_debugger_attached(),
+            signals.contains(&ctrlc::Signal::CtrlC)
+        );
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "rollback" {
+        #[cfg(all(target_os = "linux", ctrlc_hook = "sigaction"))]
+        {
+            use nix::libc;
+
+            // Lets the first change of a signal action through and fails the others with EPERM,
+            // so installing the handler for the second signal fails and so does putting the
+            // action of the first one back.
+            let (listener_tx, listener_rx) = std::sync::mpsc::channel();
+            std::thread::spawn(move || unsafe {
+                let listener: libc::c_int = listener_rx.recv().unwrap();
+                let mut changes = 0;
+                loop {
+                    let mut request: libc::seccomp_notif = std::mem::zeroed();
+                    if libc::ioctl(listener, libc::SECCOMP_IOCTL_NOTIF_RECV, &mut request) != 0 {
+                        return;
+                    }
+                    let mut response: libc::seccomp_notif_resp = std::mem::zeroed();
+                    response.id = request.id;
+                    // Queries pass no new action.
+                    let change = request.data.args[1] != 0;
+                    if change {
+                        changes += 1;
+                    }
+                    if !change || changes == 1 {
+                        response.flags = libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32;
+                    } else {
+                        response.error = -libc::EPERM;
+                    }
+                    libc::ioctl(listener, libc::SECCOMP_IOCTL_NOTIF_SEND, &mut response);
+                }
+            });
+            let listener = unsafe {
+                seccomp_filter(
+                    libc::SYS_rt_sigaction,
+                    libc::SECCOMP_RET_USER_NOTIF,
+                    libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+                )
+            };
+            listener_tx.send(listener as libc::c_int).unwrap();
+
+            match ctrlc::Builder::new()
+                .signals([ctrlc::SignalType::Ctrlc, ctrlc::SignalType::Termination])
+                .set_handler(|| {})
+            {
+                Err(ctrlc::Error::Rollback(error, failures)) => {
+                    let failures: Vec<_> = failures.iter().map(|e| e.raw_os_error()).collect();
+                    println!("{:?} {:?}", error.raw_os_error(), failures);
+                }
+                ret => println!("{:?}", ret),
+            }
+        }
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "is-mintty" {
+        #[cfg(windows)]
+        println!("{}", ctrlc::windows::is_mintty());
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "read-interruptible" {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        ctrlc::set_handler(|| {}).unwrap();
+        println!("ready");
+        let err = ctrlc::io::read_interruptible(&stream, &mut [0u8; 16]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+        std::process::exit(CHILD_EXIT_CODE);
+    } else if mode == "read-after-reset" {
+        use std::io::Write;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        ctrlc::set_handler(|| {}).unwrap();
+        println!("ready");
+        let err = ctrlc::io::read_interruptible(&stream, &mut [0u8; 16]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+        while ctrlc::lifecycle() != ctrlc::LifecycleState::Stopped {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        ctrlc::remove_all_handlers().unwrap();
+        client.write_all(b"data").unwrap();
+        let mut buf = [0u8; 16];
+        let n = ctrlc::io::read_interruptible(&stream, &mut buf).unwrap();
+        assert_eq!(&buf[..n], b"data");
+        std::process::exit(CHILD_EXIT_CODE);
+    } else {
+        ctrlc::set_handler(|| std::process::exit(CHILD_EXIT_CODE)).unwrap();
+    }
+    println!("ready");
+    std::thread::sleep(Duration::from_secs(10));
+    std::process::exit(CHILD_TIMEOUT_EXIT_CODE);
+}
+
+/// Make `syscall` return `action` for the current thread and the threads it creates from now on,
+/// allow the other system calls. Returns the result of seccomp(2) with `flags`.
+#[cfg(target_os = "linux")]
+unsafe fn seccomp_filter(
+    syscall: nix::libc::c_long,
+    action: u32,
+    flags: nix::libc::c_ulong,
+) -> nix::libc::c_long {
+    use nix::libc::{self, sock_filter, sock_fprog};
+
+    let statement = |code: u32, k: u32| sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    };
+    let filter = [
+        statement(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, 0),
+        sock_filter {
+            code: (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16,
+            jt: 0,
+            jf: 1,
+            k: syscall as u32,
+        },
+        statement(libc::BPF_RET | libc::BPF_K, action),
+        statement(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_ALLOW),
+    ];
+    let program = sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_ptr() as *mut sock_filter,
+    };
+    assert_eq!(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0), 0);
+    let ret = libc::syscall(
+        libc::SYS_seccomp,
+        libc::SECCOMP_SET_MODE_FILTER,
+        flags,
+        &program as *const sock_fprog,
+    );
+    assert!(ret >= 0, "{}", std::io::Error::last_os_error());
+    ret
+}
+
+/// Poll `future` on the current thread until it resolves, parking in between.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::Thread;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(Unpark(std::thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+fn spawn_child(mode: &str) -> Child {
+    spawn_child_with_stdout(mode).0
+}
+
+fn spawn_child_with_stdout(mode: &str) -> (Child, BufReader<ChildStdout>) {
+    spawn_command(Command::new(std::env::current_exe().unwrap()).env(CHILD_ENV, mode))
+}
+
+fn spawn_command(command: &mut Command) -> (Child, BufReader<ChildStdout>) {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!(line.trim(), "ready");
+    (child, stdout)
+}
+
+fn test_send_interrupt() {
+    let mut child = spawn_child("1");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+fn test_force_quit_message() {
+    let mut child = spawn_child("force-quit");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+
+    let mut stderr = String::new();
+    child
+        .stderr
+        .take()
+        .unwrap()
+        .read_to_string(&mut stderr)
+        .unwrap();
+    assert_eq!(stderr.trim(), "2 more to force quit, 5s grace");
+}
+
+fn test_storm_protection() {
+    let mut child = spawn_child("storm");
+    for _ in 0..3 {
+        ctrlc::testing::send_interrupt(&child).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(1));
+}
+
+fn test_force_on_repeat() {
+    let (mut child, mut stdout) = spawn_child_with_stdout("repeat");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!(line.trim(), "handler");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(nix::libc::SIGINT));
+    }
+    #[cfg(windows)]
+    assert_eq!(status.code(), Some(0xC000013A_u32 as i32));
+}
+
+fn test_watchdog() {
+    let mut child = spawn_child("watchdog");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(1));
+}
+
+fn test_flush_output() {
+    let (mut child, mut stdout) = spawn_child_with_stdout("flush");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(1));
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "stdout without newline");
+    let mut stderr = String::new();
+    let mut pipe = child.stderr.take().unwrap();
+    pipe.read_to_string(&mut stderr).unwrap();
+    assert_eq!(stderr, "stderr without newline");
+}
+
+fn test_reraise() {
+    let (mut child, mut stdout) = spawn_child_with_stdout("reraise");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(nix::libc::SIGINT));
+    }
+    #[cfg(windows)]
+    assert_eq!(status.code(), Some(0xC000013A_u32 as i32));
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "handler\ncleanup\n");
+}
+
+fn test_exit_on() {
+    let mut child = spawn_child("exit-on");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(130));
+}
+
+fn test_lifecycle() {
+    let (mut child, mut stdout) = spawn_child_with_stdout("lifecycle");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines, ["Draining", "handler Draining", "Stopped"]);
+}
+
+fn test_shutdown_future() {
+    let mut child = spawn_child("shutdown-future");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+fn test_shutdown_token() {
+    let mut child = spawn_child("shutdown-token");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+fn test_flag() {
+    let mut child = spawn_child("flag");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+fn test_harden_panic_path() {
+    let mut child = spawn_child("harden-panic-path");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_ne!(status.code(), Some(CHILD_EXIT_CODE));
+    assert!(!status.success());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(6));
+    }
+
+    let mut stderr = String::new();
+    child
+        .stderr
+        .take()
+        .unwrap()
+        .read_to_string(&mut stderr)
+        .unwrap();
+    assert!(
+        stderr.contains("Signal received after a panic, aborting"),
+        "{}",
+        stderr
+    );
+}
+
+fn test_read_interruptible() {
+    let mut child = spawn_child("read-interruptible");
+    std::thread::sleep(Duration::from_millis(100));
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+fn test_read_after_reset() {
+    let mut child = spawn_child("read-after-reset");
+    std::thread::sleep(Duration::from_millis(100));
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+#[cfg(target_os = "linux")]
+fn test_job_control() {
+    use std::os::unix::process::CommandExt;
+
+    fn kill(signal: &str, child: &Child) {
+        let status = Command::new("kill")
+            .arg(signal)
+            .arg(child.id().to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    fn is_stopped(child: &Child) -> bool {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", child.id())).unwrap();
+        // The state follows the command name, which is in parentheses.
+        stat.rsplit(')')
+            .next()
+            .unwrap()
+            .trim_start()
+            .starts_with('T')
+    }
+
+    // In its own process group, which isn't orphaned, so the default action of SIGTSTP stops it.
+    let (mut child, mut stdout) = spawn_command(
+        Command::new(std::env::current_exe().unwrap())
+            .env(CHILD_ENV, "job-control")
+            .process_group(0),
+    );
+    kill("-TSTP", &child);
+
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!(line.trim(), "suspend");
+    while !is_stopped(&child) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    kill("-CONT", &child);
+    line.clear();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!(line.trim(), "resume");
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+#[cfg(target_os = "linux")]
+fn test_seccomp_violation() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .env(CHILD_ENV, "seccomp")
+        .output()
+        .unwrap();
+    // Reported, then killed by the default action.
+    assert_eq!(output.status.signal(), Some(nix::libc::SIGSYS));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let report = format!(
+        "[ctrlc] seccomp blocked syscall {} (arch 0x",
+        nix::libc::SYS_getppid
+    );
+    assert!(stderr.starts_with(&report), "{}", stderr);
+}
+
+#[cfg(target_os = "linux")]
+fn test_thread_priority_denied() {
+    let mut child = spawn_child("priority-denied");
+    ctrlc::testing::send_interrupt(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+
+    // Reported by both handler threads, which still run.
+    let mut stderr = String::new();
+    let mut pipe = child.stderr.take().unwrap();
+    pipe.read_to_string(&mut stderr).unwrap();
+    let message =
+        "[ctrlc] Could not set the handler thread priority: Operation not permitted (os error 1)";
+    let reported = stderr.lines().filter(|&line| line == message).count();
+    assert_eq!(reported, 2, "{}", stderr);
+}
+
+#[cfg(all(target_os = "linux", ctrlc_hook = "sigaction"))]
+fn test_rollback() {
+    let output = Command::new(std::env::current_exe().unwrap())
+        .env(CHILD_ENV, "rollback")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(CHILD_EXIT_CODE));
+    // Installing the second handler failed, then restoring the action of the first signal.
+    let eperm = Some(nix::libc::EPERM);
+    let expected = format!("{:?} {:?}\n", eperm, [eperm]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[cfg(target_os = "linux")]
+fn test_debugger_passthrough() {
+    use nix::libc;
+    use std::os::unix::process::CommandExt;
+    use std::ptr::null_mut;
+
+    /// Run the child, traced by this process if `traced`, and return its output.
+    // A traced child is reaped with waitpid(2) instead of `Child::wait()`.
+    #[allow(clippy::zombie_processes)]
+    fn run(traced: bool) -> String {
+        let mut command = Command::new(std::env::current_exe().unwrap());
+        command.env(CHILD_ENV, "debugger").stdout(Stdio::piped());
+        if traced {
+            unsafe {
+                command.pre_exec(|| {
+                    let null = null_mut::<libc::c_void>();
+                    if libc::ptrace(libc::PTRACE_TRACEME, 0, null, null) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        let mut child = command.spawn().unwrap();
+
+        let code = if traced {
+            // Act as the debugger, resume the child from every stop until it exits.
+            let pid = child.id() as libc::pid_t;
+            loop {
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                if !libc::WIFSTOPPED(status) {
+                    assert!(libc::WIFEXITED(status));
+                    break Some(libc::WEXITSTATUS(status));
+                }
+                // The stop after execve(2) is a SIGTRAP, pass any other signal on.
+                let signal = match libc::WSTOPSIG(status) {
+                    libc::SIGTRAP => 0,
+                    signal => signal,
+                };
+                let signal = signal as usize as *mut libc::c_void;
+                unsafe { libc::ptrace(libc::PTRACE_CONT, pid, null_mut::<libc::c_void>(), signal) };
+            }
+        } else {
+            child.wait().unwrap().code()
+        };
+        assert_eq!(code, Some(CHILD_EXIT_CODE));
+
+        let mut stdout = String::new();
+        let mut pipe = child.stdout.take().unwrap();
+        pipe.read_to_string(&mut stdout).unwrap();
+        stdout.trim().to_string()
+    }
+
+    // Detected, and Ctrl-C is left to the debugger only while traced.
+    assert_eq!(run(false), "false true");
+    assert_eq!(run(true), "true false");
+}
+
+#[cfg(windows)]
+fn test_break_child() {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+    let (mut child, _) = spawn_command(
+        Command::new(std::env::current_exe().unwrap())
+            .env(CHILD_ENV, "1")
+            .creation_flags(CREATE_NEW_PROCESS_GROUP),
+    );
+    ctrlc::windows::break_child(&child).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(CHILD_EXIT_CODE));
+}
+
+#[cfg(windows)]
+fn test_is_mintty() {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{FromRawHandle, OwnedHandle};
+    use std::os::windows::process::CommandExt;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_OUTBOUND;
+    use windows_sys::Win32::System::Pipes::{CreateNamedPipeW, PIPE_TYPE_BYTE};
+
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+
+    fn is_mintty(command: &mut Command) -> bool {
+        let output = command.env(CHILD_ENV, "is-mintty").output().unwrap();
+        assert_eq!(output.status.code(), Some(CHILD_EXIT_CODE));
+        String::from_utf8(output.stdout).unwrap().trim() == "true"
+    }
+
+    let exe = std::env::current_exe().unwrap();
+    // Standard handles that are not a pty.
+    assert!(!is_mintty(&mut Command::new(&exe)));
+
+    // No console and stdin named like the pipes of a Cygwin pty, which is what mintty gives
+    // native programs.
+    let name = format!(
+        r"\\.\pipe\msys-0123456789abcdef-pty{}-from-master",
+        std::process::id()
+    );
+    let wide: Vec<u16> = std::ffi::OsStr::new(&name)
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    let server = unsafe {
+        CreateNamedPipeW(
+            wide.as_ptr(),
+            PIPE_ACCESS_OUTBOUND,
+            PIPE_TYPE_BYTE,
+            1,
+            0,
+            0,
+            0,
+            std::ptr::null(),
+        )
+    };
+    assert_ne!(server, INVALID_HANDLE_VALUE);
+    let _server = unsafe { OwnedHandle::from_raw_handle(server) };
+    let pty = std::fs::File::open(&name).unwrap();
+    assert!(is_mintty(
+        Command::new(&exe)
+            .stdin(pty)
+            .creation_flags(DETACHED_PROCESS)
+    ));
+}
+
+fn tests() {
+    run_tests!(test_send_interrupt);
+    run_tests!(test_force_quit_message);
+    run_tests!(test_storm_protection);
+    run_tests!(test_force_on_repeat);
+    run_tests!(test_watchdog);
+    run_tests!(test_flush_output);
+    run_tests!(test_reraise);
+    run_tests!(test_exit_on);
+    run_tests!(test_lifecycle);
+    run_tests!(test_shutdown_future);
+    run_tests!(test_shutdown_token);
+    run_tests!(test_flag);
+    run_tests!(test_harden_panic_path);
+    run_tests!(test_read_interruptible);
+    run_tests!(test_read_after_reset);
+    #[cfg(target_os = "linux")]
+    run_tests!(test_job_control);
+    #[cfg(target_os = "linux")]
+    run_tests!(test_seccomp_violation);
+    #[cfg(target_os = "linux")]
+    run_tests!(test_thread_priority_denied);
+    #[cfg(all(target_os = "linux", ctrlc_hook = "sigaction"))]
+    run_tests!(test_rollback);
+    #[cfg(target_os = "linux")]
+    run_tests!(test_debugger_passthrough);
+    #[cfg(windows)]
+    run_tests!(test_break_child);
+    #[cfg(windows)]
+    run_tests!(test_is_mintty);
+}
+
+fn main() {
+    if let Ok(mode) = std::env::var(CHILD_ENV) {
+        child(&mode);
+    }
+    run_harness(tests);
+}
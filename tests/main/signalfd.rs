@@ -0,0 +1,31 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+// The signalfd backend only sees the signals blocked by the threads spawned after installing
+// the handler. The main tests spawn threads before and raise thread-directed signals.
+
+// Other backends build the harness for nothing.
+#![cfg_attr(not(ctrlc_hook = "signalfd"), allow(dead_code, unused_macros))]
+
+#[macro_use]
+mod harness;
+use harness::run_harness;
+
+mod test_blocking;
+#[cfg(ctrlc_hook = "signalfd")]
+use test_blocking::test_blocking;
+
+fn tests() {
+    #[cfg(ctrlc_hook = "signalfd")]
+    run_tests!(test_blocking);
+}
+
+fn main() {
+    run_harness(tests);
+}
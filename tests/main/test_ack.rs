@@ -0,0 +1,30 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::time::Duration;
+
+pub fn test_ack_barrier() {
+    assert_eq!(ctrlc::wait_for_acks(Duration::ZERO), Ok(()));
+
+    let db = ctrlc::AckHandle::new("db");
+    let worker = ctrlc::AckHandle::new("worker");
+    let cache = ctrlc::AckHandle::new("cache");
+
+    worker.acknowledge();
+    assert_eq!(
+        ctrlc::wait_for_acks(Duration::from_millis(10)),
+        Err(vec!["db".to_owned(), "cache".to_owned()])
+    );
+
+    let waiter = std::thread::spawn(|| ctrlc::wait_for_acks(Duration::from_secs(10)));
+    std::thread::sleep(Duration::from_millis(10));
+    db.acknowledge();
+    drop(cache);
+    assert_eq!(waiter.join().unwrap(), Ok(()));
+}
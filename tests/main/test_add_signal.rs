@@ -0,0 +1,106 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(unix)]
+
+use ctrlc::{Signal, SignalType};
+use nix::sys::signal::{
+    raise, sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal as NixSignal,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_add_signal() {
+    let usr2 = SignalType::Other(Signal::SIGUSR2);
+    match ctrlc::add_signal(usr2) {
+        Err(ctrlc::Error::HandlerRemoved) => {}
+        ret => panic!("Expected Error::HandlerRemoved, got {:?}", ret),
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::set_handler({
+        let count = count.clone();
+        move || {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+    })
+    .unwrap();
+
+    ctrlc::add_signal(usr2).unwrap();
+    assert!(ctrlc::handlers()[0].signals().contains(&Signal::SIGUSR2));
+    raise(NixSignal::SIGUSR2).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    ctrlc::remove_signal(usr2).unwrap();
+    assert!(!ctrlc::handlers()[0].signals().contains(&Signal::SIGUSR2));
+    // The registry never puts the previous action back.
+    if cfg!(not(feature = "signal-hook-registry")) {
+        let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+        let previous = unsafe { sigaction(NixSignal::SIGUSR2, &default).unwrap() };
+        assert_eq!(previous.handler(), SigHandler::SigDfl);
+    }
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_builder_add_signal() {
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .add_signal(SignalType::Other(Signal::SIGXCPU))
+        .add_signal(SignalType::Other(Signal::SIGXFSZ))
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    // The default signals stay handled.
+    let signals = ctrlc::handlers()[0].signals().to_vec();
+    for signal in ctrlc::default_signals() {
+        assert!(signals.contains(&signal));
+    }
+    assert!(signals.contains(&Signal::SIGXCPU));
+    assert!(signals.contains(&Signal::SIGXFSZ));
+
+    raise(NixSignal::SIGXCPU).unwrap();
+    raise(NixSignal::SIGXFSZ).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_add_signal_no_shutdown() {
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .add_signal(SignalType::Other(Signal::SIGXCPU))
+        .grace_period(Duration::from_millis(100))
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    raise(NixSignal::SIGXCPU).unwrap();
+    // Still alive past the grace period, SIGXCPU doesn't start the shutdown.
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(ctrlc::lifecycle(), ctrlc::LifecycleState::Running);
+    assert!(!ctrlc::ShutdownToken::new().is_cancelled());
+    assert_eq!(ctrlc::grace_remaining(), None);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
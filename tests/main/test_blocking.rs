@@ -0,0 +1,50 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(any(ctrlc_hook = "signalfd", ctrlc_hook = "sigwait"))]
+
+use crate::harness::platform;
+use ctrlc::{Signal, SignalType};
+use nix::sys::signal::{kill, pthread_sigmask, SigSet, SigmaskHow, Signal as NixSignal};
+use nix::unistd::getpid;
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn blocked() -> SigSet {
+    let mut mask = SigSet::empty();
+    pthread_sigmask(SigmaskHow::SIG_BLOCK, None, Some(&mut mask)).unwrap();
+    mask
+}
+
+pub fn test_blocking() {
+    let (tx, rx) = mpsc::channel();
+    ctrlc::Builder::new()
+        .signals([SignalType::Ctrlc, SignalType::Other(Signal::SIGUSR1)])
+        .set_handler_with_signal(move |signal| tx.send(signal).unwrap())
+        .unwrap();
+    assert!(blocked().contains(NixSignal::SIGINT));
+
+    // Sent to the process, taken from the signalfd by the handler thread or by the dispatcher.
+    for (sent, signal) in [
+        (NixSignal::SIGUSR1, Signal::User1),
+        (NixSignal::SIGINT, Signal::CtrlC),
+    ] {
+        kill(getpid(), sent).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), signal);
+    }
+
+    // Sent to this thread only, nobody reads it. Removing the handler drops it instead of
+    // unblocking it into the default action.
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    ctrlc::remove_all_handlers().unwrap();
+    assert!(!blocked().contains(NixSignal::SIGINT));
+    assert!(!blocked().contains(NixSignal::SIGUSR1));
+}
@@ -0,0 +1,24 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+pub fn test_capabilities() {
+    let capabilities = ctrlc::capabilities();
+    assert_eq!(capabilities.posix_signals, cfg!(unix));
+    assert_eq!(capabilities.job_control, cfg!(unix));
+    assert!(capabilities.interruptible_reads);
+    assert!(capabilities.send_interrupt);
+    assert_eq!(
+        capabilities.seccomp_violations,
+        cfg!(any(target_os = "linux", target_os = "android"))
+    );
+    assert_eq!(
+        capabilities.realtime_signals,
+        cfg!(any(target_os = "linux", target_os = "android"))
+    );
+}
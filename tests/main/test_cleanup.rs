@@ -0,0 +1,44 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub fn test_on_shutdown() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let push = |entry: &'static str| {
+        let log = log.clone();
+        move || log.lock().unwrap().push(entry)
+    };
+
+    ctrlc::set_handler(push("handler")).unwrap();
+    ctrlc::on_shutdown(20, push("close-db"));
+    ctrlc::on_shutdown(0, push("close-listeners"));
+    ctrlc::on_shutdown(10, || std::thread::sleep(Duration::from_secs(3600)))
+        .timeout(Duration::from_millis(50), ctrlc::OnTimeout::Skip);
+    ctrlc::on_shutdown(10, push("flush-log"));
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["handler", "close-listeners", "flush-log", "close-db"]
+    );
+
+    // The callbacks run only once.
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(log.lock().unwrap().len(), 5);
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,37 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::time::Duration;
+
+pub fn test_shutdown_complete() {
+    ctrlc::set_handler(|| {}).unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    while ctrlc::lifecycle() != ctrlc::LifecycleState::Stopped {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let complete = ctrlc::shutdown_complete();
+    assert!(complete.is_complete());
+
+    let ack = ctrlc::AckHandle::new("worker");
+    assert!(!complete.is_complete());
+    assert!(!complete.wait_timeout(Duration::from_millis(10)));
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let waiter = runtime.spawn(ctrlc::shutdown_complete());
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        ack.acknowledge();
+    });
+    complete.wait();
+    runtime.block_on(waiter).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+}
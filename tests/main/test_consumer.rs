@@ -0,0 +1,46 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub fn test_consumer_stack() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let push = |name: &'static str| {
+        let log = log.clone();
+        ctrlc::push_consumer(move || log.lock().unwrap().push(name))
+    };
+    let interrupt = || {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+
+    ctrlc::set_handler({
+        let log = log.clone();
+        move || log.lock().unwrap().push("handler")
+    })
+    .unwrap();
+
+    let _outer = push("outer");
+    let _inner = push("inner");
+    let dropped = push("dropped");
+    drop(dropped);
+
+    interrupt();
+    assert_eq!(*log.lock().unwrap(), ["inner"]);
+    interrupt();
+    assert_eq!(*log.lock().unwrap(), ["inner", "outer"]);
+    interrupt();
+    assert_eq!(*log.lock().unwrap(), ["inner", "outer", "handler"]);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,82 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use ctrlc::Delivery;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+fn interrupt(times: usize) {
+    for _ in 0..times {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    }
+}
+
+pub fn test_delivery_coalesced() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .delivery(Delivery::Coalesced)
+        .set_handler({
+            let runs = runs.clone();
+            move || {
+                runs.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(200));
+            }
+        })
+        .unwrap();
+
+    interrupt(1);
+    // The next signals arrive while the handler runs.
+    while runs.load(Ordering::SeqCst) == 0 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    interrupt(3);
+    std::thread::sleep(Duration::from_millis(600));
+    // The three signals received while the handler ran share its second run.
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_delivery_concurrent() {
+    match ctrlc::Builder::new()
+        .delivery(Delivery::Concurrent)
+        .set_handler(|| {})
+    {
+        Err(ctrlc::Error::System(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+        ret => panic!("Expected Error::System, got {:?}", ret),
+    }
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .delivery(Delivery::Concurrent)
+        .set_sync_handler({
+            let running = running.clone();
+            let max_running = max_running.clone();
+            move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(200));
+                running.fetch_sub(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    interrupt(2);
+    std::thread::sleep(Duration::from_millis(400));
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
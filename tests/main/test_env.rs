@@ -0,0 +1,39 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+fn expect_env_error(var: &str, value: &str) {
+    std::env::set_var(var, value);
+    match ctrlc::Builder::new().env() {
+        Err(ctrlc::Error::Env(e)) => {
+            assert_eq!(e.var(), var);
+            assert_eq!(e.value(), value);
+        }
+        ret => panic!("Expected Error::Env for {}={:?}, got {:?}", var, value, ret),
+    }
+    std::env::remove_var(var);
+}
+
+pub fn test_env_overrides() {
+    std::env::set_var("CTRLC_SIGNALS", "INT, sigterm");
+    std::env::set_var("CTRLC_GRACE_SECS", "2.5");
+    std::env::set_var("CTRLC_FORCE_AFTER", "2");
+    ctrlc::Builder::new().env().unwrap();
+
+    std::env::set_var("CTRLC_GRACE_SECS", "");
+    ctrlc::Builder::new().env().unwrap();
+
+    std::env::remove_var("CTRLC_SIGNALS");
+    std::env::remove_var("CTRLC_GRACE_SECS");
+    std::env::remove_var("CTRLC_FORCE_AFTER");
+
+    expect_env_error("CTRLC_SIGNALS", "INT,NOT_A_SIGNAL");
+    expect_env_error("CTRLC_GRACE_SECS", "forever");
+    expect_env_error("CTRLC_GRACE_SECS", "-1");
+    expect_env_error("CTRLC_FORCE_AFTER", "0");
+}
@@ -0,0 +1,27 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::io;
+
+pub fn test_io_error() {
+    ctrlc::set_handler(|| {}).unwrap();
+    let e = ctrlc::set_handler(|| {}).unwrap_err();
+    assert!(matches!(e, ctrlc::Error::MultipleHandlers));
+    assert_eq!(e.raw_os_error(), None);
+    let e = io::Error::from(e);
+    assert_eq!(e.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(e.raw_os_error(), None);
+    ctrlc::remove_all_handlers().unwrap();
+
+    // The OS error code survives both ways.
+    let e = ctrlc::Error::System(io::Error::from_raw_os_error(1));
+    assert_eq!(e.raw_os_error(), Some(1));
+    let e = io::Error::from(e);
+    assert_eq!(e.raw_os_error(), Some(1));
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use ctrlc::SignalType;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_external() {
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .external(true)
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    // The OS handler of SIGINT is left alone. The registry keeps its handler from the tests
+    // before, so only the default action tells.
+    #[cfg(all(unix, not(feature = "signal-hook-registry")))]
+    unsafe {
+        use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+        let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+        let previous = sigaction(Signal::SIGINT, &default).unwrap();
+        assert_eq!(previous.handler(), SigHandler::SigDfl);
+    }
+
+    assert!(ctrlc::external::deliver(SignalType::Ctrlc).unwrap());
+    #[cfg(unix)]
+    assert!(!ctrlc::external::deliver(SignalType::Other(ctrlc::Signal::SIGUSR1)).unwrap());
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    ctrlc::remove_all_handlers().unwrap();
+    match ctrlc::external::deliver(SignalType::Ctrlc) {
+        Err(ctrlc::Error::HandlerRemoved) => {}
+        ret => panic!("Expected Error::HandlerRemoved, got {:?}", ret),
+    }
+}
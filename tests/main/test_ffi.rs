@@ -0,0 +1,27 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(feature = "ffi")]
+
+use crate::harness::platform;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+pub fn test_ffi_flag() {
+    ctrlc::set_handler(|| {}).unwrap();
+
+    let before = ctrlc::CTRLC_INTERRUPTED.load(Ordering::SeqCst);
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(ctrlc::CTRLC_INTERRUPTED.load(Ordering::SeqCst), before + 1);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_filter() {
+    let handled = Arc::new(AtomicU32::new(0));
+    ctrlc::Builder::new()
+        .stack_size(1 << 20)
+        .filter(|event| event.count() % 2 == 0)
+        .set_handler({
+            let handled = handled.clone();
+            move || {
+                handled.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    for _ in 0..3 {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(handled.load(Ordering::SeqCst), 1);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_filter_rejected_shutdown() {
+    let handled = Arc::new(AtomicU32::new(0));
+    ctrlc::Builder::new()
+        .grace_period(Duration::from_millis(100))
+        .filter(|_| false)
+        .set_handler({
+            let handled = handled.clone();
+            move || {
+                handled.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    // Still alive past the grace period, nothing started the shutdown.
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(handled.load(Ordering::SeqCst), 0);
+    assert!(!ctrlc::ShutdownToken::new().is_cancelled());
+    assert!(!ctrlc::shutdown().is_started());
+    assert_eq!(ctrlc::lifecycle(), ctrlc::LifecycleState::Running);
+    assert_eq!(ctrlc::grace_remaining(), None);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn test_sender_names() {
+    use ctrlc::{Signal, SignalType};
+    use std::sync::Mutex;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    ctrlc::Builder::new()
+        .signals([SignalType::Other(Signal::SIGUSR1)])
+        .sender_names(true)
+        .filter({
+            let events = events.clone();
+            move |event| {
+                events.lock().unwrap().push(event.clone());
+                true
+            }
+        })
+        .set_handler(|| {})
+        .unwrap();
+
+    // Not waited for until the name was looked up, a zombie keeps its name.
+    let mut child = std::process::Command::new("kill")
+        .args(["-USR1", &std::process::id().to_string()])
+        .spawn()
+        .unwrap();
+    let start = std::time::Instant::now();
+    while events.lock().unwrap().is_empty() {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    child.wait().unwrap();
+    let event = events.lock().unwrap().remove(0);
+    assert_eq!(event.sender().unwrap().pid(), child.id());
+    assert_eq!(event.sender_name(), Some("kill"));
+
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_handler_guard() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let guard = ctrlc::set_handler_guarded({
+        let count = count.clone();
+        move || {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+    })
+    .unwrap();
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    drop(guard);
+    assert!(ctrlc::handlers().is_empty());
+
+    // Removing the handler before dropping the guard is fine.
+    let guard = ctrlc::set_handler_guarded(|| {}).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+    drop(guard);
+    assert!(ctrlc::handlers().is_empty());
+}
+
+pub fn test_channel() {
+    let signals = ctrlc::channel().unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let event = signals.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(event.count(), 1);
+
+    // Removing the handler disconnects the channel.
+    ctrlc::remove_all_handlers().unwrap();
+    assert!(signals.recv_timeout(Duration::from_secs(10)).is_err());
+
+    // The first signal after the receiver was dropped removes the handler.
+    let signals = ctrlc::channel().unwrap();
+    drop(signals);
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    for _ in 0..1000 {
+        if ctrlc::handlers().is_empty() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(ctrlc::handlers().is_empty());
+}
+
+#[cfg(all(unix, not(feature = "signal-hook-registry")))]
+pub fn test_guard_restores_action() {
+    use ctrlc::{Signal, SignalType};
+    use nix::sys::signal::{
+        sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal as NixSignal,
+    };
+
+    extern "C" fn previous_handler(_: nix::libc::c_int) {}
+
+    let previous = SigAction::new(
+        SigHandler::Handler(previous_handler),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    unsafe { sigaction(NixSignal::SIGUSR1, &previous).unwrap() };
+
+    let guard = ctrlc::Builder::new()
+        .signals([SignalType::Other(Signal::SIGUSR1)])
+        .set_handler_guarded(|| {})
+        .unwrap();
+    drop(guard);
+
+    let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    let restored = unsafe { sigaction(NixSignal::SIGUSR1, &default).unwrap() };
+    assert_eq!(restored.handler(), previous.handler());
+}
+
+#[cfg(all(unix, not(feature = "signal-hook-registry")))]
+pub fn test_remove_restores_action() {
+    use ctrlc::{Signal, SignalType};
+    use nix::sys::signal::{
+        sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal as NixSignal,
+    };
+
+    extern "C" fn previous_handler(_: nix::libc::c_int) {}
+
+    let previous = SigAction::new(
+        SigHandler::Handler(previous_handler),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    unsafe {
+        sigaction(NixSignal::SIGUSR1, &previous).unwrap();
+        sigaction(NixSignal::SIGUSR2, &previous).unwrap();
+    }
+
+    ctrlc::Builder::new()
+        .signals([
+            SignalType::Other(Signal::SIGUSR1),
+            SignalType::Other(Signal::SIGUSR2),
+        ])
+        .set_handler(|| {})
+        .unwrap();
+    ctrlc::remove_signal(SignalType::Other(Signal::SIGUSR2)).unwrap();
+    let restored = unsafe { sigaction(NixSignal::SIGUSR2, &default).unwrap() };
+    assert_eq!(restored.handler(), previous.handler());
+    ctrlc::remove_all_handlers().unwrap();
+    let restored = unsafe { sigaction(NixSignal::SIGUSR1, &default).unwrap() };
+    assert_eq!(restored.handler(), previous.handler());
+}
+
+pub fn test_with_handler() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let result = ctrlc::with_handler(
+        {
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        },
+        || {
+            assert_eq!(ctrlc::handlers().len(), 1);
+            unsafe {
+                platform::raise_ctrl_c();
+            }
+            std::thread::sleep(Duration::from_millis(100));
+            42
+        },
+    )
+    .unwrap();
+    assert_eq!(result, 42);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert!(ctrlc::handlers().is_empty());
+
+    ctrlc::set_handler_once(|| {}).unwrap();
+    match ctrlc::with_handler(|| {}, || unreachable!()) {
+        Err(ctrlc::Error::MultipleHandlers) => {}
+        ret => panic!("Expected Error::MultipleHandlers, got {:?}", ret),
+    }
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,176 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub fn test_handle() {
+    let handle = ctrlc::Handle::new(&ctrlc::default_signals(), true).unwrap();
+    assert!(matches!(
+        ctrlc::Handle::new(&ctrlc::default_signals(), true),
+        Err(ctrlc::Error::MultipleHandlers)
+    ));
+    assert!(matches!(
+        ctrlc::set_handler(|| {}),
+        Err(ctrlc::Error::MultipleHandlers)
+    ));
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    handle.wait().unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let signal = handle.wait_signal().unwrap();
+    #[cfg(unix)]
+    assert_eq!(signal, Some(ctrlc::Signal::SIGINT));
+    #[cfg(windows)]
+    assert!(signal.is_some());
+
+    ctrlc::ShutdownController::new().shutdown("test").unwrap();
+    assert_eq!(handle.wait_signal().unwrap(), None);
+
+    let start = Instant::now();
+    assert!(!handle.wait_timeout(Duration::from_millis(50)).unwrap());
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(!handle.wait_timeout(Duration::ZERO).unwrap());
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert!(handle.wait_timeout(Duration::from_secs(10)).unwrap());
+    ctrlc::ShutdownController::new().shutdown("test").unwrap();
+    assert!(handle.wait_timeout(Duration::ZERO).unwrap());
+
+    assert!(handle.try_recv().unwrap().is_none());
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let start = Instant::now();
+    let event = loop {
+        if let Some(event) = handle.try_recv().unwrap() {
+            break event;
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    #[cfg(unix)]
+    assert_eq!(event.signal(), Some(ctrlc::Signal::SIGINT));
+    #[cfg(windows)]
+    assert!(event.signal().is_some());
+    // Every signal and wakeup taken through the handle so far.
+    assert_eq!(event.count(), 6);
+    assert!(handle.try_recv().unwrap().is_none());
+
+    drop(handle);
+    assert!(ctrlc::handlers().is_empty());
+}
+
+pub fn test_handle_drop_after_remove() {
+    let handle = ctrlc::Handle::new(&ctrlc::default_signals(), true).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+    assert!(matches!(handle.wait(), Err(ctrlc::Error::HandlerRemoved)));
+
+    ctrlc::set_handler(|| {}).unwrap();
+    drop(handle);
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_pump() {
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let handle = ctrlc::set_handler_no_thread({
+        let threads = threads.clone();
+        move || threads.lock().unwrap().push(std::thread::current().id())
+    })
+    .unwrap();
+    assert_eq!(handle.pump().unwrap(), 0);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let start = Instant::now();
+    while handle.pump().unwrap() == 0 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    // Ran once, on the thread pumping.
+    assert_eq!(*threads.lock().unwrap(), [std::thread::current().id()]);
+    assert_eq!(handle.pump().unwrap(), 0);
+
+    drop(handle);
+    assert!(ctrlc::handlers().is_empty());
+}
+
+#[cfg(unix)]
+pub fn test_resize() {
+    use ctrlc::Signal;
+    use nix::libc;
+
+    let handle = ctrlc::Handle::new(&[Signal::SIGINT, Signal::SIGWINCH], true).unwrap();
+    let next_event = || loop {
+        if let Some(event) = handle.try_recv().unwrap() {
+            return event;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    nix::sys::signal::raise(nix::sys::signal::Signal::SIGWINCH).unwrap();
+    let event = next_event();
+    assert_eq!(event.signal(), Some(Signal::SIGWINCH));
+    let terminal = [libc::STDOUT_FILENO, libc::STDIN_FILENO, libc::STDERR_FILENO]
+        .iter()
+        .any(|&fd| unsafe { libc::isatty(fd) } == 1);
+    assert_eq!(event.resize().is_some(), terminal);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert_eq!(next_event().resize(), None);
+}
+
+#[cfg(unix)]
+pub fn test_sender() {
+    use ctrlc::Signal;
+    use nix::libc;
+
+    let handle = ctrlc::Handle::new(&[Signal::SIGINT, Signal::SIGUSR1], true).unwrap();
+    let next_event = || loop {
+        if let Some(event) = handle.try_recv().unwrap() {
+            return event;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let mut child = std::process::Command::new("kill")
+        .args(["-USR1", &std::process::id().to_string()])
+        .spawn()
+        .unwrap();
+    let event = next_event();
+    assert_eq!(event.signal(), Some(Signal::SIGUSR1));
+    // The kqueue doesn't tell.
+    if cfg!(not(ctrlc_hook = "kqueue")) {
+        let sender = event.sender().unwrap();
+        assert_eq!(sender.pid(), child.id());
+        assert_eq!(sender.uid(), unsafe { libc::getuid() });
+    }
+    child.wait().unwrap();
+
+    let pid = nix::unistd::Pid::this();
+    nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGINT).unwrap();
+    let event = next_event();
+    if cfg!(not(ctrlc_hook = "kqueue")) {
+        assert_eq!(event.sender().unwrap().pid(), std::process::id());
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::time::{Duration, Instant};
+
+pub fn test_handlers() {
+    assert!(ctrlc::handlers().is_empty());
+
+    let handle = ctrlc::set_handler_once(|| {}).unwrap();
+    let handlers = ctrlc::handlers();
+    assert_eq!(handlers.len(), 1);
+    assert!(handlers[0].is_once());
+    let once_id = handlers[0].id();
+
+    // Gone once it has fired.
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    handle.join().unwrap().unwrap();
+    let start = Instant::now();
+    while !ctrlc::handlers().is_empty() {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    ctrlc::set_handler(|| {}).unwrap();
+    let handlers = ctrlc::handlers();
+    assert_eq!(handlers.len(), 1);
+    assert!(!handlers[0].is_once());
+    assert_ne!(handlers[0].id(), once_id);
+
+    ctrlc::remove_all_handlers().unwrap();
+    assert!(ctrlc::handlers().is_empty());
+}
@@ -0,0 +1,48 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub fn test_inflight() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut shutdown = ctrlc::Orchestrator::new();
+    shutdown
+        .add_task("close-db", &[], {
+            let log = log.clone();
+            move || log.lock().unwrap().push("close-db")
+        })
+        .unwrap();
+
+    let permit = ctrlc::inflight();
+    assert_eq!(ctrlc::inflight_count(), 1);
+
+    ctrlc::Builder::new()
+        .orchestrator(shutdown)
+        .set_handler({
+            let log = log.clone();
+            move || log.lock().unwrap().push("handler")
+        })
+        .unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(*log.lock().unwrap(), ["handler"]);
+
+    log.lock().unwrap().push("request");
+    drop(permit);
+    assert_eq!(ctrlc::inflight_count(), 0);
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(*log.lock().unwrap(), ["handler", "request", "close-db"]);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(all(unix, not(feature = "signal-hook-registry")))]
+
+use ctrlc::{InstallPolicy, PreviousAction, Signal, SignalType};
+use nix::sys::signal::{
+    raise, sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal as NixSignal,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+static PREVIOUS_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn previous_handler(_: nix::libc::c_int) {
+    PREVIOUS_RUNS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg_attr(ctrlc_hook = "kqueue", allow(dead_code))]
+pub fn test_chain() {
+    let previous = SigAction::new(
+        SigHandler::Handler(previous_handler),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    unsafe { sigaction(NixSignal::SIGUSR1, &previous).unwrap() };
+
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .signals([SignalType::Other(Signal::SIGUSR1)])
+        .install_policy(InstallPolicy::Chain)
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(PREVIOUS_RUNS.load(Ordering::SeqCst), 1);
+    ctrlc::remove_all_handlers().unwrap();
+
+    // Without chaining, only our handler runs.
+    ctrlc::Builder::new()
+        .signals([SignalType::Other(Signal::SIGUSR1)])
+        .set_handler(|| {})
+        .unwrap();
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(PREVIOUS_RUNS.load(Ordering::SeqCst), 1);
+    ctrlc::remove_all_handlers().unwrap();
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    assert_eq!(PREVIOUS_RUNS.load(Ordering::SeqCst), 2);
+}
+
+pub fn test_replace_if_default() {
+    let previous = SigAction::new(
+        SigHandler::Handler(previous_handler),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    let ignore = SigAction::new(SigHandler::SigIgn, SaFlags::empty(), SigSet::empty());
+    unsafe {
+        sigaction(NixSignal::SIGUSR1, &previous).unwrap();
+        sigaction(NixSignal::SIGUSR2, &ignore).unwrap();
+    }
+    let signals = [
+        SignalType::Other(Signal::SIGUSR1),
+        SignalType::Other(Signal::SIGUSR2),
+    ];
+
+    match ctrlc::Builder::new()
+        .signals(signals)
+        .install_policy(InstallPolicy::ErrorIfExists)
+        .set_handler(|| {})
+    {
+        Err(ctrlc::Error::MultipleHandlers) => {}
+        ret => panic!("Expected Error::MultipleHandlers, got {:?}", ret),
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .signals(signals)
+        .install_policy(InstallPolicy::ReplaceIfDefault)
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    let handler = &ctrlc::handlers()[0];
+    assert_eq!(handler.signals(), [Signal::SIGUSR2]);
+    let displaced = handler.displaced();
+    assert_eq!(displaced.len(), 2);
+    assert_eq!(displaced[0].signal(), Signal::SIGUSR1);
+    assert_eq!(
+        displaced[0].previous(),
+        PreviousAction::Handler(previous_handler as *const () as usize)
+    );
+    assert!(!displaced[0].is_replaced());
+    assert_eq!(displaced[1].signal(), Signal::SIGUSR2);
+    assert_eq!(displaced[1].previous(), PreviousAction::Ignore);
+    assert!(displaced[1].is_replaced());
+
+    // The ignored signal is handled, the one with a handler is left alone.
+    let runs = PREVIOUS_RUNS.load(Ordering::SeqCst);
+    raise(NixSignal::SIGUSR2).unwrap();
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(PREVIOUS_RUNS.load(Ordering::SeqCst), runs + 1);
+    ctrlc::remove_all_handlers().unwrap();
+
+    let restored = unsafe { sigaction(NixSignal::SIGUSR2, &previous).unwrap() };
+    assert_eq!(restored.handler(), SigHandler::SigIgn);
+}
@@ -0,0 +1,159 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub fn test_signal_listener() {
+    ctrlc::set_handler(|| {}).unwrap();
+    let mut listener = ctrlc::SignalListener::new();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    // Whether a signal arrives within 100ms, dropping `recv()` if not.
+    let mut received = || {
+        runtime.block_on(async {
+            tokio::select! {
+                _ = listener.recv() => true,
+                _ = tokio::time::sleep(Duration::from_millis(100)) => false,
+            }
+        })
+    };
+
+    assert!(!received());
+    unsafe {
+        platform::raise_ctrl_c();
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+
+    // Both signals are delivered, one per `recv()`, although the first future was cancelled.
+    assert!(received());
+    assert!(received());
+    assert!(!received());
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_wait_for_count() {
+    ctrlc::set_handler(|| {}).unwrap();
+    ctrlc::wait_for_count(0);
+
+    let (tx, rx) = mpsc::channel();
+    let waiter = std::thread::spawn(move || {
+        ctrlc::wait_for_count(2);
+        tx.send(()).unwrap();
+    });
+    // Counting starts at the call, let the waiter get there before the first signal.
+    std::thread::sleep(Duration::from_millis(100));
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    waiter.join().unwrap();
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_signals() {
+    // Nothing to wait for without a handler.
+    assert!(ctrlc::signals().next().is_none());
+
+    ctrlc::set_handler(|| {}).unwrap();
+    let signals = ctrlc::signals();
+    let (tx, rx) = mpsc::channel();
+    let iterating = std::thread::spawn(move || {
+        for event in signals {
+            tx.send(event.count()).unwrap();
+        }
+    });
+
+    for count in 1..=2 {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        assert_eq!(rx.recv_timeout(Duration::from_secs(10)).unwrap(), count);
+    }
+
+    // Removing the handler ends the iterator.
+    ctrlc::remove_all_handlers().unwrap();
+    iterating.join().unwrap();
+}
+
+#[cfg(feature = "futures")]
+pub fn test_signal_stream() {
+    use futures_core::Stream;
+    use std::future;
+    use std::pin::Pin;
+
+    ctrlc::set_handler(|| {}).unwrap();
+    let mut signals = ctrlc::stream();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    // The next item within 100ms, if any.
+    let mut next = || {
+        let event = future::poll_fn(|cx| Pin::new(&mut signals).poll_next(cx));
+        runtime.block_on(async {
+            tokio::select! {
+                event = event => event,
+                _ = tokio::time::sleep(Duration::from_millis(100)) => None,
+            }
+        })
+    };
+
+    assert!(next().is_none());
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let event = next().unwrap();
+    assert_eq!(event.coalesced(), 1);
+
+    // Signals missed while not polled come as one item.
+    unsafe {
+        platform::raise_ctrl_c();
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    let event = next().unwrap();
+    assert_eq!(event.coalesced(), 2);
+    assert_eq!(event.count(), 3);
+    #[cfg(unix)]
+    assert_eq!(event.signal(), Some(ctrlc::Signal::SIGINT));
+    assert!(next().is_none());
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+#[cfg(feature = "crossbeam")]
+pub fn test_channel_crossbeam() {
+    ctrlc::set_handler(|| {}).unwrap();
+    let signals = ctrlc::channel_crossbeam();
+    let dropped = ctrlc::channel_crossbeam();
+    drop(dropped);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let event = crossbeam_channel::select! {
+        recv(signals) -> event => event.unwrap(),
+        default(Duration::from_secs(10)) => panic!("No signal received"),
+    };
+    #[cfg(unix)]
+    assert_eq!(event.signal(), Some(ctrlc::Signal::SIGINT));
+    #[cfg(windows)]
+    assert!(event.signal().is_some());
+    assert!(signals.try_recv().is_err());
+
+    ctrlc::remove_all_handlers().unwrap();
+}
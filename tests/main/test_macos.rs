@@ -0,0 +1,49 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(all(target_os = "macos", feature = "macos-app"))]
+
+use ctrlc::macos::{application_should_terminate, workspace_will_power_off, TerminateReply};
+use ctrlc::{ShutdownController, ShutdownReason};
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub fn test_terminate_reply() {
+    // Nothing to shut down, the application can terminate right away.
+    assert_eq!(application_should_terminate(), TerminateReply::Now);
+    assert_eq!(TerminateReply::Now as usize, 1);
+
+    let (tx, rx) = mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = tx.send(ShutdownController::new().reason());
+    })
+    .unwrap();
+
+    assert_eq!(application_should_terminate(), TerminateReply::Later);
+    assert_eq!(TerminateReply::Later as usize, 2);
+    let reason = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(
+        reason,
+        Some(ShutdownReason::Requested(
+            "applicationShouldTerminate".to_string()
+        ))
+    );
+
+    // Runs the handler again, the reason of the first shutdown is kept.
+    workspace_will_power_off();
+    let reason = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(
+        reason,
+        Some(ShutdownReason::Requested(
+            "applicationShouldTerminate".to_string()
+        ))
+    );
+
+    ctrlc::remove_all_handlers().unwrap();
+}
@@ -0,0 +1,27 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(all(unix, ctrlc_channel = "pipe", not(ctrlc_eventfd)))]
+
+use crate::harness::platform;
+
+pub fn test_missed_signals() {
+    // Nobody reads the pipe of a prearmed handler, it fills up after at most 64 KiB.
+    ctrlc::prearm().unwrap();
+    let missed = ctrlc::missed_signals();
+    for _ in 0..(1 << 17) {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    }
+    assert!(ctrlc::missed_signals() > missed);
+
+    ctrlc::set_handler(|| {}).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+}
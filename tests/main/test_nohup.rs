@@ -0,0 +1,48 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(unix)]
+
+use crate::harness::platform;
+use ctrlc::{Signal, SignalType};
+use nix::sys::signal::{
+    raise, sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal as NixSignal,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_nohup() {
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .signals([SignalType::Ctrlc, SignalType::Termination])
+        .nohup(true)
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+    assert!(!ctrlc::handlers()[0].signals().contains(&Signal::SIGHUP));
+
+    unsafe {
+        raise(NixSignal::SIGHUP).unwrap();
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    ctrlc::remove_all_handlers().unwrap();
+    let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    let previous = unsafe { sigaction(NixSignal::SIGHUP, &default).unwrap() };
+    assert_eq!(previous.handler(), SigHandler::SigIgn);
+}
@@ -0,0 +1,116 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(all(unix, ctrlc_channel = "pipe"))]
+
+use crate::harness::platform;
+use nix::libc;
+use std::os::fd::AsRawFd;
+
+/// Whether `notifier` is readable within `timeout` milliseconds.
+fn readable(notifier: &ctrlc::unix::SignalNotifier, timeout: libc::c_int) -> bool {
+    let mut poll = libc::pollfd {
+        fd: notifier.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe { libc::poll(&mut poll, 1, timeout) == 1 }
+}
+
+pub fn test_signal_notifier() {
+    let notifier = ctrlc::unix::SignalNotifier::new(&ctrlc::default_signals(), true).unwrap();
+    assert!(!readable(&notifier, 0));
+
+    unsafe {
+        platform::raise_ctrl_c();
+        platform::raise_ctrl_c();
+    }
+    assert!(readable(&notifier, 10_000));
+    let event = notifier.try_recv().unwrap().unwrap();
+    assert_eq!(event.signal(), Some(ctrlc::Signal::SIGINT));
+    // Level-triggered, the second signal keeps it readable.
+    assert!(readable(&notifier, 0));
+    assert!(notifier.try_recv().unwrap().is_some());
+    assert!(!readable(&notifier, 0));
+    assert!(notifier.try_recv().unwrap().is_none());
+
+    drop(notifier);
+    assert!(ctrlc::handlers().is_empty());
+}
+
+#[cfg(feature = "mio")]
+pub fn test_mio_notifier() {
+    use std::time::Duration;
+
+    let mut notifier = ctrlc::unix::SignalNotifier::new(&ctrlc::default_signals(), true).unwrap();
+    let mut poll = mio::Poll::new().unwrap();
+    let mut events = mio::Events::with_capacity(4);
+    poll.registry()
+        .register(&mut notifier, mio::Token(7), mio::Interest::READABLE)
+        .unwrap();
+    poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+    assert!(events.is_empty());
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    poll.poll(&mut events, Some(Duration::from_secs(10)))
+        .unwrap();
+    let tokens: Vec<_> = events.iter().map(|event| event.token()).collect();
+    assert_eq!(tokens, [mio::Token(7)]);
+    assert!(notifier.try_recv().unwrap().is_some());
+    assert!(notifier.try_recv().unwrap().is_none());
+
+    poll.registry().deregister(&mut notifier).unwrap();
+    drop(notifier);
+    assert!(ctrlc::handlers().is_empty());
+}
+
+#[cfg(feature = "tokio")]
+pub fn test_async_ctrl_c() {
+    use std::time::Duration;
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let ctrl_c = ctrlc::async_io::ctrl_c();
+        tokio::pin!(ctrl_c);
+        // Polling it installs the handler.
+        let pending = tokio::time::timeout(Duration::from_millis(50), ctrl_c.as_mut()).await;
+        assert!(pending.is_err());
+        assert_eq!(ctrlc::handlers().len(), 1);
+
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        let event = tokio::time::timeout(Duration::from_secs(10), ctrl_c)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.signal(), Some(ctrlc::Signal::SIGINT));
+    });
+    assert!(ctrlc::handlers().is_empty());
+}
+
+#[cfg(feature = "async-io")]
+pub fn test_async_io_notifier() {
+    let notifier = ctrlc::async_io::AsyncNotifier::new(&ctrlc::default_signals(), true).unwrap();
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    unsafe {
+        platform::raise_ctrl_c();
+        platform::raise_ctrl_c();
+    }
+    for _ in 0..2 {
+        let event = async_io::block_on(notifier.recv()).unwrap();
+        assert_eq!(event.signal(), Some(ctrlc::Signal::SIGINT));
+    }
+
+    drop(notifier);
+    assert!(ctrlc::handlers().is_empty());
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub fn test_orchestrator_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut shutdown = ctrlc::Orchestrator::new();
+    let mut add = |name: &'static str, after: &[&str]| {
+        let log = log.clone();
+        shutdown
+            .add_task(name, after, move || log.lock().unwrap().push(name))
+            .map(|_| ())
+    };
+
+    // Dependencies may be registered after their dependents.
+    add("close-db", &["drain-connections"]).unwrap();
+    add("drain-connections", &["close-listeners"]).unwrap();
+    add("flush-metrics", &["close-listeners", "not-registered"]).unwrap();
+    add("close-listeners", &[]).unwrap();
+
+    match add("close-listeners", &[]) {
+        Err(ctrlc::Error::DuplicateTask(name)) => assert_eq!(name, "close-listeners"),
+        ret => panic!("Expected Error::DuplicateTask, got {:?}", ret),
+    }
+    match add("not-registered", &["flush-metrics"]) {
+        Err(ctrlc::Error::DependencyCycle(cycle)) => {
+            assert_eq!(cycle, ["not-registered", "flush-metrics"])
+        }
+        ret => panic!("Expected Error::DependencyCycle, got {:?}", ret),
+    }
+    match add("self", &["self"]) {
+        Err(ctrlc::Error::DependencyCycle(cycle)) => assert_eq!(cycle, ["self"]),
+        ret => panic!("Expected Error::DependencyCycle, got {:?}", ret),
+    }
+
+    let report = shutdown.run();
+    assert_eq!(report.tasks().len(), 4);
+    assert_eq!(report.timed_out().count(), 0);
+
+    let log = log.lock().unwrap();
+    let position = |name| log.iter().position(|&n| n == name).unwrap();
+    assert_eq!(log.len(), 4);
+    assert_eq!(position("close-listeners"), 0);
+    assert!(position("drain-connections") < position("close-db"));
+    assert!(position("flush-metrics") > 0);
+}
+
+pub fn test_orchestrator_timeout() {
+    let mut shutdown = ctrlc::Orchestrator::new();
+    shutdown
+        .add_task("stuck", &[], || {
+            std::thread::sleep(Duration::from_secs(3600))
+        })
+        .unwrap()
+        .timeout(Duration::from_millis(50), ctrlc::OnTimeout::Skip);
+    shutdown
+        .add_task("quick", &[], || {})
+        .unwrap()
+        .timeout(Duration::from_secs(3600), ctrlc::OnTimeout::Abort);
+    shutdown.add_task("after-stuck", &["stuck"], || {}).unwrap();
+
+    let report = shutdown.run();
+    assert!(report.elapsed() < Duration::from_secs(60));
+    let outcome = |name| report.task(name).unwrap().outcome();
+    assert_eq!(outcome("stuck"), ctrlc::TaskOutcome::TimedOut);
+    assert_eq!(outcome("quick"), ctrlc::TaskOutcome::Completed);
+    assert_eq!(outcome("after-stuck"), ctrlc::TaskOutcome::Completed);
+    assert!(report.task("stuck").unwrap().elapsed() >= Duration::from_millis(50));
+    assert_eq!(
+        report
+            .timed_out()
+            .map(|task| task.name())
+            .collect::<Vec<_>>(),
+        ["stuck"]
+    );
+}
+
+#[cfg(feature = "tokio")]
+pub fn test_orchestrator_async() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_time()
+        .build()
+        .unwrap();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let mut shutdown = ctrlc::Orchestrator::new();
+    shutdown
+        .add_async_task("close-pool", &[], runtime.handle(), {
+            let log = log.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                log.lock().unwrap().push("close-pool");
+            }
+        })
+        .unwrap();
+    shutdown
+        .add_task("close-db", &["close-pool"], {
+            let log = log.clone();
+            move || log.lock().unwrap().push("close-db")
+        })
+        .unwrap();
+
+    let report = shutdown.run();
+    assert_eq!(*log.lock().unwrap(), ["close-pool", "close-db"]);
+    assert_eq!(
+        report.task("close-pool").unwrap().outcome(),
+        ctrlc::TaskOutcome::Completed
+    );
+}
@@ -0,0 +1,24 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+
+pub fn test_prearm() {
+    ctrlc::prearm().unwrap();
+    ctrlc::prearm().unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let handle = ctrlc::set_handler_once(|| 42).unwrap();
+    assert_eq!(handle.join().unwrap(), Some(42));
+    assert!(ctrlc::handlers().is_empty());
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_kubernetes_preset() {
+    let handled = Arc::new(AtomicBool::new(false));
+    let builder = ctrlc::Builder::new()
+        .preset(ctrlc::Preset::Kubernetes {
+            drain_delay: Duration::from_millis(300),
+        })
+        .grace_period(Duration::from_secs(3600));
+    let readiness = builder.readiness();
+    builder
+        .set_handler({
+            let handled = handled.clone();
+            move || handled.store(true, Ordering::SeqCst)
+        })
+        .unwrap();
+    assert!(readiness.is_ready());
+    assert_eq!(ctrlc::grace_remaining(), None);
+
+    unsafe {
+        super::platform::raise_ctrl_c();
+    }
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!readiness.is_ready());
+    assert!(!handled.load(Ordering::SeqCst));
+    let remaining = ctrlc::grace_remaining().unwrap();
+    assert!(remaining > Duration::from_secs(3590) && remaining < Duration::from_secs(3600));
+
+    std::thread::sleep(Duration::from_millis(400));
+    assert!(handled.load(Ordering::SeqCst));
+
+    ctrlc::remove_all_handlers().unwrap();
+}
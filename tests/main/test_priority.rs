@@ -0,0 +1,61 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use crate::harness::platform;
+use ctrlc::ThreadPriority;
+use nix::libc;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Nice value and CPUs of the current thread.
+fn scheduling() -> (i32, Vec<usize>) {
+    unsafe {
+        let nice = libc::getpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t);
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        let size = std::mem::size_of::<libc::cpu_set_t>();
+        assert_eq!(libc::sched_getaffinity(0, size, &mut set), 0);
+        let cpus = (0..libc::CPU_SETSIZE as usize)
+            .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+            .collect();
+        (nice, cpus)
+    }
+}
+
+pub fn test_thread_scheduling() {
+    let (nice, cpus) = scheduling();
+    // Lowering the nice value needs CAP_SYS_NICE or a high enough RLIMIT_NICE, without them
+    // the handler threads keep the nice value they inherited.
+    let privileged = std::thread::spawn(|| unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, -5) == 0
+    })
+    .join()
+    .unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    ctrlc::Builder::new()
+        .thread_priority(ThreadPriority::AboveNormal)
+        .thread_affinity([cpus[0]])
+        .set_handler(move || {
+            let _ = tx.send(scheduling());
+        })
+        .unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let (handler_nice, handler_cpus) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(handler_nice, if privileged { -5 } else { nice });
+    assert_eq!(handler_cpus, [cpus[0]]);
+    // The thread registering the handler is left alone.
+    assert_eq!(scheduling(), (nice, cpus));
+
+    ctrlc::remove_all_handlers().unwrap();
+}
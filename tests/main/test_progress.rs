@@ -0,0 +1,36 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::{Arc, Mutex};
+
+pub fn test_progress_reporting() {
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    ctrlc::on_progress({
+        let updates = updates.clone();
+        move |progress| updates.lock().unwrap().push(progress.to_string())
+    });
+
+    let storage = ctrlc::ProgressReporter::new("storage");
+    let network = ctrlc::ProgressReporter::new("network");
+    storage.step("flushing WAL", 3, 7);
+    network.finish();
+
+    let snapshot = ctrlc::progress();
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[0].task(), "storage");
+    assert_eq!(snapshot[0].step(), "flushing WAL");
+    assert_eq!((snapshot[0].current(), snapshot[0].total()), (3, 7));
+    assert!(!snapshot[0].is_finished());
+    assert!(snapshot[1].is_finished());
+
+    assert_eq!(
+        *updates.lock().unwrap(),
+        ["storage: flushing WAL (3/7)", "network: done"]
+    );
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(all(unix, feature = "signal-hook-registry"))]
+
+use ctrlc::{InstallPolicy, Signal, SignalType};
+use nix::sys::signal::{raise, Signal as NixSignal};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+pub fn test_registry() {
+    let hook = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&hook)).unwrap();
+
+    // Registering through the registry doesn't conflict with signal-hook.
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::Builder::new()
+        .signals([SignalType::Other(Signal::SIGUSR1)])
+        .install_policy(InstallPolicy::ErrorIfExists)
+        .set_handler({
+            let count = count.clone();
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert!(hook.swap(false, Ordering::SeqCst));
+    ctrlc::remove_all_handlers().unwrap();
+
+    // signal-hook keeps working after the handler is removed.
+    raise(NixSignal::SIGUSR1).unwrap();
+    assert!(hook.load(Ordering::SeqCst));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,143 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc,
+};
+use std::time::Duration;
+
+fn counting_handler() -> Arc<AtomicUsize> {
+    let count = Arc::new(AtomicUsize::new(0));
+    ctrlc::set_handler({
+        let count = count.clone();
+        move || {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+    })
+    .unwrap();
+    count
+}
+
+pub fn test_reinit() {
+    for _ in 0..3 {
+        let count = counting_handler();
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        ctrlc::remove_all_handlers().unwrap();
+    }
+
+    // A waiter still busy when its handler is removed must not take the signals of the next one.
+    ctrlc::set_handler(|| {}).unwrap();
+    let (release, released) = mpsc::channel::<()>();
+    let _guard = ctrlc::push_consumer(move || {
+        let _ = released.recv();
+    });
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    ctrlc::remove_all_handlers().unwrap();
+
+    let count = counting_handler();
+    release.send(()).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    for _ in 0..4 {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_remove_wakes_waiter() {
+    let waiter = ctrlc::set_handler(|| {}).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    ctrlc::remove_all_handlers().unwrap();
+
+    // Take whatever the removed handler freed, so a reused descriptor can't keep it waiting.
+    let reused: Vec<_> = (0..4)
+        .map(|_| std::fs::File::open("Cargo.toml").unwrap())
+        .collect();
+    for _ in 0..50 {
+        if waiter.is_finished() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert!(waiter.is_finished());
+    waiter.join().unwrap();
+    drop(reused);
+}
+
+pub fn test_reinit_resets_shutdown() {
+    let (tx, rx) = mpsc::channel();
+    ctrlc::Builder::new()
+        .grace_period(Duration::from_secs(60))
+        .set_handler(move || tx.send(()).unwrap())
+        .unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    let token = ctrlc::ShutdownToken::new();
+    assert!(token.is_cancelled());
+    assert!(ctrlc::grace_remaining().is_some());
+    ctrlc::remove_all_handlers().unwrap();
+
+    let count = counting_handler();
+    assert!(!token.is_cancelled());
+    assert!(!ctrlc::shutdown().is_started());
+    assert_eq!(ctrlc::lifecycle(), ctrlc::LifecycleState::Running);
+    assert_eq!(ctrlc::grace_remaining(), None);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_reinit_concurrent() {
+    use std::sync::atomic::AtomicBool;
+
+    // Delivering while the handler is set and removed on another thread must neither write to a
+    // closed pipe nor keep a waiter of a removed handler reading.
+    let done = Arc::new(AtomicBool::new(false));
+    let deliver = std::thread::spawn({
+        let done = done.clone();
+        move || {
+            let mut delivered = 0;
+            while !done.load(Ordering::SeqCst) {
+                match ctrlc::external::deliver(ctrlc::SignalType::Ctrlc) {
+                    Ok(_) => delivered += 1,
+                    Err(ctrlc::Error::HandlerRemoved) => {}
+                    Err(e) => panic!("Unexpected error delivering: {:?}", e),
+                }
+            }
+            delivered
+        }
+    });
+
+    for _ in 0..200 {
+        let waiter = ctrlc::Builder::new()
+            .external(true)
+            .set_handler(|| {})
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        ctrlc::remove_all_handlers().unwrap();
+        waiter.join().unwrap();
+    }
+    done.store(true, Ordering::SeqCst);
+    assert!(deliver.join().unwrap() > 0);
+}
@@ -0,0 +1,254 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(unix)]
+
+use crate::harness::platform;
+use ctrlc::Signal;
+use nix::sys::signal::{raise, Signal as NixSignal};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+fn counter() -> (Arc<AtomicUsize>, impl FnMut() + Send + 'static) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let handler = {
+        let count = count.clone();
+        move || {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+    };
+    (count, handler)
+}
+
+pub fn test_on_signal() {
+    // Without a handler, the first route registers one.
+    let (usr1, handler) = counter();
+    ctrlc::on(Signal::SIGUSR1, handler).unwrap();
+    let (usr2, handler) = counter();
+    ctrlc::on(Signal::SIGUSR2, handler).unwrap();
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    raise(NixSignal::SIGUSR2).unwrap();
+    raise(NixSignal::SIGUSR2).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(usr1.load(Ordering::SeqCst), 1);
+    assert_eq!(usr2.load(Ordering::SeqCst), 2);
+    ctrlc::remove_all_handlers().unwrap();
+
+    // Routed signals bypass the main handler.
+    let (main, handler) = counter();
+    ctrlc::set_handler(handler).unwrap();
+    let (usr1, handler) = counter();
+    ctrlc::on(Signal::SIGUSR1, handler).unwrap();
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(usr1.load(Ordering::SeqCst), 1);
+    assert_eq!(main.load(Ordering::SeqCst), 0);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_add_handler() {
+    let (main, handler) = counter();
+    ctrlc::set_handler(handler).unwrap();
+    let (first, handler) = counter();
+    let first_id = ctrlc::add_handler(handler).unwrap();
+    let (second, handler) = counter();
+    ctrlc::add_handler(handler).unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(main.load(Ordering::SeqCst), 1);
+    assert_eq!(first.load(Ordering::SeqCst), 1);
+    assert_eq!(second.load(Ordering::SeqCst), 1);
+
+    assert!(ctrlc::remove(first_id));
+    assert!(!ctrlc::remove(first_id));
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(main.load(Ordering::SeqCst), 2);
+    assert_eq!(first.load(Ordering::SeqCst), 1);
+    assert_eq!(second.load(Ordering::SeqCst), 2);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_remove_route() {
+    let (usr1, handler) = counter();
+    let id = ctrlc::on(Signal::SIGUSR1, handler).unwrap();
+    ctrlc::set_handler(|| {}).unwrap_err();
+    assert_eq!(ctrlc::handlers()[0].signals(), [Signal::SIGUSR1]);
+
+    // The signal was only installed for the route.
+    assert!(ctrlc::remove(id));
+    assert!(ctrlc::handlers()[0].signals().is_empty());
+    assert_eq!(usr1.load(Ordering::SeqCst), 0);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_on_before_set_handler() {
+    // The handler registered for the route is not replaced by the main handler.
+    let (usr1, handler) = counter();
+    ctrlc::on(Signal::SIGUSR1, handler).unwrap();
+    assert!(matches!(
+        ctrlc::set_handler(|| {}),
+        Err(ctrlc::Error::MultipleHandlers)
+    ));
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(usr1.load(Ordering::SeqCst), 1);
+    ctrlc::remove_all_handlers().unwrap();
+
+    // Registered first, the main handler takes the route.
+    let (main, handler) = counter();
+    ctrlc::set_handler(handler).unwrap();
+    let (usr1, handler) = counter();
+    ctrlc::on(Signal::SIGUSR1, handler).unwrap();
+    assert_eq!(ctrlc::handlers().len(), 1);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    raise(NixSignal::SIGUSR1).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(main.load(Ordering::SeqCst), 1);
+    assert_eq!(usr1.load(Ordering::SeqCst), 1);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_on_usr() {
+    let (main, handler) = counter();
+    ctrlc::set_handler(handler).unwrap();
+    let (usr1, handler) = counter();
+    ctrlc::unix::on_usr1(handler).unwrap();
+    let (usr2, handler) = counter();
+    ctrlc::unix::on_usr2(handler).unwrap();
+
+    raise(NixSignal::SIGUSR1).unwrap();
+    raise(NixSignal::SIGUSR2).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(usr1.load(Ordering::SeqCst), 1);
+    assert_eq!(usr2.load(Ordering::SeqCst), 1);
+    assert_eq!(main.load(Ordering::SeqCst), 0);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_on_reload() {
+    let (main, handler) = counter();
+    ctrlc::Builder::new()
+        .signals([ctrlc::SignalType::Ctrlc, ctrlc::SignalType::Termination])
+        .set_handler(handler)
+        .unwrap();
+    let (reload, handler) = counter();
+    let id = ctrlc::unix::on_reload(handler).unwrap();
+
+    raise(NixSignal::SIGHUP).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(reload.load(Ordering::SeqCst), 1);
+    assert_eq!(main.load(Ordering::SeqCst), 0);
+
+    // Without the reload handler, SIGHUP is a termination signal again.
+    assert!(ctrlc::remove(id));
+    raise(NixSignal::SIGHUP).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(reload.load(Ordering::SeqCst), 1);
+    assert_eq!(main.load(Ordering::SeqCst), 1);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_reap_children() {
+    let (exited, handler) = counter();
+    ctrlc::unix::on_child_exit(handler).unwrap();
+
+    let mut child = std::process::Command::new("sh")
+        .args(["-c", "exit 3"])
+        .spawn()
+        .unwrap();
+    let start = std::time::Instant::now();
+    while exited.load(Ordering::SeqCst) == 0 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let reaped = ctrlc::unix::reap_children();
+    assert_eq!(reaped.len(), 1);
+    assert_eq!(reaped[0].0, child.id());
+    assert_eq!(reaped[0].1.code(), Some(3));
+    assert!(ctrlc::unix::reap_children().is_empty());
+    // Already reaped.
+    assert!(child.wait().is_err());
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub fn test_on_status_request() {
+    let (main, handler) = counter();
+    ctrlc::set_handler(handler).unwrap();
+    let (status, handler) = counter();
+    ctrlc::unix::on_status_request(handler).unwrap();
+
+    raise(NixSignal::SIGINFO).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(status.load(Ordering::SeqCst), 1);
+    assert_eq!(main.load(Ordering::SeqCst), 0);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn test_on_realtime() {
+    use nix::libc;
+    use std::sync::Mutex;
+
+    match ctrlc::unix::on_realtime(libc::SIGRTMAX() - libc::SIGRTMIN() + 1, |_| {}) {
+        Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+        ret => panic!("{:?}", ret),
+    }
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    ctrlc::unix::on_realtime(2, {
+        let received = received.clone();
+        move |signal| {
+            received
+                .lock()
+                .unwrap()
+                .push((signal.offset(), signal.int()))
+        }
+    })
+    .unwrap();
+
+    // Real-time signals are queued, so none of them is lost.
+    for task in 1..=3 {
+        let value = libc::sigval {
+            sival_ptr: task as usize as *mut libc::c_void,
+        };
+        let ret = unsafe { libc::sigqueue(libc::getpid(), libc::SIGRTMIN() + 2, value) };
+        assert_eq!(ret, 0);
+    }
+    let start = std::time::Instant::now();
+    while received.lock().unwrap().len() < 3 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(*received.lock().unwrap(), [(2, 1), (2, 2), (2, 3)]);
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(ctrlc_channel = "semaphore")]
+
+use crate::harness::platform;
+use ctrlc::{Signal, SignalType};
+use nix::sys::signal::{raise, Signal as NixSignal};
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub fn test_semaphore_channel() {
+    // The semaphore only counts the wakeups, each one still reports its own signal.
+    let (tx, rx) = mpsc::channel();
+    ctrlc::Builder::new()
+        .signals([SignalType::Ctrlc, SignalType::Other(Signal::SIGUSR1)])
+        .set_handler_with_signal(move |signal| tx.send(signal).unwrap())
+        .unwrap();
+    raise(NixSignal::SIGUSR1).unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let mut received = [
+        rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+        rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+    ];
+    received.sort_by_key(|signal| *signal == Signal::CtrlC);
+    assert_eq!(received, [Signal::User1, Signal::CtrlC]);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_semaphore_reinit() {
+    // The semaphore is kept for the next handlers, the waiters of a removed one are woken up
+    // through it as well and must leave the wakeups of the next one alone.
+    let mut removed = None;
+    for _ in 0..100 {
+        let (tx, rx) = mpsc::channel();
+        let waiter = ctrlc::set_handler(move || tx.send(()).unwrap()).unwrap();
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        if let Some(removed) = removed.replace(waiter) {
+            removed.join().unwrap();
+        }
+        ctrlc::remove_all_handlers().unwrap();
+    }
+    removed.unwrap().join().unwrap();
+}
@@ -0,0 +1,46 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(feature = "serde")]
+
+use ctrlc::{ShutdownReason, Signal, SignalType};
+
+pub fn test_serde() {
+    let kinds = [SignalType::Ctrlc, SignalType::Termination];
+    let json = serde_json::to_string(&kinds).unwrap();
+    assert_eq!(json, r#"["Ctrlc","Termination"]"#);
+    assert_eq!(
+        serde_json::from_str::<Vec<SignalType>>(&json).unwrap(),
+        kinds
+    );
+
+    #[cfg(unix)]
+    {
+        let other = SignalType::Other(Signal::SIGUSR1);
+        let json = serde_json::to_string(&other).unwrap();
+        assert_eq!(json, r#"{"Other":"SIGUSR1"}"#);
+        assert_eq!(serde_json::from_str::<SignalType>(&json).unwrap(), other);
+    }
+    assert!(serde_json::from_str::<SignalType>(r#"{"Other":"NOPE"}"#).is_err());
+
+    let reason = ShutdownReason::Requested("maintenance".to_string());
+    let json = serde_json::to_string(&reason).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ShutdownReason>(&json).unwrap(),
+        reason
+    );
+}
+
+pub fn test_serde_signal() {
+    let signals = [Signal::CtrlC, Signal::User1, Signal::Other(64)];
+    let json = serde_json::to_string(&signals).unwrap();
+    assert_eq!(json, r#"["CtrlC","User1",{"Other":64}]"#);
+    assert_eq!(serde_json::from_str::<Vec<Signal>>(&json).unwrap(), signals);
+    assert!(serde_json::from_str::<Signal>(r#""Nope""#).is_err());
+}
@@ -0,0 +1,33 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use ctrlc::{ShutdownController, ShutdownReason};
+
+pub fn test_shutdown_controller() {
+    let controller = ShutdownController::new();
+    assert!(matches!(
+        controller.shutdown("too early"),
+        Err(ctrlc::Error::HandlerRemoved)
+    ));
+    assert_eq!(controller.reason(), None);
+
+    let handle = ctrlc::set_handler_once({
+        let controller = controller.clone();
+        move || controller.reason()
+    })
+    .unwrap();
+    controller.shutdown("fatal error").unwrap();
+
+    let reason = handle.join().unwrap().unwrap();
+    assert_eq!(
+        reason,
+        Some(ShutdownReason::Requested("fatal error".to_string()))
+    );
+    assert_eq!(controller.reason(), None);
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use ctrlc::{Signal, SignalType};
+
+pub fn test_signal_info() {
+    let all: Vec<_> = SignalType::all().collect();
+    let find = |name: &str| all.iter().find(|info| info.name() == name);
+
+    #[cfg(unix)]
+    {
+        assert!(find("SIGINT").unwrap().is_termination());
+        assert!(find("SIGTERM").unwrap().is_termination());
+        assert!(!find("SIGUSR1").unwrap().is_termination());
+        assert!(find("SIGKILL").is_none());
+        assert_eq!(find("SIGINT").unwrap().exit_code(), 130);
+        assert_eq!(find("SIGTERM").unwrap().exit_code(), 143);
+        assert_eq!(find("SIGHUP").unwrap().signal(), Signal::Hangup);
+    }
+
+    #[cfg(windows)]
+    {
+        assert_eq!(all.len(), 5);
+        assert!(find("CTRL_C_EVENT").unwrap().is_termination());
+        assert!(find("CTRL_CLOSE_EVENT").unwrap().is_termination());
+        assert_eq!(find("CTRL_C_EVENT").unwrap().exit_code(), 130);
+        assert_eq!(find("CTRL_CLOSE_EVENT").unwrap().exit_code(), 143);
+    }
+}
+
+pub fn test_portable_signal() {
+    let signals: Vec<_> = SignalType::all().map(|info| info.signal()).collect();
+    assert!(signals.contains(&Signal::CtrlC));
+    assert!(signals.contains(&Signal::Terminate));
+    assert_eq!(Signal::CtrlC.as_raw(), Some(2));
+
+    #[cfg(unix)]
+    {
+        assert_eq!(Signal::SIGINT, Signal::CtrlC);
+        assert_eq!(Signal::SIGQUIT, Signal::Break);
+        assert_eq!(Signal::SIGWINCH.as_raw(), Some(nix::libc::SIGWINCH));
+        assert_eq!(
+            Signal::Other(nix::libc::SIGHUP).as_raw(),
+            Signal::Hangup.as_raw()
+        );
+        assert_eq!(Signal::Logoff.as_raw(), None);
+        assert!(signals.contains(&Signal::User1));
+        assert!(!signals.contains(&Signal::Other(nix::libc::SIGUSR1)));
+    }
+
+    #[cfg(windows)]
+    {
+        assert!(signals.contains(&Signal::Break));
+        assert!(signals.contains(&Signal::Shutdown));
+        assert_eq!(Signal::Hangup.as_raw(), None);
+    }
+}
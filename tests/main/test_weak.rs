@@ -0,0 +1,38 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc,
+};
+
+pub fn test_register_weak() {
+    let alive = Arc::new(AtomicU32::new(0));
+    ctrlc::register_weak(&alive, |calls| {
+        calls.fetch_add(1, Ordering::SeqCst);
+    });
+
+    let dropped_called = Arc::new(AtomicBool::new(false));
+    let dropped = Arc::new(());
+    ctrlc::register_weak(&dropped, {
+        let dropped_called = dropped_called.clone();
+        move |_| dropped_called.store(true, Ordering::SeqCst)
+    });
+    drop(dropped);
+
+    let handle = ctrlc::set_handler_once(|| {}).unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    handle.join().unwrap().unwrap();
+
+    assert_eq!(alive.load(Ordering::SeqCst), 1);
+    assert!(!dropped_called.load(Ordering::SeqCst));
+}
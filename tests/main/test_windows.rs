@@ -0,0 +1,70 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![cfg(windows)]
+
+use crate::harness::platform;
+use std::ptr;
+use std::sync::mpsc;
+use std::time::Duration;
+use windows_sys::Win32::Foundation::{GENERIC_READ, GENERIC_WRITE, HANDLE, INVALID_HANDLE_VALUE};
+use windows_sys::Win32::Storage::FileSystem::{CreateFileA, FILE_SHARE_READ, OPEN_EXISTING};
+use windows_sys::Win32::System::Console::{
+    GetConsoleMode, SetStdHandle, ENABLE_PROCESSED_INPUT, STD_INPUT_HANDLE,
+};
+
+pub fn test_ignore_ctrl_c() {
+    let (tx, rx) = mpsc::channel();
+    ctrlc::set_handler(move || tx.send(()).unwrap()).unwrap();
+
+    let guard = ctrlc::windows::ignore_ctrl_c().unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+    drop(guard);
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+pub fn test_literal_ctrl_c() {
+    // The console of the harness, whatever stdin the test was started with.
+    let input = unsafe {
+        CreateFileA(
+            "CONIN$\0".as_ptr(),
+            GENERIC_READ | GENERIC_WRITE,
+            FILE_SHARE_READ,
+            ptr::null_mut(),
+            OPEN_EXISTING,
+            0,
+            0 as HANDLE,
+        )
+    };
+    assert!(!input.is_null() && input != INVALID_HANDLE_VALUE);
+    assert!(unsafe { SetStdHandle(STD_INPUT_HANDLE, input) } != 0);
+
+    let mode = || {
+        let mut mode = 0u32;
+        assert!(unsafe { GetConsoleMode(input, &mut mode) } != 0);
+        mode
+    };
+    let before = mode();
+    assert_ne!(before & ENABLE_PROCESSED_INPUT, 0);
+
+    let guard = ctrlc::windows::literal_ctrl_c().unwrap();
+    assert_eq!(mode(), before & !ENABLE_PROCESSED_INPUT);
+
+    drop(guard);
+    assert_eq!(mode(), before);
+}
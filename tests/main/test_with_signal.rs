@@ -0,0 +1,90 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::harness::platform;
+use ctrlc::SignalType;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[cfg(unix)]
+const CTRL_C: ctrlc::Signal = ctrlc::Signal::SIGINT;
+#[cfg(unix)]
+const TERMINATION: ctrlc::Signal = ctrlc::Signal::SIGTERM;
+#[cfg(windows)]
+const CTRL_C: ctrlc::Signal = ctrlc::Signal::CtrlC;
+#[cfg(windows)]
+const TERMINATION: ctrlc::Signal = ctrlc::Signal::Terminate; // CTRL_CLOSE_EVENT
+
+pub fn test_handler_with_signal() {
+    let (tx, rx) = mpsc::channel();
+    ctrlc::set_handler_with_signal({
+        let tx = tx.clone();
+        move |signal| tx.send(signal).unwrap()
+    })
+    .unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), CTRL_C);
+    ctrlc::remove_all_handlers().unwrap();
+
+    // Signals delivered by the host runtime keep their identity too.
+    ctrlc::Builder::new()
+        .external(true)
+        .signals([SignalType::Ctrlc, SignalType::Termination])
+        .set_handler_with_signal(move |signal| tx.send(signal).unwrap())
+        .unwrap();
+
+    assert!(ctrlc::external::deliver(SignalType::Termination).unwrap());
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+        TERMINATION
+    );
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+#[cfg(unix)]
+pub fn test_signal_burst() {
+    use ctrlc::Signal;
+    use nix::sys::signal::{raise, Signal as NixSignal};
+
+    let (tx, rx) = mpsc::channel();
+    ctrlc::Builder::new()
+        .signals([SignalType::Ctrlc, SignalType::Other(Signal::SIGUSR1)])
+        .set_handler_with_signal(move |signal| tx.send(signal).unwrap())
+        .unwrap();
+
+    // Raised faster than they are handled, every one of them is still reported as itself.
+    let raised = [
+        Signal::SIGUSR1,
+        Signal::SIGINT,
+        Signal::SIGUSR1,
+        Signal::SIGINT,
+        Signal::SIGUSR1,
+    ];
+    for signal in raised {
+        raise(NixSignal::try_from(signal.as_raw().unwrap()).unwrap()).unwrap();
+    }
+    let mut received: Vec<_> = (0..raised.len())
+        .map(|_| rx.recv_timeout(Duration::from_secs(1)).unwrap())
+        .collect();
+    received.sort_by_key(|signal| signal.as_raw());
+    assert_eq!(
+        received,
+        [
+            Signal::SIGINT,
+            Signal::SIGINT,
+            Signal::SIGUSR1,
+            Signal::SIGUSR1,
+            Signal::SIGUSR1
+        ]
+    );
+    ctrlc::remove_all_handlers().unwrap();
+}